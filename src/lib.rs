@@ -2,7 +2,7 @@
 //! 
 //! ## Features
 //! - Abstractions for Minlp, Nlp, Milp, and Lp optimization problems.
-//! - Interfaces for COIN-OR optimization solvers Cbc, Clp, and Ipopt.
+//! - Interfaces for COIN-OR optimization solvers Cbc, Clp, and Ipopt, for GLPK, HiGHS, and SCIP, and for AMPL-compatible solver executables.
 //! - Modeling tools with automatic sparse first- and second-order derivatives.
 
 pub mod problem;
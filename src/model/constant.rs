@@ -1,9 +1,8 @@
 //! Constant expression.
 
 use std::fmt;
-use std::rc::Rc;
 
-use super::node::Node;
+use super::node::{Node, NodeRc};
 use super::node_base::NodeBase;
 
 /// Constant scalar.
@@ -15,7 +14,7 @@ impl ConstantScalar {
 
     /// Creates new scalar constant expression node.
     pub fn new(value: f64) -> Node {
-        Node::ConstantScalar(Rc::new(
+        Node::ConstantScalar(NodeRc::new(
             Self {
                 value: value,
             }
@@ -5,15 +5,15 @@
 
 use std::fmt;
 use std::ptr;
-use std::rc::Rc;
 use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
+use simple_error::SimpleError;
 
-use super::node::Node;
+use super::node::{Node, NodeRc};
 use super::node_base::NodeBase;
 
 /// Constraint kind.
-#[derive(PartialEq)]
+#[derive(Clone, Copy, PartialEq)]
 pub enum ConstraintKind {
     Equal,
     LessEqual,
@@ -26,10 +26,11 @@ struct ConstraintInner {
     kind: ConstraintKind,
     rhs: Node,
     label: String,
+    penalty: Option<f64>,
 }
 
 /// Constraint.
-pub struct Constraint(Rc<ConstraintInner>);
+pub struct Constraint(NodeRc<ConstraintInner>);
 
 impl Constraint {
 
@@ -39,24 +40,51 @@ impl Constraint {
     /// Gets constraint label.
     pub fn label(&self) -> &str { self.0.label.as_ref() }
 
+    /// Checks whether constraint is soft, i.e., whether it may be violated
+    /// at the expense of a penalty in the objective function.
+    pub fn is_soft(&self) -> bool { self.0.penalty.is_some() }
+
     /// Gets constraint left-hand-side.
     pub fn lhs(&self) -> &Node { &self.0.lhs }
 
     /// Creates new constraint.
     pub fn new(lhs: Node, kind: ConstraintKind, rhs: Node, label: &str) -> Constraint {
-        Constraint(Rc::new(
+        Constraint(NodeRc::new(
             ConstraintInner{
                 lhs: lhs,
                 kind: kind,
                 rhs: rhs,
                 label: String::from(label),
+                penalty: None,
             }
         ))
     }
 
+    /// Gets weight of the penalty of violations of the constraint, if soft.
+    pub fn penalty(&self) -> Option<f64> { self.0.penalty }
+
     /// Gets constraint right-hand-side.
     pub fn rhs(&self) -> &Node { &self.0.rhs }
 
+    /// Creates soft version of constraint, with the same expressions and label,
+    /// whose violations are penalized in the objective function with the given weight.
+    /// The penalty is the weight times the absolute violation of the constraint.
+    /// It fails if the weight is negative or not finite.
+    pub fn soft(&self, weight: f64) -> Result<Constraint, SimpleError> {
+        if !(weight >= 0. && weight.is_finite()) {
+            return Err(SimpleError::new("invalid penalty weight"));
+        }
+        Ok(Constraint(NodeRc::new(
+            ConstraintInner{
+                lhs: self.0.lhs.clone(),
+                kind: self.0.kind,
+                rhs: self.0.rhs.clone(),
+                label: self.0.label.clone(),
+                penalty: Some(weight),
+            }
+        )))
+    }
+
     /// Computes constraint violation given variable values.
     pub fn violation(&self, var_values: &HashMap<&Node, f64>) -> f64 {
         match self.0.kind {
@@ -75,14 +103,14 @@ impl Constraint {
 
 impl Clone for Constraint {
     fn clone(&self) -> Self {
-        Constraint(Rc::clone(&self.0))
+        Constraint(NodeRc::clone(&self.0))
     }
 }
 
 impl PartialEq for Constraint {
 
     fn eq(&self, other: &Self) -> bool {
-        Rc::ptr_eq(&self.0, &other.0)
+        NodeRc::ptr_eq(&self.0, &other.0)
     }
 }
 
@@ -185,6 +213,30 @@ mod tests {
         let z5 = Constraint::new(x.clone(), ConstraintKind::GreaterEqual, -c4.clone(), "foo");
         assert_eq!(z5.violation(&var_values), 0.);
     }
-}
 
+    #[test]
+    fn constr_soft() {
 
+        let x = VariableScalar::new_continuous("x");
+        let c = ConstantScalar::new(4.);
+
+        let z = Constraint::new(x.clone(), ConstraintKind::LessEqual, c, "foo");
+        assert!(!z.is_soft());
+        assert_eq!(z.penalty(), None);
+
+        let zs = z.soft(10.).unwrap();
+        assert!(zs.is_soft());
+        assert_eq!(zs.penalty(), Some(10.));
+        assert_eq!(zs.label(), "foo");
+        assert!(*zs.kind() == ConstraintKind::LessEqual);
+        assert_ne!(zs, z);
+        assert_eq!(format!("{}", zs), format!("{}", z));
+
+        let var_values = hashmap!{ &x => 7. };
+        assert_eq!(zs.violation(&var_values), 3.);
+
+        assert_eq!(format!("{}", z.soft(-1.).err().unwrap()), "invalid penalty weight");
+        assert!(z.soft(f64::NAN).is_err());
+        assert!(z.soft(f64::INFINITY).is_err());
+    }
+}
@@ -7,6 +7,7 @@ use crate::model::node_std::NodeStd;
 use crate::model::node_std::NodeStdProp;
 use crate::model::constant::ConstantScalar;
 use crate::model::variable::VariableScalar;
+use crate::model::function::add::FunctionAdd;
 use crate::model::constraint::{Constraint, ConstraintKind};
 
 /// Constraint standard components.
@@ -39,6 +40,10 @@ pub struct ConstraintStdComp {
     /// Data for variable lower limits.
     pub l: Vec<(Node, f64, Constraint)>, // var, value, constraint
 
+    /// Data for violation variables of soft constraints, which are nonnegative
+    /// and penalized in the objective function.
+    pub v: Vec<(Node, f64, Constraint)>, // var, weight, constraint
+
     /// Standard properties of constraint expressions.
     pub prop: Vec<NodeStdProp>,
 }
@@ -64,6 +69,7 @@ impl ConstraintStdComp {
             h: Vec::new(),
             u: Vec::new(),
             l: Vec::new(),
+            v: Vec::new(),
             prop: Vec::new(),
         }
     }
@@ -80,6 +86,7 @@ impl AddAssign for ConstraintStdComp {
         self.h.extend(other.h);
         self.u.extend(other.u);
         self.l.extend(other.l);
+        self.v.extend(other.v);
         self.prop.extend(other.prop);
     }
 }
@@ -97,8 +104,27 @@ impl ConstraintStd for Constraint {
         let mut h: Vec<Vec<(Node, Node, Node)>> = Vec::new();
         let mut u: Vec<(Node, f64, Constraint)> = Vec::new();
         let mut l: Vec<(Node, f64, Constraint)> = Vec::new();
+        let mut v: Vec<(Node, f64, Constraint)> = Vec::new();
     
-        let exp = self.lhs()-self.rhs();
+        let mut exp = self.lhs()-self.rhs();
+
+        // Soft constraint (exp - vu + vl with vu, vl >= 0)
+        if let Some(weight) = self.penalty() {
+            let row = if exp.std_properties().affine { format!("a{}", *arow) } else { format!("j{}", *jrow) };
+            let mut args = vec![exp];
+            if *self.kind() != ConstraintKind::GreaterEqual {
+                let vu = VariableScalar::new_continuous(format!("_vu_{}_", row).as_str());
+                args.push(-&vu);
+                v.push((vu, weight, self.clone()));
+            }
+            if *self.kind() != ConstraintKind::LessEqual {
+                let vl = VariableScalar::new_continuous(format!("_vl_{}_", row).as_str());
+                args.push(vl.clone());
+                v.push((vl, weight, self.clone()));
+            }
+            exp = FunctionAdd::new(args);
+        }
+
         let comp = exp.std_components();
         let mut prop = comp.prop;
 
@@ -212,6 +238,7 @@ impl ConstraintStd for Constraint {
             h: h,
             u: u,
             l: l,
+            v: v,
             prop: vec![prop],
         }
     }
@@ -449,13 +476,13 @@ mod tests {
             if *col == x {
                 assert_eq!(*row, 2);
                 assert_eq!(format!("{}", *val),
-                           "y*4 + 3*x + x*3");
+                           "4*y + 6*x");
                 counter += 1;
             }
             else if *col == y {
                 assert_eq!(*row, 2);
                 assert_eq!(format!("{}", *val),
-                           "7*y + y*7 + 4*x");
+                           "14*y + 4*x");
                 counter += 1;
             }
             else {
@@ -518,13 +545,13 @@ mod tests {
             if *col == x {
                 assert_eq!(*row, 2);
                 assert_eq!(format!("{}", *val),
-                           "y*4 + 3*x + x*3");
+                           "4*y + 6*x");
                 counter += 1;
             }
             else if *col == y {
                 assert_eq!(*row, 2);
                 assert_eq!(format!("{}", *val),
-                           "7*y + y*7 + 4*x");
+                           "14*y + 4*x");
                 counter += 1;
             }
             else if (*col).name() == "_s_j2_"  {
@@ -596,13 +623,13 @@ mod tests {
             if *col == x {
                 assert_eq!(*row, 2);
                 assert_eq!(format!("{}", *val),
-                           "y*4 + 3*x + x*3");
+                           "4*y + 6*x");
                 counter += 1;
             }
             else if *col == y {
                 assert_eq!(*row, 2);
                 assert_eq!(format!("{}", *val),
-                           "7*y + y*7 + 4*x");
+                           "14*y + 4*x");
                 counter += 1;
             }
             else if (*col).name() == "_s_j2_" {
@@ -649,4 +676,56 @@ mod tests {
         assert_eq!(arow, 1);
         assert_eq!(jrow, 3);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn constr_std_comp_soft() {
+
+        let x = VariableScalar::new_continuous("x");
+        let y = VariableScalar::new_continuous("y");
+
+        // Bound
+        let c1 = (&x).leq(3.).soft(10.).unwrap();
+        let mut arow: usize = 1;
+        let mut jrow: usize = 2;
+        let comp1 = c1.std_components(&mut arow, &mut jrow);
+
+        assert_eq!(comp1.ca.len(), 1);
+        assert_eq!(comp1.a.len(), 3);
+        for (row, col, val) in comp1.a.iter() {
+            assert_eq!(*row, 1);
+            match (*col).name() {
+                "x" => assert_eq!(*val, 1.),
+                "_vu_a1_" => assert_eq!(*val, -1.),
+                "_s_a1_" => assert_eq!(*val, -1.),
+                _ => panic!("unexpected variable"),
+            }
+        }
+        assert_eq!(comp1.b, vec![3.]);
+        assert_eq!(comp1.u.len(), 1);
+        assert_eq!(comp1.u[0].0.name(), "_s_a1_");
+        assert_eq!(comp1.l.len(), 0);
+        assert_eq!(comp1.v.len(), 1);
+        let (var, weight, c) = &comp1.v[0];
+        assert_eq!((*var).name(), "_vu_a1_");
+        assert_eq!(*weight, 10.);
+        assert_eq!(*c, c1);
+        assert_eq!(arow, 2);
+        assert_eq!(jrow, 2);
+
+        // Nonlinear equality
+        let c2 = (&x*&y).equal(2.).soft(5.).unwrap();
+        let comp2 = c2.std_components(&mut arow, &mut jrow);
+
+        assert_eq!(comp2.cj.len(), 1);
+        assert_eq!(comp2.f.len(), 1);
+        assert_eq!(format!("{}", comp2.f[0]), "x*y + -2 + -1*_vu_j2_ + _vl_j2_");
+        assert_eq!(comp2.j.len(), 4);
+        assert_eq!(comp2.u.len(), 0);
+        assert_eq!(comp2.l.len(), 0);
+        let names: Vec<&str> = comp2.v.iter().map(|(var, _w, _c)| var.name()).collect();
+        assert_eq!(names, vec!["_vu_j2_", "_vl_j2_"]);
+        assert!(comp2.v.iter().all(|(_var, w, c)| *w == 5. && *c == c2));
+        assert_eq!(arow, 2);
+        assert_eq!(jrow, 3);
+    }
+}
@@ -1,11 +1,11 @@
 //! Add function.
 
 use std::fmt;
-use std::rc::Rc;
 use std::collections::HashMap;
 
-use crate::model::node::Node;
+use crate::model::node::{Node, NodeRc};
 use crate::model::node_base::NodeBase;
+use crate::model::node_interval::interval_add;
 use crate::model::node_std::{NodeStd, NodeStdProp};
 use crate::model::constant::ConstantScalar;
 
@@ -20,7 +20,7 @@ impl FunctionAdd {
     pub fn new(args: Vec<Node>) -> Node {
 
         assert!(args.len() >= 2);
-        Node::FunctionAdd(Rc::new(
+        Node::FunctionAdd(NodeRc::new(
             Self {
                 args: args,
             }
@@ -46,6 +46,10 @@ impl NodeBase for FunctionAdd {
     fn evaluate(&self, var_values: &HashMap<&Node, f64>) -> f64 { 
         self.args.iter().map(|x| x.evaluate(var_values)).sum()
     }
+
+    fn evaluate_interval(&self, var_bounds: &HashMap<&Node, (f64, f64)>) -> (f64, f64) {
+        self.args.iter().fold((0., 0.), |r, x| interval_add(r, x.evaluate_interval(var_bounds)))
+    }
 }
 
 impl NodeStd for FunctionAdd {
@@ -203,5 +207,17 @@ mod tests {
         assert!(p3.a.contains_key(&y));
         assert!(p3.a.contains_key(&z));
     }
-}
 
+    #[test]
+    fn add_evaluate_interval() {
+
+        let x = VariableScalar::new_continuous("x");
+        let y = VariableScalar::new_continuous("y");
+        let z = VariableScalar::new_continuous("z");
+        let bounds = hashmap!{ &x => (1., 2.), &y => (-3., 4.) };
+
+        assert_eq!((&x + &y + 3.).evaluate_interval(&bounds), (1., 9.));
+        assert_eq!((&x - &x).evaluate_interval(&bounds), (-1., 1.));
+        assert_eq!((&x + &z).evaluate_interval(&bounds), (-f64::INFINITY, f64::INFINITY));
+    }
+}
@@ -1,11 +1,11 @@
 //! Cosine function.
 
 use std::fmt;
-use std::rc::Rc;
 use std::collections::HashMap;
 
-use crate::model::node::Node;
+use crate::model::node::{Node, NodeRc};
 use crate::model::node_base::NodeBase;
+use crate::model::node_interval::interval_cos;
 use crate::model::node_std::{NodeStd, NodeStdProp};
 use crate::model::node_func::NodeFunc;
 use crate::model::constant::ConstantScalar;
@@ -19,7 +19,7 @@ impl FunctionCos {
 
     /// Creates new cosine expression node.
     pub fn new(arg: Node) -> Node {
-        Node::FunctionCos(Rc::new(
+        Node::FunctionCos(NodeRc::new(
             Self {
                 arg: arg,
             }
@@ -45,6 +45,10 @@ impl NodeBase for FunctionCos {
     fn evaluate(&self, var_values: &HashMap<&Node, f64>) -> f64 { 
         self.arg.evaluate(var_values).cos()
     }
+
+    fn evaluate_interval(&self, var_bounds: &HashMap<&Node, (f64, f64)>) -> (f64, f64) {
+        interval_cos(self.arg.evaluate_interval(var_bounds))
+    }
 }
 
 impl NodeStd for FunctionCos {
@@ -101,8 +105,8 @@ mod tests {
         let z2 = (5.*&x + 3.*&y).cos();
         let z2x = z2.derivative(&x);
         let z2y = z2.derivative(&y);
-        assert_eq!(format!("{}", z2x), "-1*sin(5*x + 3*y)*5");
-        assert_eq!(format!("{}", z2y), "-1*sin(5*x + 3*y)*3");
+        assert_eq!(format!("{}", z2x), "-5*sin(5*x + 3*y)");
+        assert_eq!(format!("{}", z2y), "-3*sin(5*x + 3*y)");
     }
     
     #[test]
@@ -124,4 +128,16 @@ mod tests {
         assert!(p2.a.contains_key(&x));
         assert!(p2.a.contains_key(&y));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn cos_evaluate_interval() {
+
+        let x = VariableScalar::new_continuous("x");
+        let y = VariableScalar::new_continuous("y");
+        let bounds = maplit::hashmap!{ &x => (1., 2.), &y => (-1.5, 1.5) };
+
+        assert_eq!(x.cos().evaluate_interval(&bounds), (2_f64.cos(), 1_f64.cos()));
+        assert_eq!((&y*&y).cos().evaluate_interval(&bounds), (2.25_f64.cos(), 1.));
+        assert_eq!((&x + 2.).cos().evaluate_interval(&bounds), (-1., 4_f64.cos()));
+    }
+}
@@ -1,11 +1,11 @@
 //! Divide function.
 
 use std::fmt;
-use std::rc::Rc;
 use std::collections::HashMap;
 
-use crate::model::node::Node;
+use crate::model::node::{Node, NodeRc};
 use crate::model::node_base::NodeBase;
+use crate::model::node_interval::interval_div;
 use crate::model::node_std::{NodeStd, NodeStdProp};
 use crate::model::constant::ConstantScalar;
 
@@ -18,7 +18,7 @@ impl FunctionDiv {
 
     /// Creates new divide expression node.
     pub fn new(arg1: Node, arg2: Node) -> Node {
-        Node::FunctionDiv(Rc::new(
+        Node::FunctionDiv(NodeRc::new(
             Self {
                 args: (arg1, arg2),
             }
@@ -47,6 +47,11 @@ impl NodeBase for FunctionDiv {
     fn evaluate(&self, var_values: &HashMap<&Node, f64>) -> f64 { 
         self.args.0.evaluate(var_values)/self.args.1.evaluate(var_values)
     }
+
+    fn evaluate_interval(&self, var_bounds: &HashMap<&Node, (f64, f64)>) -> (f64, f64) {
+        interval_div(self.args.0.evaluate_interval(var_bounds),
+                     self.args.1.evaluate_interval(var_bounds))
+    }
 }
 
 impl NodeStd for FunctionDiv {
@@ -138,15 +143,15 @@ mod tests {
         let z3x = z3.derivative(&x);
         let z3y = z3.derivative(&y);
         assert!(z3x.is_constant_with_value(0.));
-        assert_eq!(format!("{}", z3y), "(-5/(-1*y*-1*y))*-1");
+        assert_eq!(format!("{}", z3y), "5/(y*y)");
 
         let z4 = 3.*&x/(&y - &x);
         let z4x = z4.derivative(&x);
         let z4y = z4.derivative(&y);
         assert_eq!(format!("{}", z4x), 
-                  "(-1*3*x/((y + -1*x)*(y + -1*x)))*-1 + (1/(y + -1*x))*3"); 
+                  "3/(-1*x + y) + 3*x/((-1*x + y)*(-1*x + y))"); 
         assert_eq!(format!("{}", z4y), 
-                  "-1*3*x/((y + -1*x)*(y + -1*x))");
+                  "-3*x/((-1*x + y)*(-1*x + y))");
 
         let f1 = &x - 2.;
         let z5 = &f1/(&f1 + 3.);
@@ -196,6 +201,20 @@ mod tests {
         assert!(p5.a.contains_key(&x));
         assert!(p5.a.contains_key(&y));
     }
-}
 
+    #[test]
+    fn div_evaluate_interval() {
 
+        let x = VariableScalar::new_continuous("x");
+        let y = VariableScalar::new_continuous("y");
+        let w = VariableScalar::new_continuous("w");
+        let bounds = maplit::hashmap!{ &x => (1., 2.), &y => (-3., 4.), &w => (0., 2.) };
+
+        assert_eq!((1./&x).evaluate_interval(&bounds), (0.5, 1.));
+        assert_eq!((&y/&x).evaluate_interval(&bounds), (-3., 4.));
+        assert_eq!((&x/&y).evaluate_interval(&bounds), (-f64::INFINITY, f64::INFINITY));
+        assert_eq!((&x/&w).evaluate_interval(&bounds), (0.5, f64::INFINITY));
+        assert_eq!((-&x/&w).evaluate_interval(&bounds), (-f64::INFINITY, -0.5));
+        assert_eq!((&x/(&w - &w)).evaluate_interval(&bounds), (-f64::INFINITY, f64::INFINITY));
+    }
+}
@@ -1,11 +1,11 @@
 //! Multiply function.
 
 use std::fmt;
-use std::rc::Rc;
 use std::collections::HashMap;
 
-use crate::model::node::Node;
+use crate::model::node::{Node, NodeRc};
 use crate::model::node_base::NodeBase;
+use crate::model::node_interval::{interval_mul, interval_sqr};
 use crate::model::node_std::{NodeStd, NodeStdProp};
 use crate::model::constant::ConstantScalar;
 
@@ -18,7 +18,7 @@ impl FunctionMul {
 
     /// Creates new multiply expression node.
     pub fn new(arg1: Node, arg2: Node) -> Node {
-        Node::FunctionMul(Rc::new(
+        Node::FunctionMul(NodeRc::new(
             Self {
                 args: (arg1, arg2),
             }
@@ -47,6 +47,16 @@ impl NodeBase for FunctionMul {
     fn evaluate(&self, var_values: &HashMap<&Node, f64>) -> f64 { 
         self.args.0.evaluate(var_values)*self.args.1.evaluate(var_values)
     }
+
+    fn evaluate_interval(&self, var_bounds: &HashMap<&Node, (f64, f64)>) -> (f64, f64) {
+        if self.args.0 == self.args.1 {
+            interval_sqr(self.args.0.evaluate_interval(var_bounds))
+        }
+        else {
+            interval_mul(self.args.0.evaluate_interval(var_bounds),
+                         self.args.1.evaluate_interval(var_bounds))
+        }
+    }
 }
 
 impl NodeStd for FunctionMul {
@@ -137,14 +147,14 @@ mod tests {
         let z3x = z3.derivative(&x);
         let z3y = z3.derivative(&y);
         assert_eq!(z3x, y);
-        assert_eq!(format!("{}", z3y), "y*-1*y + y*-1*y + x + -1*y*y + -3");
+        assert_eq!(format!("{}", z3y), "-3*y*y + x + -3");
 
         let f1 = 3.*&x;
         let z4 = &f1*(&f1*&y);
         let z4x = z4.derivative(&x);
         let z4y = z4.derivative(&y);
-        assert_eq!(format!("{}", z4x), "3*x*y*3 + 3*x*y*3");
-        assert_eq!(format!("{}", z4y), "3*x*3*x");
+        assert_eq!(format!("{}", z4x), "18*x*y");
+        assert_eq!(format!("{}", z4y), "9*x*x");
     }
 
     #[test]
@@ -188,5 +198,19 @@ mod tests {
         assert!(p5.a.contains_key(&x));
         assert!(p5.a.contains_key(&y));
     }
-}
 
+    #[test]
+    fn mul_evaluate_interval() {
+
+        let x = VariableScalar::new_continuous("x");
+        let y = VariableScalar::new_continuous("y");
+        let z = VariableScalar::new_continuous("z");
+        let bounds = maplit::hashmap!{ &x => (1., 2.), &y => (-3., 4.), &z => (2., f64::INFINITY) };
+
+        assert_eq!((&x*&y).evaluate_interval(&bounds), (-6., 8.));
+        assert_eq!((&y*&y).evaluate_interval(&bounds), (0., 16.));
+        assert_eq!((&y*&x*&y).evaluate_interval(&bounds), (-24., 32.));
+        assert_eq!((-3.*&x).evaluate_interval(&bounds), (-6., -3.));
+        assert_eq!((&x*&z).evaluate_interval(&bounds), (2., f64::INFINITY));
+    }
+}
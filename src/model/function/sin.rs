@@ -1,11 +1,11 @@
 //! Sine function.
 
 use std::fmt;
-use std::rc::Rc;
 use std::collections::HashMap;
 
-use crate::model::node::Node;
+use crate::model::node::{Node, NodeRc};
 use crate::model::node_base::NodeBase;
+use crate::model::node_interval::interval_sin;
 use crate::model::node_std::{NodeStd, NodeStdProp};
 use crate::model::node_func::NodeFunc;
 use crate::model::constant::ConstantScalar;
@@ -19,7 +19,7 @@ impl FunctionSin {
 
     /// Creates new sine expression node.
     pub fn new(arg: Node) -> Node {
-        Node::FunctionSin(Rc::new(
+        Node::FunctionSin(NodeRc::new(
             Self {
                 arg: arg,
             }
@@ -45,6 +45,10 @@ impl NodeBase for FunctionSin {
     fn evaluate(&self, var_values: &HashMap<&Node, f64>) -> f64 {
         self.arg.evaluate(var_values).sin()
     }
+
+    fn evaluate_interval(&self, var_bounds: &HashMap<&Node, (f64, f64)>) -> (f64, f64) {
+        interval_sin(self.arg.evaluate_interval(var_bounds))
+    }
 }
 
 impl NodeStd for FunctionSin {
@@ -100,8 +104,8 @@ mod tests {
         let z2 = (5.*&x + 3.*&y).sin();
         let z2x = z2.derivative(&x);
         let z2y = z2.derivative(&y);
-        assert_eq!(format!("{}", z2x), "cos(5*x + 3*y)*5");
-        assert_eq!(format!("{}", z2y), "cos(5*x + 3*y)*3");
+        assert_eq!(format!("{}", z2x), "5*cos(5*x + 3*y)");
+        assert_eq!(format!("{}", z2y), "3*cos(5*x + 3*y)");
     }
 
     #[test]
@@ -123,4 +127,17 @@ mod tests {
         assert!(p2.a.contains_key(&x));
         assert!(p2.a.contains_key(&y));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn sin_evaluate_interval() {
+
+        let x = VariableScalar::new_continuous("x");
+        let y = VariableScalar::new_continuous("y");
+        let bounds = maplit::hashmap!{ &x => (1., 2.) };
+
+        assert_eq!(x.sin().evaluate_interval(&bounds), (1_f64.sin(), 1.));
+        assert_eq!((2.*&x).sin().evaluate_interval(&bounds), (4_f64.sin(), 2_f64.sin()));
+        assert_eq!((100.*&x).sin().evaluate_interval(&bounds), (-1., 1.));
+        assert_eq!(y.sin().evaluate_interval(&bounds), (-1., 1.));
+    }
+}
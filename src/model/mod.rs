@@ -4,7 +4,7 @@
 //! of optimization models.
 //! 
 //! ## Example
-//! ```
+//! ```no_run
 //! use numopt::model::*;
 //! use numopt::solver::*;
 //! 
@@ -54,6 +54,10 @@ pub mod node;
 pub mod node_base;
 pub mod node_func;
 pub mod node_diff;
+pub mod node_interval;
+pub mod node_simplify;
+pub mod node_tape;
+pub mod node_curv;
 pub mod node_cmp;
 pub mod node_std;
 pub mod constant;
@@ -63,14 +67,25 @@ pub mod constraint;
 pub mod constraint_std;
 pub mod model;
 pub mod model_std;
+pub mod model_solver;
+pub mod model_nl;
+pub mod model_fbbt;
+pub mod model_deriv_check;
+pub mod model_iis;
+pub mod model_report;
 
 pub use node::Node;
 pub use node_cmp::NodeCmp;
 pub use node_base::NodeBase;
 pub use node_func::NodeFunc;
 pub use node_diff::NodeDiff;
+pub use node_simplify::NodeSimplify;
+pub use node_curv::{NodeCurv, NodeCurvature};
 pub use variable::VariableScalar;
 pub use constant::ConstantScalar;
 pub use constraint::Constraint;
 pub use model::Model;
-pub use model::Objective;
\ No newline at end of file
+pub use model::Objective;
+pub use model::ModelConvexity;
+pub use model::ModelSensitivity;
+pub use model_solver::ModelSolver;
\ No newline at end of file
@@ -0,0 +1,152 @@
+//! Finite-difference checks of the derivatives of optimization models.
+
+use std::fmt;
+use std::collections::HashMap;
+
+use crate::model::node::Node;
+use crate::model::constraint::Constraint;
+use crate::model::model::Model;
+use crate::model::model_std::ModelStd;
+use crate::problem::deriv_check::{ProblemDerivCheck,
+                                  ProblemDerivEntry,
+                                  ProblemDerivKind,
+                                  ProblemDerivReport,
+                                  DERIV_REPORT_ENTRIES};
+
+/// Report of a derivative check of an optimization model, with the
+/// entries of the problem in standard form.
+pub struct ModelDerivReport {
+
+    /// Report of the problem in standard form, whose objective function
+    /// is to be minimized.
+    pub problem: ProblemDerivReport,
+
+    /// Variables of the problem in standard form, by index.
+    pub vars: Vec<Node>,
+
+    /// Map between nonlinear equality constraint row and model constraint.
+    pub jindex2constr: HashMap<usize, Constraint>,
+}
+
+/// A trait for checking the derivatives of optimization models with finite differences.
+pub trait ModelDerivCheck {
+
+    /// Compares the derivatives of the problem in standard form against central
+    /// differences with relative steps of the given size, at the initial
+    /// values of the variables (zero for variables without initial values).
+    /// See [ProblemDerivCheck](../../problem/deriv_check/trait.ProblemDerivCheck.html).
+    fn check_derivatives(&self, step: f64) -> ModelDerivReport;
+}
+
+impl ModelDerivCheck for Model {
+
+    fn check_derivatives(&self, step: f64) -> ModelDerivReport {
+
+        let mut std_prob = self.std_problem();
+        let mut vars: Vec<(usize, Node)> = std_prob.var2index.iter()
+                                                             .map(|(v, i)| (*i, v.clone()))
+                                                             .collect();
+        vars.sort_by_key(|(i, _v)| *i);
+        let mut x = vec![0.;vars.len()];
+        for (var, val) in self.init_primals().iter() {
+            if let Some(i) = std_prob.var2index.get(var) {
+                x[*i] = *val;
+            }
+        }
+
+        ModelDerivReport {
+            problem: std_prob.prob.check_derivatives(&x, step),
+            vars: vars.into_iter().map(|(_i, v)| v).collect(),
+            jindex2constr: std_prob.jindex2constr,
+        }
+    }
+}
+
+impl ModelDerivReport {
+
+    /// Gets largest error, or zero if there are no entries.
+    pub fn max_error(&self) -> f64 { self.problem.max_error() }
+
+    /// Describes entry in terms of model variables and constraints.
+    pub fn describe(&self, e: &ProblemDerivEntry) -> String {
+        let var = |i: usize| self.vars[i].name().to_string();
+        let constr = |i: usize| {
+            match self.jindex2constr.get(&i) {
+                Some(c) if c.label() != "" => c.label().to_string(),
+                Some(c) => format!("{}", c),
+                None => format!("{}", i),
+            }
+        };
+        let location = match e.kind {
+            ProblemDerivKind::Gradient => format!("gradient {}", var(e.j)),
+            ProblemDerivKind::ObjectiveHessian => format!("objective hessian {}, {}", var(e.i), var(e.j)),
+            ProblemDerivKind::Jacobian => format!("jacobian {}, {}", constr(e.i), var(e.j)),
+            ProblemDerivKind::ConstraintHessian(k) => {
+                format!("hessian of {} : {}, {}", constr(k), var(e.i), var(e.j))
+            },
+            ProblemDerivKind::CombinedHessian => format!("combined hessian {}, {}", var(e.i), var(e.j)),
+        };
+        format!("{} : {:.6e} vs {:.6e} (error {:.3e})", location, e.value, e.estimate, e.error)
+    }
+}
+
+impl fmt::Display for ModelDerivReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for e in self.problem.worst(DERIV_REPORT_ENTRIES).iter() {
+            writeln!(f, "{}", self.describe(e))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use maplit::hashmap;
+
+    use super::*;
+    use crate::model::node_cmp::NodeCmp;
+    use crate::model::node_func::NodeFunc;
+    use crate::model::variable::VariableScalar;
+    use crate::model::model::Objective;
+
+    #[test]
+    fn model_check_derivatives() {
+
+        let x = VariableScalar::new_continuous("x");
+        let y = VariableScalar::new_continuous("y");
+
+        let mut m = Model::new();
+        m.set_objective(Objective::maximize(&(&x*&y + (&x).cos())));
+        m.add_constraint(&(&x*&x*&y/(&y + 3.)).equal_and_tag(2., "c1"));
+        m.add_constraint(&(&x + (&y).sin()).equal(1.));
+        m.add_constraint(&(&x).leq(5.));
+        m.set_init_primals(&hashmap!{ &x => 1.5, &y => 0.7 });
+
+        let report = m.check_derivatives(1e-6);
+        assert!(report.max_error() < 1e-6);
+        assert_eq!(report.vars.len(), 2);
+        assert_eq!(report.jindex2constr.len(), 2);
+
+        let text = format!("{}", report);
+        assert_eq!(text.lines().count(), DERIV_REPORT_ENTRIES);
+        for e in report.problem.entries.iter() {
+            let d = report.describe(e);
+            match e.kind {
+                ProblemDerivKind::Gradient => assert!(d.starts_with("gradient x") || d.starts_with("gradient y")),
+                ProblemDerivKind::Jacobian => {
+                    let c1 = report.jindex2constr.get(&e.i).unwrap().label() == "c1";
+                    assert_eq!(d.starts_with("jacobian c1, "), c1);
+                },
+                _ => (),
+            }
+        }
+
+        // Entries of x*x*y/(y + 3) Hessian
+        let k = report.jindex2constr.iter().find(|(_i, c)| c.label() == "c1").unwrap().0;
+        let n = report.problem.entries.iter()
+                                      .filter(|e| e.kind == ProblemDerivKind::ConstraintHessian(*k))
+                                      .count();
+        assert_eq!(n, 3);
+    }
+}
@@ -0,0 +1,500 @@
+//! Feasibility-based bound tightening of optimization models.
+//!
+//! Variable bounds are propagated through the expressions of all constraints
+//! using interval arithmetic. A forward pass encloses the values of all
+//! subexpressions, the enclosure of each constraint expression is intersected
+//! with the set allowed by the constraint, and a backward pass projects the
+//! result onto the arguments of each subexpression, down to the variables.
+//! Passes over the constraints are repeated while bounds improve.
+
+use std::fmt;
+use std::f64::consts::PI;
+use std::collections::{HashSet, HashMap};
+
+use crate::model::node::Node;
+use crate::model::node_base::NodeBase;
+use crate::model::node_std::NodeStd;
+use crate::model::node_interval::{interval_add, interval_mul, interval_sqr,
+                                  interval_div, interval_sin, interval_cos};
+use crate::model::constraint::{Constraint, ConstraintKind};
+use crate::model::model::{Model, Objective};
+use crate::model::model_std::ModelStd;
+
+/// Tolerance for intersections of intervals to be considered empty.
+const FEAS_TOL: f64 = 1e-8;
+
+/// Relative bound improvement needed for another pass over the constraints.
+const IMPROVE_TOL: f64 = 1e-6;
+
+/// Result of feasibility-based bound tightening.
+pub struct ModelFbbtResult {
+
+    /// Initial variable bounds (lower, upper) derived from the bound constraints of the model.
+    pub initial: HashMap<Node, (f64, f64)>,
+
+    /// Tightened variable bounds (lower, upper).
+    pub bounds: HashMap<Node, (f64, f64)>,
+
+    /// Constraints that were found to be infeasible, if any. These are either a
+    /// constraint through which the bounds cannot be propagated, or the bound
+    /// constraints that set conflicting lower and upper bounds of a variable.
+    pub infeasible: Vec<Constraint>,
+
+    /// Number of passes over the constraints.
+    pub passes: usize,
+}
+
+/// A trait for feasibility-based bound tightening of optimization models.
+pub trait ModelFbbt {
+
+    /// Tightens variable bounds by propagating the bounds of the problem in
+    /// standard form through all the constraints, with at most the given
+    /// number of passes over the constraints. Bounds of integer variables
+    /// are rounded.
+    fn fbbt(&self, max_passes: usize) -> ModelFbbtResult;
+}
+
+/// Bound propagator through constraint expressions.
+struct FbbtPropagator<'a> {
+    bounds: &'a mut HashMap<Node, (f64, f64)>,
+    ranges: HashMap<Node, (f64, f64)>,
+    improved: bool,
+}
+
+impl ModelFbbtResult {
+
+    /// Gets the tightened variable bounds, keyed by variable references.
+    pub fn var_bounds(&self) -> HashMap<&Node, (f64, f64)> {
+        self.bounds.iter().map(|(var, b)| (var, *b)).collect()
+    }
+
+    /// Gets the variables whose bounds were tightened, sorted by name.
+    pub fn tightened(&self) -> Vec<&Node> {
+        let mut vars: Vec<&Node> = self.bounds.iter()
+                                              .filter(|(var, b)| self.initial.get(*var) != Some(*b))
+                                              .map(|(var, _)| var)
+                                              .collect();
+        vars.sort_by(|x, y| x.name().cmp(y.name()));
+        vars
+    }
+}
+
+impl ModelFbbt for Model {
+
+    fn fbbt(&self, max_passes: usize) -> ModelFbbtResult {
+
+        // Variables
+        let mut vars: HashSet<Node> = HashSet::new();
+        if let Objective::Minimize(f) | Objective::Maximize(f) = self.objective() {
+            vars.extend(f.std_properties().a.into_keys());
+        }
+        for c in self.constraints().iter() {
+            vars.extend((c.lhs()-c.rhs()).std_properties().a.into_keys());
+        }
+
+        // Initial bounds
+        let comp = self.std_components();
+        let mut initial: HashMap<Node, (f64, f64)> = vars.iter()
+                                                         .map(|x| (x.clone(), (-f64::INFINITY, f64::INFINITY)))
+                                                         .collect();
+        let mut sources: HashMap<Node, (Option<&Constraint>, Option<&Constraint>)> = HashMap::new();
+        for (x, val, c) in comp.constr.u.iter() {
+            if let Some(b) = initial.get_mut(x) {
+                let val = if *val == 0. { 0. } else { *val };
+                if val < b.1 {
+                    b.1 = val;
+                    sources.entry(x.clone()).or_insert((None, None)).1 = Some(c);
+                }
+            }
+        }
+        for (x, val, c) in comp.constr.l.iter() {
+            if let Some(b) = initial.get_mut(x) {
+                let val = if *val == 0. { 0. } else { *val };
+                if val > b.0 {
+                    b.0 = val;
+                    sources.entry(x.clone()).or_insert((None, None)).0 = Some(c);
+                }
+            }
+        }
+
+        // Constraints (soft constraints do not restrict variables)
+        let constraints: Vec<(&Constraint, Node, (f64, f64))> = self.constraints().iter().filter(|c| !c.is_soft()).map(|c| {
+            let target = match c.kind() {
+                ConstraintKind::Equal => (0., 0.),
+                ConstraintKind::LessEqual => (-f64::INFINITY, 0.),
+                ConstraintKind::GreaterEqual => (0., f64::INFINITY),
+            };
+            (c, c.lhs()-c.rhs(), target)
+        }).collect();
+
+        // Passes
+        let mut bounds = initial.clone();
+        let mut infeasible: Vec<Constraint> = Vec::new();
+        let mut passes: usize = 0;
+        let mut p = FbbtPropagator {
+            bounds: &mut bounds,
+            ranges: HashMap::new(),
+            improved: false,
+        };
+        let mut initial_vars: Vec<&Node> = initial.keys().collect();
+        initial_vars.sort_by(|x, y| x.name().cmp(y.name()));
+        for x in initial_vars.into_iter() {
+            if p.update(x, initial[x]).is_err() {
+                if let Some((cl, cu)) = sources.get(x) {
+                    infeasible.extend(cl.iter().chain(cu.iter()).map(|c| (*c).clone()));
+                }
+                break;
+            }
+        }
+        while infeasible.is_empty() && passes < max_passes {
+            passes += 1;
+            p.improved = false;
+            for (c, e, target) in constraints.iter() {
+                p.ranges.clear();
+                p.forward(e);
+                if p.backward(e, *target).is_err() {
+                    infeasible.push((*c).clone());
+                    break;
+                }
+            }
+            if !p.improved {
+                break;
+            }
+        }
+
+        ModelFbbtResult {
+            initial: initial,
+            bounds: bounds,
+            infeasible: infeasible,
+            passes: passes,
+        }
+    }
+}
+
+impl<'a> FbbtPropagator<'a> {
+
+    /// Encloses the values of expression and its subexpressions.
+    fn forward(&mut self, node: &Node) -> (f64, f64) {
+
+        if let Some(r) = self.ranges.get(node) {
+            return *r;
+        }
+
+        let args: Vec<(f64, f64)> = node.arguments().iter().map(|a| self.forward(a)).collect();
+        let r = match node {
+            Node::ConstantScalar(x) => (x.value(), x.value()),
+            Node::VariableScalar(_x) => {
+                match self.bounds.get(node) {
+                    Some(b) => *b,
+                    None => (-f64::INFINITY, f64::INFINITY),
+                }
+            },
+            Node::FunctionAdd(_x) => args.iter().fold((0., 0.), |r, a| interval_add(r, *a)),
+            Node::FunctionMul(_x) => {
+                let a = node.arguments();
+                if a[0] == a[1] { interval_sqr(args[0]) } else { interval_mul(args[0], args[1]) }
+            },
+            Node::FunctionDiv(_x) => interval_div(args[0], args[1]),
+            Node::FunctionCos(_x) => interval_cos(args[0]),
+            Node::FunctionSin(_x) => interval_sin(args[0]),
+        };
+
+        self.ranges.insert(node.clone(), r);
+        r
+    }
+
+    /// Restricts the values of expression to the given interval and projects
+    /// the restriction onto its arguments. It fails if the resulting set of
+    /// values is empty.
+    fn backward(&mut self, node: &Node, r: (f64, f64)) -> Result<(), ()> {
+
+        let current = self.ranges[node];
+        let r = intersect(current, r)?;
+        if r == current {
+            return Ok(());
+        }
+        self.ranges.insert(node.clone(), r);
+
+        let args = node.arguments();
+        match node {
+            Node::ConstantScalar(_x) => (),
+            Node::VariableScalar(_x) => self.update(node, r)?,
+            Node::FunctionAdd(_x) => {
+                for (i, a) in args.iter().enumerate() {
+                    let others = args.iter()
+                                     .enumerate()
+                                     .filter(|(j, _)| *j != i)
+                                     .fold((0., 0.), |s, (_, b)| interval_add(s, self.ranges[*b]));
+                    self.backward(a, interval_sub(r, others))?;
+                }
+            },
+            Node::FunctionMul(_x) => {
+                if args[0] == args[1] {
+                    let hi = r.1.max(0.).sqrt();
+                    let lo = r.0.max(0.).sqrt();
+                    let ra = self.ranges[args[0]];
+                    let pos = intersect(ra, (lo, hi)).ok();
+                    let neg = intersect(ra, (-hi, -lo)).ok();
+                    let h = match (neg, pos) {
+                        (Some(n), Some(p)) => (n.0, p.1),
+                        (Some(n), None) => n,
+                        (None, Some(p)) => p,
+                        (None, None) => return Err(()),
+                    };
+                    self.backward(args[0], h)?;
+                }
+                else {
+                    for (a, b) in [(args[0], args[1]), (args[1], args[0])].iter() {
+                        let rb = self.ranges[*b];
+                        if !(contains_zero(rb) && contains_zero(r)) {
+                            self.backward(a, interval_div(r, rb))?;
+                        }
+                    }
+                }
+            },
+            Node::FunctionDiv(_x) => {
+                let rb = self.ranges[args[1]];
+                self.backward(args[0], interval_mul(r, rb))?;
+                let ra = self.ranges[args[0]];
+                if !(contains_zero(ra) && contains_zero(r)) {
+                    self.backward(args[1], interval_div(ra, r))?;
+                }
+            },
+            Node::FunctionCos(_x) => {
+                let ra = self.ranges[args[0]];
+                if let Some(h) = asin_branch(r, (ra.0 + PI/2., ra.1 + PI/2.)) {
+                    self.backward(args[0], (h.0 - PI/2., h.1 - PI/2.))?;
+                }
+            },
+            Node::FunctionSin(_x) => {
+                let ra = self.ranges[args[0]];
+                if let Some(h) = asin_branch(r, ra) {
+                    self.backward(args[0], h)?;
+                }
+            },
+        };
+
+        Ok(())
+    }
+
+    /// Restricts the bounds of variable to the given interval.
+    fn update(&mut self, var: &Node, r: (f64, f64)) -> Result<(), ()> {
+
+        let current = match self.bounds.get(var) {
+            Some(b) => *b,
+            None => (-f64::INFINITY, f64::INFINITY),
+        };
+        let mut r = intersect(current, r)?;
+        if let Node::VariableScalar(x) = var {
+            if x.is_integer() {
+                r = ((r.0 - FEAS_TOL).ceil(), (r.1 + FEAS_TOL).floor());
+                if r.0 > r.1 {
+                    return Err(());
+                }
+            }
+        }
+
+        let improves = |old: f64, new: f64| {
+            (new - old).abs() > IMPROVE_TOL*(1. + new.abs()) || (old.is_infinite() && new.is_finite())
+        };
+        if improves(current.0, r.0) || improves(current.1, r.1) {
+            self.improved = true;
+        }
+        self.bounds.insert(var.clone(), r);
+        self.ranges.insert(var.clone(), r);
+        Ok(())
+    }
+}
+
+/// Gets intersection of intervals. It fails if the intersection is empty
+/// beyond the feasibility tolerance.
+fn intersect(r0: (f64, f64), r1: (f64, f64)) -> Result<(f64, f64), ()> {
+    let lo = r0.0.max(r1.0);
+    let hi = r0.1.min(r1.1);
+    if lo <= hi {
+        Ok((lo, hi))
+    }
+    else if lo - hi <= FEAS_TOL*(1. + lo.abs().min(hi.abs())) {
+        Ok((hi, lo))
+    }
+    else {
+        Err(())
+    }
+}
+
+/// Gets interval of the differences of values in the given intervals.
+fn interval_sub(r0: (f64, f64), r1: (f64, f64)) -> (f64, f64) {
+    let lo = r0.0 - r1.1;
+    let hi = r0.1 - r1.0;
+    (if lo.is_nan() { -f64::INFINITY } else { lo },
+     if hi.is_nan() { f64::INFINITY } else { hi })
+}
+
+/// Determines whether interval contains zero.
+fn contains_zero(r: (f64, f64)) -> bool {
+    r.0 <= 0. && r.1 >= 0.
+}
+
+/// Gets enclosure of the values in the given argument interval whose sine is
+/// in the given interval, if the argument interval is within a monotone branch
+/// of the sine.
+fn asin_branch(r: (f64, f64), arg: (f64, f64)) -> Option<(f64, f64)> {
+
+    if !arg.0.is_finite() || !arg.1.is_finite() {
+        return None;
+    }
+
+    // Branch [(k-1/2)*pi, (k+1/2)*pi], nondecreasing for even k
+    let k = ((arg.0 + PI/2.)/PI).floor();
+    if arg.1 + PI/2. > (k+1.)*PI {
+        return None;
+    }
+    let lo = r.0.clamp(-1., 1.).asin();
+    let hi = r.1.clamp(-1., 1.).asin();
+    let tol = FEAS_TOL*(1. + arg.0.abs().max(arg.1.abs()));
+    if k.rem_euclid(2.) == 0. {
+        Some((k*PI + lo - tol, k*PI + hi + tol))
+    }
+    else {
+        Some((k*PI - hi - tol, k*PI - lo + tol))
+    }
+}
+
+impl fmt::Display for ModelFbbtResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.infeasible.is_empty() {
+            for c in self.infeasible.iter() {
+                if c.label() != "" {
+                    writeln!(f, "infeasible constraint: {} : {}", c, c.label())?;
+                }
+                else {
+                    writeln!(f, "infeasible constraint: {}", c)?;
+                }
+            }
+            return Ok(());
+        }
+        for var in self.tightened().iter() {
+            let b0 = self.initial[*var];
+            let b1 = self.bounds[*var];
+            writeln!(f, "{} : [{}, {}] -> [{}, {}]", var, b0.0, b0.1, b1.0, b1.1)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use approx::assert_abs_diff_eq;
+
+    use super::*;
+    use crate::model::node_cmp::NodeCmp;
+    use crate::model::node_func::NodeFunc;
+    use crate::model::variable::VariableScalar;
+
+    #[test]
+    fn model_fbbt_linear() {
+
+        let x = VariableScalar::new_continuous("x");
+        let y = VariableScalar::new_continuous("y");
+        let n = VariableScalar::new_integer("n");
+
+        let mut m = Model::new();
+        m.set_objective(Objective::minimize(&(&x + &y + &n)));
+        m.add_constraint(&(&x).geq(0.));
+        m.add_constraint(&(&x).leq(3.));
+        m.add_constraint(&(&y).geq(0.));
+        m.add_constraint(&(&y).leq(8.));
+        m.add_constraint(&(&x + &y).equal(10.));
+        m.add_constraint(&(2.*&n).leq(&x + 4.));
+        m.add_constraint(&(&n).geq(-1.5));
+
+        let r = m.fbbt(10);
+        assert!(r.infeasible.is_empty());
+        assert!(r.passes >= 1);
+        assert_eq!(r.initial[&x], (0., 3.));
+        assert_eq!(r.initial[&n], (-1.5, f64::INFINITY));
+        assert_eq!(r.bounds[&x], (2., 3.));
+        assert_eq!(r.bounds[&y], (7., 8.));
+        assert_eq!(r.bounds[&n], (-1., 3.));
+        assert_eq!(r.tightened(), vec![&n, &x, &y]);
+        assert_eq!(format!("{}", r),
+                   "n : [-1.5, inf] -> [-1, 3]\n\
+                    x : [0, 3] -> [2, 3]\n\
+                    y : [0, 8] -> [7, 8]\n");
+    }
+
+    #[test]
+    fn model_fbbt_nonlinear() {
+
+        let x = VariableScalar::new_continuous("x");
+        let y = VariableScalar::new_continuous("y");
+        let z = VariableScalar::new_continuous("z");
+        let w = VariableScalar::new_continuous("w");
+        let t = VariableScalar::new_continuous("t");
+
+        let mut m = Model::new();
+        m.add_constraint(&(&x*&x).leq(4.));
+        m.add_constraint(&(&y).geq(1.));
+        m.add_constraint(&(&y).leq(2.));
+        m.add_constraint(&(&z).equal(&x*&y));
+        m.add_constraint(&(&w).geq(0.));
+        m.add_constraint(&(&w).leq(10.));
+        m.add_constraint(&(1./&w).geq(2.));
+        m.add_constraint(&(&t).geq(0.));
+        m.add_constraint(&(&t).leq(1.5));
+        m.add_constraint(&t.sin().geq(0.5));
+
+        let r = m.fbbt(10);
+        assert!(r.infeasible.is_empty());
+        assert_eq!(r.bounds[&x], (-2., 2.));
+        assert_eq!(r.bounds[&y], (1., 2.));
+        assert_eq!(r.bounds[&z], (-4., 4.));
+        assert_eq!(r.bounds[&w], (0., 0.5));
+        assert_abs_diff_eq!(r.bounds[&t].0, PI/6., epsilon = 1e-7);
+        assert_eq!(r.bounds[&t].1, 1.5);
+
+        // Square with positive lower bound
+        m.add_constraint(&(&x).geq(-0.5));
+        m.add_constraint(&(&x*&x).geq(1.));
+        let r = m.fbbt(10);
+        assert_eq!(r.bounds[&x], (1., 2.));
+        assert_eq!(r.bounds[&z], (1., 4.));
+    }
+
+    #[test]
+    fn model_fbbt_infeasible() {
+
+        let x = VariableScalar::new_continuous("x");
+        let y = VariableScalar::new_continuous("y");
+
+        let c = (&x*&x).geq_and_tag(16., "far");
+
+        let mut m = Model::new();
+        m.add_constraint(&(&x).geq(0.));
+        m.add_constraint(&(&x).leq(3.));
+        m.add_constraint(&(&y).equal(&x + 1.));
+        m.add_constraint(&c);
+
+        let r = m.fbbt(10);
+        assert_eq!(r.infeasible, vec![c]);
+        assert_eq!(r.passes, 1);
+        assert_eq!(format!("{}", r), "infeasible constraint: x*x >= 16 : far\n");
+
+        // Inconsistent bounds
+        let cl = (&x).geq_and_tag(3., "low");
+        let cu = (&x).leq_and_tag(2., "up");
+        let mut m = Model::new();
+        m.add_constraint(&(&y).equal(&x + 1.));
+        m.add_constraint(&(&x).geq(1.));
+        m.add_constraint(&cl);
+        m.add_constraint(&cu);
+        m.add_constraint(&(&x).leq(5.));
+        let r = m.fbbt(10);
+        assert_eq!(r.infeasible, vec![cl, cu]);
+        assert_eq!(r.passes, 0);
+        assert_eq!(format!("{}", r),
+                   "infeasible constraint: x >= 3 : low\n\
+                    infeasible constraint: x <= 2 : up\n");
+    }
+}
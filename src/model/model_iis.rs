@@ -0,0 +1,206 @@
+//! Irreducible infeasible subsets of constraints of optimization models.
+//!
+//! An irreducible infeasible subset (IIS) is a set of constraints that is
+//! infeasible, but that becomes feasible if any one of its constraints is
+//! removed. It is found with a deletion filter: constraints are tentatively
+//! removed, in blocks whose size is halved each time the remaining constraints
+//! become feasible, and only removals that keep the constraints infeasible
+//! are accepted. Each test is a feasibility problem, i.e., a model with an
+//! empty objective, solved with the given solver.
+
+use std::fmt;
+use simple_error::SimpleError;
+
+use crate::solver::base::SolverStatus;
+use crate::model::model_solver::ModelSolver;
+use crate::model::constraint::Constraint;
+use crate::model::model::{Model, Objective};
+
+/// Result of an irreducible infeasible subset computation.
+pub struct ModelIisResult {
+
+    /// Conflicting constraints, in the order in which they appear in the model.
+    pub constraints: Vec<Constraint>,
+
+    /// Flag that indicates whether the subset is known to be irreducible.
+    /// It is false if the solver did not determine whether some subsets were
+    /// feasible or infeasible, in which case their constraints were kept.
+    pub irreducible: bool,
+
+    /// Number of solver calls.
+    pub solves: usize,
+}
+
+/// A trait for finding irreducible infeasible subsets of the constraints of
+/// optimization models.
+pub trait ModelIis {
+
+    /// Finds an irreducible infeasible subset of the constraints of the model by
+    /// repeatedly solving feasibility problems on subsets of its constraints with
+    /// the given solver. It fails if the solver cannot solve a feasibility problem,
+    /// or if it does not find the model to be infeasible.
+    fn iis(&self, solver: &dyn ModelSolver) -> Result<ModelIisResult, SimpleError>;
+}
+
+impl ModelIisResult {
+
+    /// Gets the labels of the conflicting constraints.
+    pub fn labels(&self) -> Vec<&str> {
+        self.constraints.iter().map(|c| c.label()).collect()
+    }
+}
+
+impl ModelIis for Model {
+
+    fn iis(&self, solver: &dyn ModelSolver) -> Result<ModelIisResult, SimpleError> {
+
+        let mut solves: usize = 0;
+        let mut irreducible = true;
+        let mut feasibility = |constraints: &[&Constraint]| -> Result<SolverStatus, SimpleError> {
+            solves += 1;
+            let mut m = Model::new();
+            m.set_objective(Objective::empty());
+            m.add_constraints(constraints);
+            m.solve(solver)?;
+            Ok(*m.solver_status().unwrap())
+        };
+
+        // Check
+        let mut kept: Vec<&Constraint> = self.constraints().iter().collect();
+        match feasibility(&kept)? {
+            SolverStatus::Infeasible => (),
+            SolverStatus::Solved => return Err(SimpleError::new("model is feasible")),
+            _ => return Err(SimpleError::new("solver did not determine model feasibility")),
+        }
+
+        // Deletion filter
+        let mut block = (kept.len()/2).max(1);
+        let mut i: usize = 0;
+        while i < kept.len() {
+            let end = (i + block).min(kept.len());
+            let rest: Vec<&Constraint> = kept[..i].iter().chain(kept[end..].iter()).cloned().collect();
+            let status = if rest.is_empty() { SolverStatus::Solved } else { feasibility(&rest)? };
+            match status {
+                SolverStatus::Infeasible => kept = rest,
+                _ if end - i > 1 => block = (block/2).max(1),
+                SolverStatus::Solved => i += 1,
+                _ => {
+                    irreducible = false;
+                    i += 1;
+                },
+            }
+        }
+
+        Ok(ModelIisResult {
+            constraints: kept.into_iter().cloned().collect(),
+            irreducible: irreducible,
+            solves: solves,
+        })
+    }
+}
+
+impl fmt::Display for ModelIisResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.constraints.iter() {
+            if c.label() != "" {
+                writeln!(f, "{} : {}", c, c.label())?;
+            }
+            else {
+                writeln!(f, "{}", c)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::problem::base::{Problem, ProblemSol};
+    use crate::solver::base::{Solver, SolverParam};
+    use crate::model::node_cmp::NodeCmp;
+    use crate::model::variable::VariableScalar;
+
+    // Solver that decides the feasibility of linear problems by
+    // propagating the variable limits through the rows.
+    struct SolverPropagation {
+        parameters: HashMap<String, SolverParam>,
+    }
+
+    impl Solver for SolverPropagation {
+
+        fn get_params(&self) -> &HashMap<String, SolverParam> { &self.parameters }
+        fn get_params_mut(&mut self) -> &mut HashMap<String, SolverParam> { &mut self.parameters }
+
+        fn solve(&self, problem: &mut Problem) -> Result<(SolverStatus, ProblemSol), SimpleError> {
+            let p = match problem {
+                Problem::Lp(x) => x,
+                _ => return Err(SimpleError::new("problem type not supported")),
+            };
+            let mut l = p.l().to_vec();
+            let mut u = p.u().to_vec();
+            let mut rows: Vec<Vec<(usize, f64)>> = vec![Vec::new(); p.na()];
+            for (i, j, v) in p.a().iter() {
+                rows[*i].push((*j, *v));
+            }
+            for _pass in 0..20 {
+                for (i, row) in rows.iter().enumerate() {
+                    for (j, v) in row.iter() {
+                        let (mut lo, mut hi) = (p.b()[i], p.b()[i]);
+                        for (k, w) in row.iter().filter(|(k, _w)| k != j) {
+                            lo -= (w*u[*k]).max(w*l[*k]);
+                            hi -= (w*u[*k]).min(w*l[*k]);
+                        }
+                        let (lo, hi) = if *v > 0. { (lo/v, hi/v) } else { (hi/v, lo/v) };
+                        l[*j] = l[*j].max(lo);
+                        u[*j] = u[*j].min(hi);
+                    }
+                }
+            }
+            let solution = ProblemSol::new(p.nx(), p.na(), 0);
+            if l.iter().zip(u.iter()).any(|(lo, hi)| *lo > *hi + 1e-9) {
+                Ok((SolverStatus::Infeasible, solution))
+            }
+            else {
+                Ok((SolverStatus::Solved, solution))
+            }
+        }
+    }
+
+    impl ModelSolver for SolverPropagation {}
+
+    #[test]
+    fn model_iis() {
+
+        let x = VariableScalar::new_continuous("x");
+        let y = VariableScalar::new_continuous("y");
+        let z = VariableScalar::new_continuous("z");
+
+        let mut m = Model::new();
+        m.set_objective(Objective::minimize(&(&x + &y + &z)));
+        m.add_constraint(&(&x).geq_and_tag(0., "x lower"));
+        m.add_constraint(&(&y).geq_and_tag(1., "y lower"));
+        m.add_constraint(&(&x - &z).leq_and_tag(50., "gap"));
+        m.add_constraint(&(&x).leq_and_tag(3., "x upper"));
+        m.add_constraint(&(&z).geq(-2.));
+        m.add_constraint(&(&x + &y).geq_and_tag(10., "demand"));
+        m.add_constraint(&(&z + &y).leq_and_tag(20., "capacity"));
+        m.add_constraint(&(2.*&y).leq_and_tag(8., "y upper"));
+
+        let s = SolverPropagation { parameters: HashMap::new() };
+        let result = m.iis(&s).unwrap();
+        assert!(result.irreducible);
+        assert_eq!(result.labels(), vec!["x upper", "demand", "y upper"]);
+        assert!(result.solves < 3*m.constraints().len());
+        assert_eq!(format!("{}", result), "x <= 3 : x upper\nx + y >= 10 : demand\n2*y <= 8 : y upper\n");
+
+        // Feasible
+        let mut m = Model::new();
+        m.add_constraint(&(&x + &y).geq(10.));
+        m.add_constraint(&(&x).leq(3.));
+        assert_eq!(m.iis(&s).err().unwrap().to_string(), "model is feasible");
+    }
+}
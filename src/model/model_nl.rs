@@ -0,0 +1,677 @@
+//! Structures and traits for writing optimization models to AMPL's NL format
+//! and reading AMPL solution files.
+
+use std::fs::File;
+use std::io::prelude::*;
+use std::io::{self, BufReader, BufWriter};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::problem::base::{Problem, ProblemSol};
+use crate::problem::minlp::ProblemMinlp;
+
+use crate::model::node::Node;
+use crate::model::node_base::NodeBase;
+use crate::model::node_std::NodeStd;
+use crate::model::model_std::{ModelStdProb, INF};
+
+/// Solution read from an AMPL solution file.
+pub struct NlSol {
+
+    /// Solver message.
+    pub message: String,
+
+    /// AMPL solve result number, if provided by the solver.
+    pub solve_result_num: Option<i32>,
+
+    /// Solution of the problem in standard form.
+    pub solution: ProblemSol,
+}
+
+/// A trait for writing problems in standard form obtained from optimization
+/// models to AMPL's NL format and reading their AMPL solutions.
+pub trait ModelStdNl {
+
+    /// Writes problem to an NL file (text format). Nonlinear equality constraints
+    /// are written first, followed by linear equality constraints. Variables are
+    /// ordered as required by the NL format, with nonlinear variables first, and
+    /// the linear parts of expressions are written as Jacobian and gradient entries.
+    fn write_to_nl_file(&self, filename: &str) -> io::Result<()>;
+
+    /// Reads AMPL solution file corresponding to problem written with
+    /// [write_to_nl_file](#tymethod.write_to_nl_file). Since AMPL solution files
+    /// do not include variable limit duals, these are recovered from the
+    /// optimality conditions.
+    fn read_sol_file(&mut self, filename: &str) -> io::Result<NlSol>;
+}
+
+// Expression split into linear and nonlinear parts.
+struct NlExpr {
+    linear: BTreeMap<usize, f64>,
+    constant: f64,
+    nonlinear: Vec<Node>,
+    nlvars: HashSet<usize>,
+}
+
+// Layout of variables in NL file.
+struct NlLayout {
+    perm: Vec<usize>,
+    nlvc: usize,
+    nlvo: usize,
+    nlvb: usize,
+    niv: usize,
+    nlvbi: usize,
+    nlvci: usize,
+    nlvoi: usize,
+}
+
+impl NlExpr {
+
+    fn new() -> Self {
+        Self {
+            linear: BTreeMap::new(),
+            constant: 0.,
+            nonlinear: Vec::new(),
+            nlvars: HashSet::new(),
+        }
+    }
+
+    fn from_node(e: &Node, var2index: &HashMap<Node, usize>) -> Self {
+        let mut nle = Self::new();
+        nle.add_terms(e, var2index);
+        for i in nle.nlvars.iter() {
+            nle.linear.entry(*i).or_insert(0.);
+        }
+        nle
+    }
+
+    fn add_terms(&mut self, e: &Node, var2index: &HashMap<Node, usize>) {
+        match e {
+            Node::FunctionAdd(_) => {
+                for arg in e.arguments() {
+                    self.add_terms(arg, var2index);
+                }
+            },
+            _ => {
+                let prop = e.std_properties();
+                if prop.affine {
+                    for (var, val) in prop.a.iter() {
+                        *self.linear.entry(*var2index.get(var).unwrap()).or_insert(0.) += *val;
+                    }
+                    self.constant += prop.b;
+                }
+                else {
+                    for var in prop.a.keys() {
+                        self.nlvars.insert(*var2index.get(var).unwrap());
+                    }
+                    self.nonlinear.push(e.clone());
+                }
+            }
+        }
+    }
+
+    fn write_node(e: &Node, var2nl: &dyn Fn(&Node) -> usize, s: &mut String) {
+        let args = e.arguments();
+        match e {
+            Node::ConstantScalar(x) => s.push_str(&format!("n{}\n", x.value())),
+            Node::VariableScalar(_) => s.push_str(&format!("v{}\n", var2nl(e))),
+            Node::FunctionAdd(_) => {
+                if args.len() == 2 {
+                    s.push_str("o0\n");
+                }
+                else {
+                    s.push_str(&format!("o54\n{}\n", args.len()));
+                }
+            },
+            Node::FunctionMul(_) => s.push_str("o2\n"),
+            Node::FunctionDiv(_) => s.push_str("o3\n"),
+            Node::FunctionSin(_) => s.push_str("o41\n"),
+            Node::FunctionCos(_) => s.push_str("o46\n"),
+        };
+        for arg in args {
+            Self::write_node(arg, var2nl, s);
+        }
+    }
+
+    fn write_nonlinear(&self, constant: f64, var2nl: &dyn Fn(&Node) -> usize) -> String {
+        let mut terms: Vec<String> = Vec::new();
+        for e in self.nonlinear.iter() {
+            let mut s = String::new();
+            Self::write_node(e, var2nl, &mut s);
+            terms.push(s);
+        }
+        if constant != 0. {
+            terms.push(format!("n{}\n", constant));
+        }
+        match terms.len() {
+            0 => "n0\n".to_string(),
+            1 => terms.pop().unwrap(),
+            2 => format!("o0\n{}", terms.concat()),
+            n => format!("o54\n{}\n{}", n, terms.concat()),
+        }
+    }
+}
+
+impl NlLayout {
+
+    fn new(p: &ProblemMinlp, rows: &[NlExpr], obj: &NlExpr) -> Self {
+
+        let nx = p.nx();
+        let mut in_c = vec![false; nx];
+        for r in rows.iter() {
+            for i in r.nlvars.iter() {
+                in_c[*i] = true;
+            }
+        }
+
+        // Categories in NL order
+        let cat: Vec<usize> = (0..nx).map(|i| {
+            let int = p.p()[i] as usize;
+            match (in_c[i], obj.nlvars.contains(&i)) {
+                (true, true) => int,
+                (true, false) => 2 + int,
+                (false, true) => 4 + int,
+                (false, false) => 6 + int,
+            }
+        }).collect();
+        let count = |c: usize| cat.iter().filter(|&&k| k == c).count();
+
+        let mut order: Vec<usize> = (0..nx).collect();
+        order.sort_by_key(|i| cat[*i]);
+        let mut perm = vec![0; nx];
+        for (k, i) in order.iter().enumerate() {
+            perm[*i] = k;
+        }
+
+        let nlvb = count(0) + count(1);
+        let nlvc = nlvb + count(2) + count(3);
+        let nlvo_only = count(4) + count(5);
+        Self {
+            perm: perm,
+            nlvc: nlvc,
+            nlvo: if nlvo_only > 0 { nlvc + nlvo_only } else { nlvb },
+            nlvb: nlvb,
+            niv: count(7),
+            nlvbi: count(1),
+            nlvci: count(3),
+            nlvoi: count(5),
+        }
+    }
+}
+
+impl ModelStdProb {
+
+    pub(crate) fn as_minlp(&self) -> &ProblemMinlp {
+        match &self.prob {
+            Problem::Minlp(x) => x,
+            Problem::Milp(x) => x.as_minlp(),
+            Problem::Nlp(x) => x.as_minlp(),
+            Problem::Lp(x) => x.as_minlp(),
+        }
+    }
+
+    // Gets constraint rows (nonlinear first) and objective in NL form.
+    fn nl_exprs(&self) -> (Vec<NlExpr>, NlExpr) {
+
+        let p = self.as_minlp();
+        let mut rows: Vec<NlExpr> = self.f.iter()
+                                          .map(|e| NlExpr::from_node(e, &self.var2index))
+                                          .collect();
+        let mut a = p.a().to_csr();
+        a.sum_duplicates();
+        for i in 0..a.rows() {
+            let mut nle = NlExpr::new();
+            for k in a.indptr()[i]..a.indptr()[i+1] {
+                nle.linear.insert(a.indices()[k], a.data()[k]);
+            }
+            nle.constant = -p.b()[i];
+            rows.push(nle);
+        }
+        let obj = NlExpr::from_node(&self.phi, &self.var2index);
+
+        (rows, obj)
+    }
+}
+
+impl ModelStdNl for ModelStdProb {
+
+    fn write_to_nl_file(&self, filename: &str) -> io::Result<()> {
+
+        let p = self.as_minlp();
+        let (rows, obj) = self.nl_exprs();
+        let layout = NlLayout::new(p, &rows, &obj);
+        let perm = &layout.perm;
+        let var2nl = |v: &Node| perm[*self.var2index.get(v).unwrap()];
+
+        let nx = p.nx();
+        let m = rows.len();
+        let nlc = self.f.len();
+        let nlo = if obj.nonlinear.is_empty() { 0 } else { 1 };
+        let nzj: usize = rows.iter().map(|r| r.linear.len()).sum();
+        let nzg = obj.linear.len();
+
+        let f = File::create(filename)?;
+        let mut w = BufWriter::new(f);
+
+        // Header
+        writeln!(w, "g3 1 1 0\t# problem numopt")?;
+        writeln!(w, " {} {} 1 0 {}\t# vars, constraints, objectives, ranges, eqns", nx, m, m)?;
+        writeln!(w, " {} {}\t# nonlinear constraints, objectives", nlc, nlo)?;
+        writeln!(w, " 0 0\t# network constraints: nonlinear, linear")?;
+        writeln!(w, " {} {} {}\t# nonlinear vars in constraints, objectives, both",
+                 layout.nlvc, layout.nlvo, layout.nlvb)?;
+        writeln!(w, " 0 0 0 1\t# linear network variables; functions; arith, flags")?;
+        writeln!(w, " 0 {} {} {} {}\t# discrete variables: binary, integer, nonlinear (b,c,o)",
+                 layout.niv, layout.nlvbi, layout.nlvci, layout.nlvoi)?;
+        writeln!(w, " {} {}\t# nonzeros in Jacobian, gradients", nzj, nzg)?;
+        writeln!(w, " 0 0\t# max name lengths: constraints, variables")?;
+        writeln!(w, " 0 0 0 0 0\t# common exprs: b,c,o,c1,o1")?;
+
+        // Constraint nonlinear parts
+        for (i, r) in rows.iter().enumerate() {
+            writeln!(w, "C{}", i)?;
+            w.write_all(r.write_nonlinear(0., &var2nl).as_bytes())?;
+        }
+
+        // Objective nonlinear part
+        writeln!(w, "O0 0")?;
+        w.write_all(obj.write_nonlinear(obj.constant, &var2nl).as_bytes())?;
+
+        // Initial point
+        if let Some(x0) = p.x0() {
+            let mut x0_nl = vec![0.; nx];
+            for (i, val) in x0.iter().enumerate() {
+                x0_nl[perm[i]] = *val;
+            }
+            writeln!(w, "x{}", nx)?;
+            for (k, val) in x0_nl.iter().enumerate() {
+                writeln!(w, "{} {}", k, val)?;
+            }
+        }
+
+        // Constraint right-hand sides
+        writeln!(w, "r")?;
+        for r in rows.iter() {
+            writeln!(w, "4 {}", -r.constant)?;
+        }
+
+        // Variable limits
+        let mut l_nl = vec![0.; nx];
+        let mut u_nl = vec![0.; nx];
+        for i in 0..nx {
+            l_nl[perm[i]] = if p.l()[i] == 0. { 0. } else { p.l()[i] }; // avoids negative zeros
+            u_nl[perm[i]] = if p.u()[i] == 0. { 0. } else { p.u()[i] };
+        }
+        writeln!(w, "b")?;
+        for (l, u) in l_nl.iter().zip(u_nl.iter()) {
+            if l == u {
+                writeln!(w, "4 {}", l)?;
+            }
+            else if *l > -INF && *u < INF {
+                writeln!(w, "0 {} {}", l, u)?;
+            }
+            else if *u < INF {
+                writeln!(w, "1 {}", u)?;
+            }
+            else if *l > -INF {
+                writeln!(w, "2 {}", l)?;
+            }
+            else {
+                writeln!(w, "3")?;
+            }
+        }
+
+        // Jacobian column counts
+        let mut col_counts = vec![0; nx];
+        for r in rows.iter() {
+            for i in r.linear.keys() {
+                col_counts[perm[*i]] += 1;
+            }
+        }
+        if nx > 0 {
+            writeln!(w, "k{}", nx-1)?;
+            let mut total: usize = 0;
+            for c in col_counts.iter().take(nx-1) {
+                total += c;
+                writeln!(w, "{}", total)?;
+            }
+        }
+
+        // Jacobian linear parts
+        for (i, r) in rows.iter().enumerate() {
+            if r.linear.is_empty() {
+                continue;
+            }
+            let mut entries: Vec<(usize, f64)> = r.linear.iter()
+                                                         .map(|(j, v)| (perm[*j], *v))
+                                                         .collect();
+            entries.sort_by_key(|(j, _)| *j);
+            writeln!(w, "J{} {}", i, entries.len())?;
+            for (j, v) in entries.iter() {
+                writeln!(w, "{} {}", j, v)?;
+            }
+        }
+
+        // Objective gradient linear part
+        if nzg > 0 {
+            let mut entries: Vec<(usize, f64)> = obj.linear.iter()
+                                                           .map(|(j, v)| (perm[*j], *v))
+                                                           .collect();
+            entries.sort_by_key(|(j, _)| *j);
+            writeln!(w, "G0 {}", entries.len())?;
+            for (j, v) in entries.iter() {
+                writeln!(w, "{} {}", j, v)?;
+            }
+        }
+
+        w.flush()?;
+
+        Ok(())
+    }
+
+    fn read_sol_file(&mut self, filename: &str) -> io::Result<NlSol> {
+
+        let (rows, obj) = self.nl_exprs();
+        let layout = NlLayout::new(self.as_minlp(), &rows, &obj);
+        let nx = self.as_minlp().nx();
+        let na = self.as_minlp().na();
+        let nf = self.f.len();
+
+        let f = File::open(filename)?;
+        let r = BufReader::new(f);
+        let e = || io::Error::other("bad solution file");
+        let lines: Vec<String> = r.lines().collect::<io::Result<Vec<String>>>()?;
+        let mut iter = lines.iter().map(|l| l.trim());
+
+        // Message
+        let mut message: Vec<&str> = Vec::new();
+        for line in &mut iter {
+            if line.is_empty() {
+                break;
+            }
+            message.push(line);
+        }
+
+        // Options
+        match iter.next() {
+            Some(line) if line.starts_with("Options") => (),
+            _ => return Err(e())
+        };
+        let mut next_int = || -> io::Result<usize> {
+            match iter.next().map(|s| s.parse::<usize>()) {
+                Some(Ok(n)) => Ok(n),
+                _ => Err(e())
+            }
+        };
+        let mut nopts = next_int()?;
+        let need_vbtol = nopts > 4;
+        if need_vbtol {
+            nopts -= 2;
+        }
+        let mut z: Vec<usize> = Vec::with_capacity(nopts+4);
+        for _ in 0..nopts+4 {
+            z.push(next_int()?);
+        }
+        if need_vbtol {
+            iter.next();
+        }
+        let num_duals = z[nopts+1];
+        let num_primals = z[nopts+3];
+
+        // Values
+        let mut next_float = || -> io::Result<f64> {
+            match iter.next().map(|s| s.parse::<f64>()) {
+                Some(Ok(x)) => Ok(x),
+                _ => Err(e())
+            }
+        };
+        let mut y: Vec<f64> = Vec::with_capacity(num_duals);
+        for _ in 0..num_duals {
+            y.push(next_float()?);
+        }
+        let mut x: Vec<f64> = Vec::with_capacity(num_primals);
+        for _ in 0..num_primals {
+            x.push(next_float()?);
+        }
+
+        // Solve result num
+        let mut solve_result_num: Option<i32> = None;
+        for line in iter {
+            let tokens: Vec<&str> = line.split_ascii_whitespace().collect();
+            if tokens.len() == 3 && tokens[0] == "objno" {
+                solve_result_num = match tokens[2].parse() {
+                    Ok(n) => Some(n),
+                    Err(_e) => return Err(e())
+                };
+                break;
+            }
+        }
+
+        // Solution
+        let mut solution = ProblemSol::new(nx, na, nf);
+        if x.len() == nx {
+            for i in 0..nx {
+                solution.x[i] = x[layout.perm[i]];
+            }
+        }
+        if y.len() == nf + na {
+            solution.nu.copy_from_slice(&y[..nf]);
+            solution.lam.copy_from_slice(&y[nf..]);
+
+            // Variable limit duals (gphi - A^T lam - J^T nu + mu - pi = 0)
+            if x.len() == nx {
+                let p = match &mut self.prob {
+                    Problem::Minlp(x) => x,
+                    Problem::Milp(x) => x.as_mut_minlp(),
+                    Problem::Nlp(x) => x.as_mut_minlp(),
+                    Problem::Lp(x) => x.as_mut_minlp(),
+                };
+                p.evaluate(&solution.x);
+                let mut res: Vec<f64> = p.gphi().to_vec();
+                for (i, j, v) in p.a().iter() {
+                    res[*j] -= v*solution.lam[*i];
+                }
+                for (i, j, v) in p.j().iter() {
+                    res[*j] -= v*solution.nu[*i];
+                }
+                for (i, r) in res.iter().enumerate() {
+                    if *r > 0. {
+                        solution.pi[i] = *r;
+                    }
+                    else {
+                        solution.mu[i] = -*r;
+                    }
+                }
+            }
+        }
+
+        Ok(NlSol {
+            message: message.join("\n"),
+            solve_result_num: solve_result_num,
+            solution: solution,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::fs;
+    use tempfile::NamedTempFile;
+
+    use super::*;
+    use crate::model::node_cmp::NodeCmp;
+    use crate::model::node_func::NodeFunc;
+    use crate::model::variable::VariableScalar;
+    use crate::model::model::{Model, Objective};
+    use crate::model::model_std::ModelStd;
+    use crate::solver::base::SolverStatus;
+    use crate::assert_vec_approx_eq;
+
+    fn model_construct() -> (Model, Vec<Node>) {
+
+        // min   x*y + 2*x + n + 3
+        // s.t.  cos(z) + x == 1
+        //       x + y + n <= 4
+        //       x >= 0
+        //       n <= 5, integer
+
+        let x = VariableScalar::new_continuous("x");
+        let y = VariableScalar::new_continuous("y");
+        let z = VariableScalar::new_continuous("z");
+        let n = VariableScalar::new_integer("n");
+
+        let mut m = Model::new();
+        m.set_objective(Objective::minimize(&(&x*&y + 2.*&x + &n + 3.)));
+        m.add_constraint(&(z.cos() + &x).equal(1.));
+        m.add_constraint(&(&x + &y + &n).leq(4.));
+        m.add_constraint(&x.geq(0.));
+        m.add_constraint(&n.leq(5.));
+        (m, vec![x, y, z, n])
+    }
+
+    #[test]
+    fn model_nl_write_to_nl_file() {
+
+        let (m, _vars) = model_construct();
+        let std_prob = m.std_problem();
+        let f = NamedTempFile::new().unwrap();
+        let fname = f.path().to_str().unwrap();
+        std_prob.write_to_nl_file(fname).unwrap();
+
+        // Variables in NL order: z, x, y, _s_a0_, n
+        let content = fs::read_to_string(fname).unwrap();
+        assert_eq!(content,
+                   "g3 1 1 0\t# problem numopt\n \
+                    5 2 1 0 2\t# vars, constraints, objectives, ranges, eqns\n \
+                    1 1\t# nonlinear constraints, objectives\n \
+                    0 0\t# network constraints: nonlinear, linear\n \
+                    1 3 0\t# nonlinear vars in constraints, objectives, both\n \
+                    0 0 0 1\t# linear network variables; functions; arith, flags\n \
+                    0 1 0 0 0\t# discrete variables: binary, integer, nonlinear (b,c,o)\n \
+                    6 3\t# nonzeros in Jacobian, gradients\n \
+                    0 0\t# max name lengths: constraints, variables\n \
+                    0 0 0 0 0\t# common exprs: b,c,o,c1,o1\n\
+                    C0\n\
+                    o46\n\
+                    v0\n\
+                    C1\n\
+                    n0\n\
+                    O0 0\n\
+                    o0\n\
+                    o2\n\
+                    v1\n\
+                    v2\n\
+                    n3\n\
+                    x5\n\
+                    0 0\n\
+                    1 0\n\
+                    2 0\n\
+                    3 0\n\
+                    4 0\n\
+                    r\n\
+                    4 1\n\
+                    4 4\n\
+                    b\n\
+                    3\n\
+                    2 0\n\
+                    3\n\
+                    1 0\n\
+                    1 5\n\
+                    k4\n\
+                    1\n\
+                    3\n\
+                    4\n\
+                    5\n\
+                    J0 2\n\
+                    0 0\n\
+                    1 1\n\
+                    J1 4\n\
+                    1 1\n\
+                    2 1\n\
+                    3 -1\n\
+                    4 1\n\
+                    G0 3\n\
+                    1 2\n\
+                    2 0\n\
+                    4 1\n");
+    }
+
+    #[test]
+    fn model_nl_read_sol_file() {
+
+        let (mut m, vars) = model_construct();
+        let mut std_prob = m.std_problem();
+        let f = NamedTempFile::new().unwrap();
+        let fname = f.path().to_str().unwrap();
+
+        // Duals of constraints and primals in NL order: z, x, y, _s_a0_, n
+        fs::write(fname,
+                  "Ipopt 3.14.4: Optimal Solution Found\n\
+                   \n\
+                   Options\n\
+                   3\n\
+                   1\n\
+                   1\n\
+                   0\n\
+                   2\n\
+                   2\n\
+                   5\n\
+                   5\n\
+                   -2\n\
+                   -0.5\n\
+                   0\n\
+                   0\n\
+                   -5\n\
+                   0\n\
+                   5\n\
+                   objno 0 0\n").unwrap();
+        let nl_sol = std_prob.read_sol_file(fname).unwrap();
+        assert_eq!(nl_sol.message, "Ipopt 3.14.4: Optimal Solution Found");
+        assert_eq!(nl_sol.solve_result_num, Some(0));
+        let solution = &nl_sol.solution;
+        assert_vec_approx_eq!(solution.nu, &vec![-2.], epsilon=0.);
+        assert_vec_approx_eq!(solution.lam, &vec![-0.5], epsilon=0.);
+
+        // Optimality conditions
+        let p = std_prob.as_minlp();
+        let mut res: Vec<f64> = p.gphi().to_vec();
+        for (i, j, v) in p.a().iter() {
+            res[*j] -= v*solution.lam[*i];
+        }
+        for (i, j, v) in p.j().iter() {
+            res[*j] -= v*solution.nu[*i];
+        }
+        for i in 0..res.len() {
+            res[i] += solution.mu[i] - solution.pi[i];
+        }
+        assert_vec_approx_eq!(res, &vec![0.; 5], epsilon=1e-12);
+        assert!(solution.mu.iter().all(|v| *v >= 0.));
+        assert!(solution.pi.iter().all(|v| *v >= 0.));
+
+        // Model results
+        m.set_solution(&std_prob, SolverStatus::Solved, solution);
+        let final_primals = m.final_primals();
+        assert_eq!(*final_primals.get(&vars[0]).unwrap(), 0.);
+        assert_eq!(*final_primals.get(&vars[1]).unwrap(), -5.);
+        assert_eq!(*final_primals.get(&vars[2]).unwrap(), 0.);
+        assert_eq!(*final_primals.get(&vars[3]).unwrap(), 5.);
+
+        // No solution
+        fs::write(fname,
+                  "Solver: infeasible problem\n\
+                   \n\
+                   Options\n\
+                   3\n\
+                   1\n\
+                   1\n\
+                   0\n\
+                   2\n\
+                   0\n\
+                   5\n\
+                   0\n\
+                   objno 0 200\n").unwrap();
+        let nl_sol = std_prob.read_sol_file(fname).unwrap();
+        assert_eq!(nl_sol.solve_result_num, Some(200));
+        assert_vec_approx_eq!(nl_sol.solution.x, &vec![0.; 5], epsilon=0.);
+    }
+}
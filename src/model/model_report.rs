@@ -0,0 +1,382 @@
+//! Solution reports of optimization models.
+
+use std::io;
+use std::fs;
+use std::fmt;
+use std::collections::HashSet;
+
+use crate::model::node::Node;
+use crate::model::node_base::NodeBase;
+use crate::model::node_std::NodeStd;
+use crate::model::constraint::ConstraintKind;
+use crate::model::model::{Model, Objective};
+use crate::model::model_std::{ModelStd, sort_vars};
+
+/// Row of a solution report for a variable.
+pub struct ModelVarReport {
+
+    /// Variable name.
+    pub name: String,
+
+    /// Final value, if available.
+    pub value: Option<f64>,
+
+    /// Lower limit from the bound constraints of the model.
+    pub lower: f64,
+
+    /// Upper limit from the bound constraints of the model.
+    pub upper: f64,
+
+    /// Final reduced cost, if available.
+    pub reduced_cost: Option<f64>,
+}
+
+/// Row of a solution report for a constraint.
+pub struct ModelConstrReport {
+
+    /// Constraint label, or constraint expression if it has no label.
+    pub name: String,
+
+    /// Constraint sense ("==", "<=" or ">=").
+    pub sense: String,
+
+    /// Final value of the left-hand side, if available.
+    pub body: Option<f64>,
+
+    /// Final value of the right-hand side, if available.
+    pub rhs: Option<f64>,
+
+    /// Final slack, i.e., the difference between the right-hand side and the
+    /// left-hand side (or vice versa for ">=" constraints), if available.
+    pub slack: Option<f64>,
+
+    /// Final dual value, if available.
+    pub dual: Option<f64>,
+
+    /// Final violation, if available.
+    pub violation: Option<f64>,
+}
+
+/// Solution report of an optimization model, with variables in the order of the
+/// standard problem of the model, i.e., sorted by name, and constraints sorted by
+/// label (or expression), and otherwise in the order in which they appear in the model.
+pub struct ModelSolutionReport {
+
+    /// Final objective function value, if available.
+    pub objective: Option<f64>,
+
+    /// Variable rows.
+    pub variables: Vec<ModelVarReport>,
+
+    /// Constraint rows.
+    pub constraints: Vec<ModelConstrReport>,
+}
+
+/// Column headers of variable rows.
+const VAR_HEADERS: [&str; 5] = ["name", "value", "lower", "upper", "reduced cost"];
+
+/// Column headers of constraint rows.
+const CONSTR_HEADERS: [&str; 7] = ["name", "sense", "body", "rhs", "slack", "dual", "violation"];
+
+impl ModelSolutionReport {
+
+    /// Creates solution report of model from its final primal and dual values.
+    /// Values that are not available, e.g., before the model is solved, are left empty.
+    pub fn new(model: &Model) -> Self {
+
+        let solved = model.solver_status().is_some();
+        let primals = model.final_primals();
+        let duals = model.final_duals();
+        let reduced_costs = model.reduced_costs();
+
+        // Variables
+        let mut vars: Vec<Node> = Vec::new();
+        let mut varset: HashSet<Node> = HashSet::new();
+        let mut exprs: Vec<Node> = Vec::new();
+        if let Objective::Minimize(f) | Objective::Maximize(f) = model.objective() {
+            exprs.push(f.clone());
+        }
+        exprs.extend(model.constraints().iter().map(|c| c.lhs()-c.rhs()));
+        for e in exprs.iter() {
+            for var in e.std_properties().a.into_keys() {
+                if varset.insert(var.clone()) {
+                    vars.push(var);
+                }
+            }
+        }
+        sort_vars(model, &mut vars);
+
+        // Bounds
+        let comp = model.std_components();
+        let bound = |var: &Node| {
+            let l = comp.constr.l.iter().filter(|(x, _v, _c)| x == var).fold(-f64::INFINITY, |b, (_x, v, _c)| b.max(*v));
+            let u = comp.constr.u.iter().filter(|(x, _v, _c)| x == var).fold(f64::INFINITY, |b, (_x, v, _c)| b.min(*v));
+            (l, u)
+        };
+
+        let variables = vars.iter().map(|var| {
+            let (lower, upper) = bound(var);
+            ModelVarReport {
+                name: var.name().to_string(),
+                value: primals.get(var).cloned(),
+                lower: lower,
+                upper: upper,
+                reduced_cost: reduced_costs.get(var).cloned(),
+            }
+        }).collect();
+
+        // Constraints
+        let mut constraints: Vec<ModelConstrReport> = model.constraints().iter().map(|c| {
+            let body = if solved { Some(c.lhs().evaluate(&primals)) } else { None };
+            let rhs = if solved { Some(c.rhs().evaluate(&primals)) } else { None };
+            let (sense, slack) = match c.kind() {
+                ConstraintKind::Equal => ("==", rhs.zip(body).map(|(r, b)| r - b)),
+                ConstraintKind::LessEqual => ("<=", rhs.zip(body).map(|(r, b)| r - b)),
+                ConstraintKind::GreaterEqual => (">=", rhs.zip(body).map(|(r, b)| b - r)),
+            };
+            ModelConstrReport {
+                name: if c.label() != "" { c.label().to_string() } else { format!("{}", c) },
+                sense: sense.to_string(),
+                body: body,
+                rhs: rhs,
+                slack: slack,
+                dual: duals.get(c).cloned(),
+                violation: if solved { Some(c.violation(&primals)) } else { None },
+            }
+        }).collect();
+        constraints.sort_by(|x, y| x.name.cmp(&y.name));
+
+        // Objective
+        let objective = match model.objective() {
+            Objective::Minimize(f) | Objective::Maximize(f) if solved => Some(f.evaluate(&primals)),
+            _ => None,
+        };
+
+        Self {
+            objective: objective,
+            variables: variables,
+            constraints: constraints,
+        }
+    }
+
+    /// Gets variable rows as CSV, with a header line.
+    pub fn variables_csv(&self) -> String {
+        let rows: Vec<Vec<String>> = self.variables.iter().map(|v| {
+            vec![v.name.clone(), csv_value(v.value), csv_value(Some(v.lower)),
+                 csv_value(Some(v.upper)), csv_value(v.reduced_cost)]
+        }).collect();
+        csv_table(&VAR_HEADERS, &rows)
+    }
+
+    /// Gets constraint rows as CSV, with a header line.
+    pub fn constraints_csv(&self) -> String {
+        let rows: Vec<Vec<String>> = self.constraints.iter().map(|c| {
+            vec![c.name.clone(), c.sense.clone(), csv_value(c.body), csv_value(c.rhs),
+                 csv_value(c.slack), csv_value(c.dual), csv_value(c.violation)]
+        }).collect();
+        csv_table(&CONSTR_HEADERS, &rows)
+    }
+
+    /// Writes variable and constraint rows to CSV files.
+    pub fn write_to_csv_files(&self, var_filename: &str, constr_filename: &str) -> io::Result<()> {
+        fs::write(var_filename, self.variables_csv())?;
+        fs::write(constr_filename, self.constraints_csv())
+    }
+
+    /// Gets report as plain text, with aligned columns.
+    pub fn to_text(&self) -> String {
+        format!("{}", self)
+    }
+}
+
+impl fmt::Display for ModelSolutionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Objective : {}", text_value(self.objective))?;
+        let rows: Vec<Vec<String>> = self.variables.iter().map(|v| {
+            vec![v.name.clone(), text_value(v.value), text_value(Some(v.lower)),
+                 text_value(Some(v.upper)), text_value(v.reduced_cost)]
+        }).collect();
+        write!(f, "\nVariables\n{}", text_table(&VAR_HEADERS, &rows))?;
+        let rows: Vec<Vec<String>> = self.constraints.iter().map(|c| {
+            vec![c.name.clone(), c.sense.clone(), text_value(c.body), text_value(c.rhs),
+                 text_value(c.slack), text_value(c.dual), text_value(c.violation)]
+        }).collect();
+        write!(f, "\nConstraints\n{}", text_table(&CONSTR_HEADERS, &rows))
+    }
+}
+
+/// Formats value for CSV, with an empty field if not available.
+fn csv_value(value: Option<f64>) -> String {
+    value.map(|x| format!("{}", x)).unwrap_or_default()
+}
+
+/// Formats value for plain text, with a dash if not available.
+fn text_value(value: Option<f64>) -> String {
+    value.map(|x| format!("{:.6e}", x)).unwrap_or_else(|| "-".to_string())
+}
+
+/// Formats CSV table, quoting fields with commas, quotes or line breaks.
+fn csv_table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let field = |s: &str| {
+        if s.contains([',', '"', '\n']) {
+            format!("\"{}\"", s.replace("\"", "\"\""))
+        }
+        else {
+            s.to_string()
+        }
+    };
+    let mut out = headers.join(",");
+    out.push('\n');
+    for row in rows.iter() {
+        out.push_str(&row.iter().map(|s| field(s)).collect::<Vec<String>>().join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// Formats plain text table, with the first column aligned left and the others right.
+fn text_table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.len()).collect();
+    for row in rows.iter() {
+        for (w, s) in widths.iter_mut().zip(row.iter()) {
+            *w = (*w).max(s.chars().count());
+        }
+    }
+    let line = |cells: Vec<&str>| {
+        let s: Vec<String> = cells.iter().zip(widths.iter()).enumerate().map(|(k, (s, w))| {
+            if k == 0 { format!("{:<w$}", s, w = w) } else { format!("{:>w$}", s, w = w) }
+        }).collect();
+        format!("{}\n", s.join("  ").trim_end())
+    };
+    let mut out = line(headers.to_vec());
+    for row in rows.iter() {
+        out.push_str(&line(row.iter().map(|s| s.as_str()).collect()));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::fs;
+    use tempfile::NamedTempFile;
+    use approx::assert_abs_diff_eq;
+
+    use super::*;
+    use crate::problem::base::ProblemSol;
+    use crate::solver::base::SolverStatus;
+    use crate::model::node_cmp::NodeCmp;
+    use crate::model::variable::VariableScalar;
+
+    #[test]
+    fn model_solution_report() {
+
+        let y = VariableScalar::new_continuous("y");
+        let x = VariableScalar::new_continuous("x");
+
+        let c1 = (&x + &y).leq_and_tag(4., "capacity");
+        let c2 = (&x).geq(1.);
+        let c3 = (&x - 2.*&y).equal_and_tag(0., "ratio, fixed");
+
+        let mut m = Model::new();
+        m.set_objective(Objective::maximize(&(3.*&x + &y)));
+        m.add_constraints(&vec!(&c1, &c2, &c3));
+
+        // Before solve
+        let report = m.solution_report();
+        assert_eq!(report.objective, None);
+        assert_eq!(report.variables.iter().map(|v| v.name.as_str()).collect::<Vec<&str>>(), vec!["x", "y"]);
+        assert_eq!(report.variables[0].lower, 1.);
+        assert_eq!(report.variables[0].upper, f64::INFINITY);
+        assert_eq!(report.variables[1].lower, -f64::INFINITY);
+        assert!(report.variables.iter().all(|v| v.value.is_none() && v.reduced_cost.is_none()));
+        assert!(report.constraints.iter().all(|c| c.body.is_none() && c.dual.is_none()));
+        assert_eq!(report.variables_csv(), "name,value,lower,upper,reduced cost\nx,,1,inf,\ny,,-inf,inf,\n");
+
+        // After solve
+        let std_prob = m.std_problem();
+        let mut sol = ProblemSol::new(std_prob.var2index.len(), 2, 0);
+        let ix = *std_prob.var2index.get(&x).unwrap();
+        let iy = *std_prob.var2index.get(&y).unwrap();
+        let row = |c| *std_prob.aindex2constr.iter().find(|(_i, d)| **d == c).unwrap().0;
+        sol.x[ix] = 8./3.;
+        sol.x[iy] = 4./3.;
+        sol.lam[row(c1.clone())] = 7./3.;
+        let slack = std_prob.var2index.iter().find(|(v, _i)| v.name() == format!("_s_a{}_", row(c1.clone()))).unwrap().1;
+        sol.mu[*slack] = 7./3.;
+        sol.lam[row(c3.clone())] = -2./3.;
+        m.set_solution(&std_prob, SolverStatus::Solved, &sol);
+
+        let report = m.solution_report();
+        assert_abs_diff_eq!(report.objective.unwrap(), 28./3., epsilon = 1e-12);
+        assert_eq!(report.variables[0].value, Some(8./3.));
+        assert_eq!(report.variables[1].value, Some(4./3.));
+        assert_eq!(report.variables[0].reduced_cost, Some(0.));
+        let names: Vec<&str> = report.constraints.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["capacity", "ratio, fixed", "x >= 1"]);
+        let capacity = &report.constraints[0];
+        assert_eq!(capacity.sense, "<=");
+        assert_eq!(capacity.body, Some(4.));
+        assert_eq!(capacity.rhs, Some(4.));
+        assert_eq!(capacity.slack, Some(0.));
+        assert_eq!(capacity.dual, Some(7./3.));
+        assert_eq!(capacity.violation, Some(0.));
+        let bound = &report.constraints[2];
+        assert_eq!(bound.sense, ">=");
+        assert_abs_diff_eq!(bound.slack.unwrap(), 5./3., epsilon = 1e-12);
+        assert_eq!(bound.violation, Some(0.));
+
+        // Text
+        let text = report.to_text();
+        assert_eq!(text, format!("{}", report));
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "Objective : 9.333333e0");
+        assert_eq!(lines[2], "Variables");
+        assert_eq!(lines[3], "name       value       lower  upper  reduced cost");
+        assert_eq!(lines[4], "x     2.666667e0  1.000000e0    inf    0.000000e0");
+        assert_eq!(lines[7], "Constraints");
+        assert!(lines[8].starts_with("name          sense"));
+        assert!(lines[9].starts_with("capacity         <=  4.000000e0"));
+        assert_eq!(lines.len(), 12);
+
+        // CSV
+        let csv = report.constraints_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "name,sense,body,rhs,slack,dual,violation");
+        assert_eq!(lines[1], format!("capacity,<=,4,4,0,{},0", 7./3.));
+        assert!(lines[2].starts_with("\"ratio, fixed\",==,"));
+        assert_eq!(lines.len(), 4);
+
+        let fv = NamedTempFile::new().unwrap();
+        let fc = NamedTempFile::new().unwrap();
+        let fv_name = fv.path().to_str().unwrap();
+        let fc_name = fc.path().to_str().unwrap();
+        report.write_to_csv_files(fv_name, fc_name).unwrap();
+        assert_eq!(fs::read_to_string(fv_name).unwrap(), report.variables_csv());
+        assert_eq!(fs::read_to_string(fc_name).unwrap(), csv);
+    }
+
+    #[test]
+    fn model_solution_report_equal_names() {
+
+        // Variables with equal names in the order in which they first appear
+        let x1 = VariableScalar::new_continuous("x");
+        let x2 = VariableScalar::new_continuous("x");
+        let x3 = VariableScalar::new_continuous("x");
+
+        let mut m = Model::new();
+        m.set_objective(Objective::minimize(&(&x3 + &x1)));
+        m.add_constraint(&(&x1 + &x2 + &x3).leq(10.));
+        m.add_constraint(&(&x1).geq(1.));
+        m.add_constraint(&(&x2).geq(2.));
+        m.add_constraint(&(&x3).geq(3.));
+
+        for _ in 0..10 {
+            let std_prob = m.std_problem();
+            let report = m.solution_report();
+            assert_eq!(report.variables.iter().map(|v| v.lower).collect::<Vec<f64>>(), vec![3., 1., 2.]);
+            assert!(std_prob.var2index[&x3] < std_prob.var2index[&x1]);
+            assert!(std_prob.var2index[&x1] < std_prob.var2index[&x2]);
+        }
+    }
+}
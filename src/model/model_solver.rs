@@ -0,0 +1,25 @@
+//! Solvers of optimization models.
+
+use simple_error::SimpleError;
+
+use crate::solver::base::{Solver, SolverStatus};
+use crate::problem::base::{Problem, ProblemSol};
+use crate::model::model_std::ModelStdProb;
+
+/// A trait for optimization solvers that solve optimization models.
+///
+/// By default, the standard problem of the model is solved as any other problem.
+/// Solvers that need the model expressions, e.g., to write them to a file,
+/// override [solve_std](#method.solve_std) and
+/// [uses_model_expressions](#method.uses_model_expressions).
+pub trait ModelSolver: Solver {
+
+    /// Solves optimization problem obtained from an optimization model.
+    fn solve_std(&self, std_prob: &mut ModelStdProb) -> Result<(SolverStatus, ProblemSol), SimpleError> {
+        self.solve(&mut std_prob.prob)
+    }
+
+    /// Checks whether the solver solves the standard problems of models of the type of
+    /// the given problem from the model expressions, instead of from the problem itself.
+    fn uses_model_expressions(&self, _problem: &Problem) -> bool { false }
+}
@@ -12,12 +12,14 @@ use crate::problem::lp::ProblemLp;
 use crate::model::node::Node;
 use crate::model::node_base::NodeBase;
 use crate::model::node_std::{NodeStd, NodeStdComp};
+use crate::model::node_tape::NodeTape;
 use crate::model::constant::ConstantScalar;
+use crate::model::function::add::FunctionAdd;
 use crate::model::constraint::Constraint;
 use crate::model::constraint_std::{ConstraintStd, ConstraintStdComp};
 use crate::model::model::{Model, Objective};
 
-const INF: f64 = 1e8;
+pub(crate) const INF: f64 = 1e8;
 
 /// Optimization model standard components.
 pub struct ModelStdComp {
@@ -50,6 +52,12 @@ pub struct ModelStdProb {
 
     /// Map between variable lower limit and model constraint.
     pub lindex2constr: HashMap<usize, Constraint>,
+
+    /// Objective function expression (to be minimized).
+    pub phi: Node,
+
+    /// Nonlinear equality constraint expressions (f(x) = 0).
+    pub f: Vec<Node>,
 }
 
 /// A trait for transforming optimization models to problems in standard form.
@@ -66,13 +74,6 @@ impl ModelStd for Model {
 
     fn std_components(&self) -> ModelStdComp {
 
-        // Objective std comp
-        let obj = match self.objective() {
-            Objective::Maximize(f) => (-f).std_components(),
-            Objective::Minimize(f) => f.std_components(),
-            Objective::Empty => ConstantScalar::new(0.).std_components(),
-        };
-
         // Constraint std comp
         let mut arow: usize = 0;
         let mut jrow: usize = 0;
@@ -81,6 +82,21 @@ impl ModelStd for Model {
             constr += c.std_components(&mut arow, &mut jrow);
         }
 
+        // Objective std comp (with penalties of soft constraints)
+        let f = match self.objective() {
+            Objective::Maximize(f) => -f,
+            Objective::Minimize(f) => f.clone(),
+            Objective::Empty => ConstantScalar::new(0.),
+        };
+        let obj = if constr.v.is_empty() {
+            f.std_components()
+        }
+        else {
+            let mut args = vec![f];
+            args.extend(constr.v.iter().map(|(var, weight, _c)| *weight*var));
+            FunctionAdd::new(args).std_components()
+        };
+
         // Return
         ModelStdComp {
             obj: obj,
@@ -102,7 +118,7 @@ impl ModelStd for Model {
         }
         let num_vars: usize = varset.len();
         let mut vars: Vec<Node> = varset.into_iter().collect();
-        vars.sort_by(|x,y| x.name().partial_cmp(y.name()).unwrap());
+        sort_vars(self, &mut vars);
         let var2index: HashMap<Node, usize> = vars.into_iter()
                                                   .enumerate()
                                                   .map(|(i,v)| (v,i))
@@ -113,6 +129,7 @@ impl ModelStd for Model {
     
         // Objective (phi)
         let phi_data = comp.obj.phi;
+        let phi = phi_data.clone();
         let mut gphi_indices: Vec<usize> = Vec::with_capacity(comp.obj.gphi.len());
         let mut gphi_data: Vec<Node> = Vec::with_capacity(comp.obj.gphi.len());
         for (v, e) in comp.obj.gphi.into_iter() {
@@ -190,6 +207,7 @@ impl ModelStd for Model {
             vec![0.; j_data.len()]
         );
         let f_data = comp.constr.f;
+        let f = f_data.clone();
         let mut h_data: Vec<Vec<Node>> = Vec::with_capacity(num_j);
         let mut h_vec: Vec<CooMat<f64>> = Vec::with_capacity(num_j);
         for hh in comp.constr.h.into_iter() {
@@ -237,6 +255,9 @@ impl ModelStd for Model {
                 lindex2constr.insert(index, constr);
             }
         }
+        for (var, _weight, _constr) in comp.constr.v.iter() {
+            l_data[*var2index.get(var).unwrap()] = 0.;
+        }
 
         // Integer restrictions
         let mut num_int: usize = 0;
@@ -262,6 +283,22 @@ impl ModelStd for Model {
             }
         }
        
+        // Evaluation tape with shared subexpressions of all expressions
+        let num_gphi = gphi_data.len();
+        let num_hphi = hphi_data.len();
+        let num_f = f_data.len();
+        let num_jdata = j_data.len();
+        let mut exprs: Vec<Node> = Vec::with_capacity(1 + num_gphi + num_hphi + num_f + num_jdata);
+        exprs.push(phi_data);
+        exprs.extend(gphi_data);
+        exprs.extend(hphi_data);
+        exprs.extend(f_data);
+        exprs.extend(j_data);
+        for hh_data in h_data.into_iter() {
+            exprs.extend(hh_data);
+        }
+        let tape = NodeTape::new(&exprs, &var2index_eval);
+
         // Eval
         let eval_fn = Box::new(move | phi: &mut f64, 
                                       gphi: &mut Vec<f64>, 
@@ -271,42 +308,41 @@ impl ModelStd for Model {
                                       h: &mut Vec<CooMat<f64>>,
                                       x: &[f64] | {
 
-            // Var values
-            let mut var_values: HashMap<&Node, f64> = HashMap::with_capacity(x.len());
-            for (var, index) in var2index_eval.iter() {
-                var_values.insert(var, x[*index]);
-            }
+            // Values of all expressions
+            let values = tape.evaluate(x);
+            let mut values = values.iter();
 
             // phi
-            *phi = phi_data.evaluate(&var_values);
+            *phi = *values.next().unwrap();
 
             // gphi
-            for (index, exp) in gphi_indices.iter().zip(gphi_data.iter()) {
-                (*gphi)[*index] = exp.evaluate(&var_values);
+            for (index, val) in gphi_indices.iter().zip(values.by_ref().take(num_gphi)) {
+                (*gphi)[*index] = *val;
             }
 
             // hphi
             let hphi_dest = hphi.data_mut();
-            for (val, exp) in hphi_dest.iter_mut().zip(hphi_data.iter()) {
-                *val = exp.evaluate(&var_values);           
+            for (dest, val) in hphi_dest.iter_mut().zip(values.by_ref().take(num_hphi)) {
+                *dest = *val;
             }
 
             // f
-            for (val, exp) in f.iter_mut().zip(f_data.iter()) {
-                *val = exp.evaluate(&var_values);
+            for (dest, val) in f.iter_mut().zip(values.by_ref().take(num_f)) {
+                *dest = *val;
             }
 
             // j
             let j_dest = j.data_mut();
-            for (val, exp) in j_dest.iter_mut().zip(j_data.iter()) {
-                *val = exp.evaluate(&var_values);
+            for (dest, val) in j_dest.iter_mut().zip(values.by_ref().take(num_jdata)) {
+                *dest = *val;
             }
             
             // h
-            for (hh, hh_data) in h.iter_mut().zip(h_data.iter()) {
+            for hh in h.iter_mut() {
                 let hh_dest = hh.data_mut();
-                for (val, exp) in hh_dest.iter_mut().zip(hh_data.iter()) {
-                    *val = exp.evaluate(&var_values);
+                let n = hh_dest.len();
+                for (dest, val) in hh_dest.iter_mut().zip(values.by_ref().take(n)) {
+                    *dest = *val;
                 }
             }
         });
@@ -386,14 +422,50 @@ impl ModelStd for Model {
             jindex2constr: jindex2constr,
             uindex2constr: uindex2constr,
             lindex2constr: lindex2constr,
+            phi: phi,
+            f: f,
+        }
+    }
+}
+
+/// Sorts variables in the order of the standard problem of the model, i.e., by name,
+/// and variables with equal names in the order in which they first appear in the
+/// objective and constraint expressions.
+pub(crate) fn sort_vars(model: &Model, vars: &mut [Node]) {
+
+    let mut exprs: Vec<&Node> = Vec::new();
+    if let Objective::Minimize(f) | Objective::Maximize(f) = model.objective() {
+        exprs.push(f);
+    }
+    for c in model.constraints().iter() {
+        exprs.push(c.lhs());
+        exprs.push(c.rhs());
+    }
+
+    // Depth-first search
+    let mut order: HashMap<Node, usize> = HashMap::new();
+    let mut visited: HashSet<Node> = HashSet::new();
+    let mut stack: Vec<&Node> = exprs.into_iter().rev().collect();
+    while let Some(node) = stack.pop() {
+        if !visited.insert(node.clone()) {
+            continue;
+        }
+        if let Node::VariableScalar(_x) = node {
+            let n = order.len();
+            order.insert(node.clone(), n);
         }
+        stack.extend(node.arguments().into_iter().rev());
     }
+
+    let rank = |x: &Node| order.get(x).cloned().unwrap_or(usize::MAX);
+    vars.sort_by(|x, y| x.name().cmp(y.name()).then(rank(x).cmp(&rank(y))));
 }
 
 #[cfg(test)]
 mod tests {
 
     use maplit::hashmap;
+    use approx::assert_abs_diff_eq;
 
     use super::*;
     use crate::problem::base::Problem;
@@ -612,7 +684,7 @@ mod tests {
         assert_vec_approx_eq!(nlp.b(), vec![7.], epsilon=0.);
 
         assert_eq!(nlp.f().len(), 2);
-        assert_eq!(nlp.f()[0], 4_f64.sin() + 4.*5. + 4. - 4. - 10. - 2.);
+        assert_abs_diff_eq!(nlp.f()[0], 4_f64.sin() + 4.*5. + 4. - 4. - 10. - 2., epsilon = 1e-12);
         assert_eq!(nlp.f()[1], 5. - 4.*4. - 3.);
         assert_eq!(nlp.j().nnz(), 5);
         for (row, col, val) in nlp.j().iter() {
@@ -705,4 +777,45 @@ mod tests {
         assert_eq!(std_p.lindex2constr.len(), 1);
         assert_eq!(*std_p.lindex2constr.get(&1).unwrap(), c3);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn model_std_problem_soft() {
+
+        let x = VariableScalar::new_continuous("x");
+        let y = VariableScalar::new_continuous("y");
+
+        let c1 = (&x + &y).geq(4.).soft(10.).unwrap();
+        let c2 = x.leq(1.).soft(2.).unwrap();
+        let c3 = (&x - &y).equal(0.).soft(3.).unwrap();
+
+        let mut m = Model::new();
+        m.set_objective(Objective::maximize(&(-&x - &y)));
+        m.add_constraints(&vec!(&c1, &c2, &c3));
+
+        let std_p = m.std_problem();
+        let names: Vec<&str> = {
+            let mut v: Vec<(&Node, &usize)> = std_p.var2index.iter().collect();
+            v.sort_by_key(|(_var, i)| **i);
+            v.into_iter().map(|(var, _i)| var.name()).collect()
+        };
+        assert_eq!(names, vec!["_s_a0_", "_s_a1_", "_vl_a0_", "_vl_a2_", "_vu_a1_", "_vu_a2_", "x", "y"]);
+        assert_eq!(std_p.aindex2constr.len(), 3);
+        assert_eq!(std_p.uindex2constr.len(), 1);
+        assert_eq!(std_p.lindex2constr.len(), 1);
+
+        let lp = match std_p.prob {
+            Problem::Lp(x) => x,
+            _ => panic!("invalid std problem")
+        };
+
+        assert_vec_approx_eq!(lp.c(), vec![0., 0., 10., 3., 2., 3., 1., 1.], epsilon=0.);
+        assert_vec_approx_eq!(lp.b(), vec![4., 1., 0.], epsilon=0.);
+        assert_vec_approx_eq!(lp.l(), vec![0., -1e8, 0., 0., 0., 0., -1e8, -1e8], epsilon=0.);
+        assert_vec_approx_eq!(lp.u(), vec![1e8, 0., 1e8, 1e8, 1e8, 1e8, 1e8, 1e8], epsilon=0.);
+        let mut a: Vec<(usize, usize, f64)> = lp.a().iter().map(|(i, j, v)| (*i, *j, *v)).collect();
+        a.sort_by(|p, q| p.partial_cmp(q).unwrap());
+        assert_eq!(a, vec![(0, 0, -1.), (0, 2, 1.), (0, 6, 1.), (0, 7, 1.),
+                           (1, 1, -1.), (1, 4, -1.), (1, 6, 1.),
+                           (2, 3, 1.), (2, 5, -1.), (2, 6, 1.), (2, 7, -1.)]);
+    }
+}
@@ -2,7 +2,6 @@
 
 use std::fmt;
 use std::ptr;
-use std::rc::Rc;
 use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
 use num_traits::cast::ToPrimitive;
@@ -18,15 +17,29 @@ use crate::model::function::div::FunctionDiv;
 use crate::model::function::cos::FunctionCos;
 use crate::model::function::sin::FunctionSin;
 
+/// Reference-counted pointer shared by expression nodes and constraints.
+/// This is [Rc](https://doc.rust-lang.org/std/rc/struct.Rc.html) by default, and
+/// [Arc](https://doc.rust-lang.org/std/sync/struct.Arc.html) with the "sync" feature,
+/// in which case models can be built and solved across threads.
+#[cfg(not(feature = "sync"))]
+pub use std::rc::Rc as NodeRc;
+
+/// Reference-counted pointer shared by expression nodes and constraints.
+/// This is [Rc](https://doc.rust-lang.org/std/rc/struct.Rc.html) by default, and
+/// [Arc](https://doc.rust-lang.org/std/sync/struct.Arc.html) with the "sync" feature,
+/// in which case models can be built and solved across threads.
+#[cfg(feature = "sync")]
+pub use std::sync::Arc as NodeRc;
+
 /// Expression node.
 pub enum Node {
-    ConstantScalar(Rc<ConstantScalar>),
-    VariableScalar(Rc<VariableScalar>),
-    FunctionAdd(Rc<FunctionAdd>),
-    FunctionCos(Rc<FunctionCos>),
-    FunctionDiv(Rc<FunctionDiv>),
-    FunctionMul(Rc<FunctionMul>),
-    FunctionSin(Rc<FunctionSin>),
+    ConstantScalar(NodeRc<ConstantScalar>),
+    VariableScalar(NodeRc<VariableScalar>),
+    FunctionAdd(NodeRc<FunctionAdd>),
+    FunctionCos(NodeRc<FunctionCos>),
+    FunctionDiv(NodeRc<FunctionDiv>),
+    FunctionMul(NodeRc<FunctionMul>),
+    FunctionSin(NodeRc<FunctionSin>),
 }
 
 impl Node {
@@ -75,13 +88,13 @@ impl PartialEq for Node {
 
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
-            (Node::ConstantScalar(x), Node::ConstantScalar(y)) => Rc::ptr_eq(x, y),
-            (Node::VariableScalar(x), Node::VariableScalar(y)) => Rc::ptr_eq(x, y),
-            (Node::FunctionAdd(x), Node::FunctionAdd(y)) => Rc::ptr_eq(x, y),
-            (Node::FunctionCos(x), Node::FunctionCos(y)) => Rc::ptr_eq(x, y),
-            (Node::FunctionDiv(x), Node::FunctionDiv(y)) => Rc::ptr_eq(x, y),
-            (Node::FunctionMul(x), Node::FunctionMul(y)) => Rc::ptr_eq(x, y),
-            (Node::FunctionSin(x), Node::FunctionSin(y)) => Rc::ptr_eq(x, y),
+            (Node::ConstantScalar(x), Node::ConstantScalar(y)) => NodeRc::ptr_eq(x, y),
+            (Node::VariableScalar(x), Node::VariableScalar(y)) => NodeRc::ptr_eq(x, y),
+            (Node::FunctionAdd(x), Node::FunctionAdd(y)) => NodeRc::ptr_eq(x, y),
+            (Node::FunctionCos(x), Node::FunctionCos(y)) => NodeRc::ptr_eq(x, y),
+            (Node::FunctionDiv(x), Node::FunctionDiv(y)) => NodeRc::ptr_eq(x, y),
+            (Node::FunctionMul(x), Node::FunctionMul(y)) => NodeRc::ptr_eq(x, y),
+            (Node::FunctionSin(x), Node::FunctionSin(y)) => NodeRc::ptr_eq(x, y),
             _ => false,
         }
     }
@@ -92,13 +105,13 @@ impl Eq for Node {}
 impl Clone for Node {
     fn clone(&self) -> Self {
         match self {
-            Node::ConstantScalar(x) => Node::ConstantScalar(Rc::clone(&x)),
-            Node::VariableScalar(x) => Node::VariableScalar(Rc::clone(&x)),
-            Node::FunctionAdd(x) => Node::FunctionAdd(Rc::clone(&x)),
-            Node::FunctionCos(x) => Node::FunctionCos(Rc::clone(&x)),
-            Node::FunctionDiv(x) => Node::FunctionDiv(Rc::clone(&x)),
-            Node::FunctionMul(x) => Node::FunctionMul(Rc::clone(&x)),
-            Node::FunctionSin(x) => Node::FunctionSin(Rc::clone(&x)), 
+            Node::ConstantScalar(x) => Node::ConstantScalar(NodeRc::clone(&x)),
+            Node::VariableScalar(x) => Node::VariableScalar(NodeRc::clone(&x)),
+            Node::FunctionAdd(x) => Node::FunctionAdd(NodeRc::clone(&x)),
+            Node::FunctionCos(x) => Node::FunctionCos(NodeRc::clone(&x)),
+            Node::FunctionDiv(x) => Node::FunctionDiv(NodeRc::clone(&x)),
+            Node::FunctionMul(x) => Node::FunctionMul(NodeRc::clone(&x)),
+            Node::FunctionSin(x) => Node::FunctionSin(NodeRc::clone(&x)), 
         }
     }
 }
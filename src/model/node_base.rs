@@ -17,6 +17,13 @@ pub trait NodeBase {
 
     /// Evaluates expression for given variable values.
     fn evaluate(&self, _var_values: &HashMap<&Node, f64>) -> f64 { NAN }
+
+    /// Evaluates enclosure (lower, upper) of expression values for given
+    /// variable bounds (lower, upper). Variables without bounds are taken
+    /// to be unbounded.
+    fn evaluate_interval(&self, _var_bounds: &HashMap<&Node, (f64, f64)>) -> (f64, f64) {
+        (-f64::INFINITY, f64::INFINITY)
+    }
 }
 
 impl NodeBase for Node {
@@ -61,5 +68,22 @@ impl NodeBase for Node {
             Node::FunctionSin(x) => x.evaluate(var_values),            
         }
     }
+
+    fn evaluate_interval(&self, var_bounds: &HashMap<&Node, (f64, f64)>) -> (f64, f64) {
+        match self {
+            Node::ConstantScalar(x) => (x.value(), x.value()),
+            Node::VariableScalar(_) => {
+                match var_bounds.get(self) {
+                    Some(x) => *x,
+                    None => (-f64::INFINITY, f64::INFINITY),
+                }
+            },
+            Node::FunctionAdd(x) => x.evaluate_interval(var_bounds),
+            Node::FunctionCos(x) => x.evaluate_interval(var_bounds),
+            Node::FunctionDiv(x) => x.evaluate_interval(var_bounds),
+            Node::FunctionMul(x) => x.evaluate_interval(var_bounds),
+            Node::FunctionSin(x) => x.evaluate_interval(var_bounds),
+        }
+    }
 }
 
@@ -0,0 +1,367 @@
+//! Curvature analysis of expression nodes.
+//!
+//! The analysis follows the rules of disciplined convex programming. The curvature
+//! of a function of an expression is determined from the curvature of the function
+//! and its monotonicity over the range of the expression, e.g., "1/(x + y)" is convex
+//! if "x + y" is positive, and "sin(x)" is concave if "x" is between 0 and pi.
+//! Ranges are derived from given variable bounds, so sign information is used
+//! whenever it is available.
+
+use std::fmt;
+use std::f64::consts::PI;
+use std::collections::HashMap;
+
+use crate::model::node::Node;
+use crate::model::node_base::NodeBase;
+use crate::model::node_simplify::NodeSimplifier;
+use crate::model::node_interval::{interval_add, interval_mul, interval_sqr,
+                                  interval_div, interval_sin, interval_cos};
+
+/// Curvature of expression node.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NodeCurvature {
+    Constant,
+    Affine,
+    Convex,
+    Concave,
+    Unknown,
+}
+
+/// Monotonicity of scalar function over a range of its argument.
+#[derive(Clone, Copy, PartialEq)]
+enum NodeMonotonicity {
+    Nondecreasing,
+    Nonincreasing,
+    Unknown,
+}
+
+/// Trait for curvature analysis of expression nodes.
+pub trait NodeCurv {
+
+    /// Gets curvature of expression node given variable bounds (lower, upper).
+    /// Variables without bounds are taken to be unbounded.
+    fn curvature(&self, var_bounds: &HashMap<&Node, (f64, f64)>) -> NodeCurvature;
+}
+
+/// Curvature analyzer of expression nodes.
+struct NodeCurvAnalyzer<'a> {
+    var_bounds: &'a HashMap<&'a Node, (f64, f64)>,
+    cache: HashMap<Node, (NodeCurvature, (f64, f64))>,
+}
+
+impl NodeCurvature {
+
+    /// Determines whether curvature is constant or affine.
+    pub fn is_affine(&self) -> bool {
+        matches!(self, NodeCurvature::Constant | NodeCurvature::Affine)
+    }
+
+    /// Determines whether curvature is convex, which includes affine.
+    pub fn is_convex(&self) -> bool {
+        self.is_affine() || *self == NodeCurvature::Convex
+    }
+
+    /// Determines whether curvature is concave, which includes affine.
+    pub fn is_concave(&self) -> bool {
+        self.is_affine() || *self == NodeCurvature::Concave
+    }
+
+    /// Gets curvature of the negation of an expression with this curvature.
+    pub fn negate(&self) -> NodeCurvature {
+        match self {
+            NodeCurvature::Convex => NodeCurvature::Concave,
+            NodeCurvature::Concave => NodeCurvature::Convex,
+            c => *c,
+        }
+    }
+
+    /// Gets curvature of the sum of expressions with these curvatures.
+    fn add(&self, other: NodeCurvature) -> NodeCurvature {
+        match (*self, other) {
+            (NodeCurvature::Constant, c) | (c, NodeCurvature::Constant) => c,
+            (NodeCurvature::Affine, c) | (c, NodeCurvature::Affine) => c,
+            (c1, c2) if c1 == c2 => c1,
+            _ => NodeCurvature::Unknown,
+        }
+    }
+
+    /// Gets curvature of the product of a constant and an expression with this curvature.
+    fn scale(&self, value: f64) -> NodeCurvature {
+        if value == 0. {
+            NodeCurvature::Constant
+        }
+        else if value > 0. {
+            *self
+        }
+        else if value < 0. {
+            self.negate()
+        }
+        else {
+            NodeCurvature::Unknown
+        }
+    }
+
+    /// Gets curvature of the composition of a convex or concave function having
+    /// the given monotonicity with an expression with this curvature.
+    fn compose(&self, func: NodeCurvature, mono: NodeMonotonicity) -> NodeCurvature {
+        let ok = match (func, mono) {
+            _ if self.is_affine() => true,
+            (NodeCurvature::Convex, NodeMonotonicity::Nondecreasing) => self.is_convex(),
+            (NodeCurvature::Convex, NodeMonotonicity::Nonincreasing) => self.is_concave(),
+            (NodeCurvature::Concave, NodeMonotonicity::Nondecreasing) => self.is_concave(),
+            (NodeCurvature::Concave, NodeMonotonicity::Nonincreasing) => self.is_convex(),
+            _ => false,
+        };
+        match func {
+            NodeCurvature::Convex | NodeCurvature::Concave if ok => func,
+            _ => NodeCurvature::Unknown,
+        }
+    }
+}
+
+impl<'a> NodeCurvAnalyzer<'a> {
+
+    /// Creates new analyzer.
+    fn new(var_bounds: &'a HashMap<&'a Node, (f64, f64)>) -> Self {
+        Self {
+            var_bounds: var_bounds,
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Gets curvature and range of simplified expression node.
+    fn analyze(&mut self, node: &Node) -> (NodeCurvature, (f64, f64)) {
+
+        if let Some(x) = self.cache.get(node) {
+            return *x;
+        }
+
+        let args: Vec<(NodeCurvature, (f64, f64))> = node.arguments()
+                                                         .iter()
+                                                         .map(|a| self.analyze(a))
+                                                         .collect();
+        let args_constant = args.iter().all(|(c, _)| *c == NodeCurvature::Constant);
+
+        let x = match node {
+            Node::ConstantScalar(x) => (NodeCurvature::Constant, (x.value(), x.value())),
+            Node::VariableScalar(_x) => {
+                let r = match self.var_bounds.get(node) {
+                    Some(b) => *b,
+                    None => (-f64::INFINITY, f64::INFINITY),
+                };
+                (NodeCurvature::Affine, r)
+            },
+            _ if args_constant => {
+                let v = node.evaluate(&HashMap::new());
+                (NodeCurvature::Constant, (v, v))
+            },
+            Node::FunctionAdd(_x) => {
+                let c = args.iter().fold(NodeCurvature::Constant, |c, (a, _)| c.add(*a));
+                let r = args.iter().fold((0., 0.), |r, (_, a)| interval_add(r, *a));
+                (c, r)
+            },
+            Node::FunctionMul(_x) => {
+                let (c0, r0) = args[0];
+                let (c1, r1) = args[1];
+                let a = node.arguments();
+                if a[0] == a[1] {
+                    // Square, which is nondecreasing for nonnegative arguments
+                    // and nonincreasing for nonpositive arguments
+                    (c0.compose(NodeCurvature::Convex, monotonicity(r0, 0.)), interval_sqr(r0))
+                }
+                else {
+                    let c = if c0 == NodeCurvature::Constant {
+                        c1.scale(r0.0)
+                    }
+                    else if c1 == NodeCurvature::Constant {
+                        c0.scale(r1.0)
+                    }
+                    else {
+                        NodeCurvature::Unknown
+                    };
+                    (c, interval_mul(r0, r1))
+                }
+            },
+            Node::FunctionDiv(_x) => {
+                let (c0, r0) = args[0];
+                let (c1, r1) = args[1];
+                let r = interval_div(r0, r1);
+                let c = if c1 == NodeCurvature::Constant {
+                    c0.scale(1./r1.0)
+                }
+                else if c0 == NodeCurvature::Constant {
+                    // Reciprocal, which is nonincreasing and convex for positive
+                    // arguments and concave for negative arguments
+                    let f = if r1.0 > 0. {
+                        NodeCurvature::Convex
+                    }
+                    else if r1.1 < 0. {
+                        NodeCurvature::Concave
+                    }
+                    else {
+                        NodeCurvature::Unknown
+                    };
+                    c1.compose(f, NodeMonotonicity::Nonincreasing).scale(r0.0)
+                }
+                else {
+                    NodeCurvature::Unknown
+                };
+                (c, r)
+            },
+            Node::FunctionCos(_x) => {
+                let (c0, r0) = args[0];
+                let (f, mono) = sin_shape((r0.0 + PI/2., r0.1 + PI/2.));
+                (c0.compose(f, mono), interval_cos(r0))
+            },
+            Node::FunctionSin(_x) => {
+                let (c0, r0) = args[0];
+                let (f, mono) = sin_shape(r0);
+                (c0.compose(f, mono), interval_sin(r0))
+            },
+        };
+
+        self.cache.insert(node.clone(), x);
+        x
+    }
+}
+
+/// Gets monotonicity of function with minimum at the given point over a range.
+fn monotonicity(r: (f64, f64), point: f64) -> NodeMonotonicity {
+    if r.0 >= point {
+        NodeMonotonicity::Nondecreasing
+    }
+    else if r.1 <= point {
+        NodeMonotonicity::Nonincreasing
+    }
+    else {
+        NodeMonotonicity::Unknown
+    }
+}
+
+/// Gets curvature and monotonicity of sine over a range.
+fn sin_shape(r: (f64, f64)) -> (NodeCurvature, NodeMonotonicity) {
+
+    if !r.0.is_finite() || !r.1.is_finite() {
+        return (NodeCurvature::Unknown, NodeMonotonicity::Unknown);
+    }
+
+    // Concave over [2k*pi, (2k+1)*pi] and convex over [(2k+1)*pi, (2k+2)*pi]
+    let k = (r.0/PI).floor();
+    let curvature = if r.1 > (k+1.)*PI {
+        NodeCurvature::Unknown
+    }
+    else if k.rem_euclid(2.) == 0. {
+        NodeCurvature::Concave
+    }
+    else {
+        NodeCurvature::Convex
+    };
+
+    // Nondecreasing over [(2k-1/2)*pi, (2k+1/2)*pi] and nonincreasing otherwise
+    let k = ((r.0 + PI/2.)/PI).floor();
+    let mono = if r.1 + PI/2. > (k+1.)*PI {
+        NodeMonotonicity::Unknown
+    }
+    else if k.rem_euclid(2.) == 0. {
+        NodeMonotonicity::Nondecreasing
+    }
+    else {
+        NodeMonotonicity::Nonincreasing
+    };
+
+    (curvature, mono)
+}
+
+impl NodeCurv for Node {
+
+    fn curvature(&self, var_bounds: &HashMap<&Node, (f64, f64)>) -> NodeCurvature {
+        let node = NodeSimplifier::new().simplify(self);
+        NodeCurvAnalyzer::new(var_bounds).analyze(&node).0
+    }
+}
+
+impl fmt::Display for NodeCurvature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NodeCurvature::Constant => write!(f, "constant"),
+            NodeCurvature::Affine => write!(f, "affine"),
+            NodeCurvature::Convex => write!(f, "convex"),
+            NodeCurvature::Concave => write!(f, "concave"),
+            NodeCurvature::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use maplit::hashmap;
+
+    use super::*;
+    use crate::model::node_func::NodeFunc;
+    use crate::model::variable::VariableScalar;
+    use crate::model::constant::ConstantScalar;
+
+    #[test]
+    fn node_curvature() {
+
+        let x = VariableScalar::new_continuous("x");
+        let y = VariableScalar::new_continuous("y");
+        let none = HashMap::new();
+
+        // Constant and affine
+        assert_eq!(ConstantScalar::new(3.).curvature(&none), NodeCurvature::Constant);
+        assert_eq!((ConstantScalar::new(2.).cos()*4.).curvature(&none), NodeCurvature::Constant);
+        assert_eq!((3.*&x - &y/2. + 1.).curvature(&none), NodeCurvature::Affine);
+        assert_eq!((0.*(&x*&y)).curvature(&none), NodeCurvature::Constant);
+
+        // Squares and products
+        assert_eq!((&x*&x).curvature(&none), NodeCurvature::Convex);
+        assert_eq!((-2.*&x*&x + &y).curvature(&none), NodeCurvature::Concave);
+        assert_eq!(((&x + &y)*(&x + &y)).curvature(&none), NodeCurvature::Convex);
+        assert_eq!((&x*&y).curvature(&none), NodeCurvature::Unknown);
+        assert_eq!((&x*&x - &y*&y).curvature(&none), NodeCurvature::Unknown);
+
+        // Square of convex expression requires sign
+        let z = (&x*&x + 1.)*(&x*&x + 1.);
+        assert_eq!(z.curvature(&none), NodeCurvature::Convex);
+        let z = (&x*&x - 1.)*(&x*&x - 1.);
+        assert_eq!(z.curvature(&none), NodeCurvature::Unknown);
+
+        // Division
+        assert_eq!((&x/4.).curvature(&none), NodeCurvature::Affine);
+        assert_eq!((1./&x).curvature(&none), NodeCurvature::Unknown);
+        let b = hashmap!{ &x => (1., 5.) };
+        assert_eq!((1./&x).curvature(&b), NodeCurvature::Convex);
+        assert_eq!((-3./&x).curvature(&b), NodeCurvature::Concave);
+        assert_eq!((2./(&x - &x*&x)).curvature(&b), NodeCurvature::Unknown);
+        let b = hashmap!{ &x => (-5., -1.) };
+        assert_eq!((1./&x).curvature(&b), NodeCurvature::Concave);
+        assert_eq!((1./(&x*&x - 30.)).curvature(&b), NodeCurvature::Concave);
+        assert_eq!((1./(&x - &x*&x)).curvature(&b), NodeCurvature::Unknown);
+        assert_eq!((&y/&x).curvature(&b), NodeCurvature::Unknown);
+        let b = hashmap!{ &x => (1., 3.) };
+        assert_eq!((1./(10. - &x*&x)).curvature(&b), NodeCurvature::Convex);
+
+        // Sine and cosine
+        assert_eq!(x.sin().curvature(&none), NodeCurvature::Unknown);
+        let b = hashmap!{ &x => (0.5, 2.5) };
+        assert_eq!(x.sin().curvature(&b), NodeCurvature::Concave);
+        assert_eq!((-x.sin()).curvature(&b), NodeCurvature::Convex);
+        assert_eq!((x.sin() + 7.).curvature(&b), NodeCurvature::Concave);
+        assert_eq!(x.cos().curvature(&b), NodeCurvature::Unknown);
+        let b = hashmap!{ &x => (-1., 1.) };
+        assert_eq!(x.cos().curvature(&b), NodeCurvature::Concave);
+        assert_eq!((&x + 11.).sin().curvature(&b), NodeCurvature::Convex);
+        assert_eq!(x.sin().curvature(&b), NodeCurvature::Unknown);
+
+        // Composition with monotone sine
+        let b = hashmap!{ &x => (0., 1.) };
+        assert_eq!((&x*&x).sin().curvature(&b), NodeCurvature::Unknown);
+        let b = hashmap!{ &x => (-0.5, 0.5), &y => (1., 1.5) };
+        assert_eq!((&y - &x*&x).sin().curvature(&b), NodeCurvature::Concave);
+
+        // Display
+        assert_eq!(format!("{}", NodeCurvature::Concave), "concave");
+    }
+}
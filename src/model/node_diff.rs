@@ -6,6 +6,7 @@ use std::collections::{HashMap, HashSet, VecDeque};
 use crate::model::node::Node;
 use crate::model::node_base::NodeBase;
 use crate::model::constant::ConstantScalar;
+use crate::model::node_simplify::NodeSimplifier;
 
 /// Trait for differentiating expression nodes.
 pub trait NodeDiff {
@@ -15,10 +16,12 @@ pub trait NodeDiff {
     fn all_simple_paths(&self, vars: &[&Node]) -> HashMap<Node, Vec<Vec<Node>>>;
     
     /// Obtains the derivative of the expression node with respect to a given variable node.
+    /// The derivative is simplified.
     fn derivative(&self, var: &Node) -> Node;
 
     /// Obtains the derivatives of the expression node with respect to a given array
-    /// of variable nodes.
+    /// of variable nodes. The derivatives are simplified and share their common
+    /// subexpressions.
     fn derivatives(&self, vars: &[&Node]) -> HashMap<Node, Node>;
 }
 
@@ -102,6 +105,7 @@ impl NodeDiff for Node {
 
         // Derivatives
         let paths = self.all_simple_paths(vars);
+        let mut simplifier = NodeSimplifier::new();
         let mut derivs: HashMap<Node, Node> = HashMap::new();
         for v in varset.iter() {
             let mut d = ConstantScalar::new(0.);
@@ -112,7 +116,7 @@ impl NodeDiff for Node {
                 }
                 d = d + prod;
             }
-            derivs.insert((**v).clone(), d);
+            derivs.insert((**v).clone(), simplifier.simplify(&d));
         }
         derivs
     }
@@ -0,0 +1,157 @@
+//! Interval arithmetic for expression nodes.
+//!
+//! Intervals are pairs (lower, upper) of possibly infinite values. The functions
+//! of this module give enclosures of the values of the corresponding operations
+//! over all points of the argument intervals. Products of zero and infinity are
+//! taken to be zero, since an infinite bound is never attained.
+
+use std::f64::consts::PI;
+
+/// Gets interval of the sum of values in the given intervals.
+pub fn interval_add(r0: (f64, f64), r1: (f64, f64)) -> (f64, f64) {
+    (r0.0 + r1.0, r0.1 + r1.1)
+}
+
+/// Gets interval of the product of values in the given intervals.
+pub fn interval_mul(r0: (f64, f64), r1: (f64, f64)) -> (f64, f64) {
+    let p = |a: f64, b: f64| if a == 0. || b == 0. { 0. } else { a*b };
+    let v = [p(r0.0, r1.0), p(r0.0, r1.1), p(r0.1, r1.0), p(r0.1, r1.1)];
+    (v.iter().cloned().fold(f64::INFINITY, f64::min),
+     v.iter().cloned().fold(-f64::INFINITY, f64::max))
+}
+
+/// Gets interval of the squares of values in the given interval.
+pub fn interval_sqr(r: (f64, f64)) -> (f64, f64) {
+    if r.0 >= 0. {
+        (r.0*r.0, r.1*r.1)
+    }
+    else if r.1 <= 0. {
+        (r.1*r.1, r.0*r.0)
+    }
+    else {
+        (0., (r.0*r.0).max(r.1*r.1))
+    }
+}
+
+/// Gets interval of the reciprocals of the nonzero values in the given interval.
+/// An interval with only zero gives an unbounded interval.
+pub fn interval_recip(r: (f64, f64)) -> (f64, f64) {
+    if r.0 > 0. || r.1 < 0. {
+        (1./r.1, 1./r.0)
+    }
+    else if r.0 == 0. && r.1 > 0. {
+        (1./r.1, f64::INFINITY)
+    }
+    else if r.1 == 0. && r.0 < 0. {
+        (-f64::INFINITY, 1./r.0)
+    }
+    else {
+        (-f64::INFINITY, f64::INFINITY)
+    }
+}
+
+/// Gets interval of the quotient of values in the given intervals, where
+/// the denominator interval may contain zero.
+pub fn interval_div(r0: (f64, f64), r1: (f64, f64)) -> (f64, f64) {
+    if r1.0 == 0. && r1.1 == 0. {
+        return (-f64::INFINITY, f64::INFINITY);
+    }
+    interval_mul(r0, interval_recip(r1))
+}
+
+/// Gets interval of the sines of values in the given interval.
+pub fn interval_sin(r: (f64, f64)) -> (f64, f64) {
+    interval_periodic(r, f64::sin, PI/2.)
+}
+
+/// Gets interval of the cosines of values in the given interval.
+pub fn interval_cos(r: (f64, f64)) -> (f64, f64) {
+    interval_periodic(r, f64::cos, 0.)
+}
+
+/// Gets interval of the values of a sinusoid, with maxima at the given phase
+/// plus multiples of 2*pi, over the given interval.
+fn interval_periodic(r: (f64, f64), f: fn(f64) -> f64, phase: f64) -> (f64, f64) {
+
+    if !r.0.is_finite() || !r.1.is_finite() || r.1 - r.0 >= 2.*PI {
+        return (-1., 1.);
+    }
+
+    // Extrema within rounding tolerance of the interval are included
+    let tol = 1e-12*(1. + r.0.abs().max(r.1.abs()));
+    let contains = |p: f64| {
+        let k = ((r.0 - tol - p)/(2.*PI)).ceil();
+        p + 2.*PI*k <= r.1 + tol
+    };
+    let (a, b) = (f(r.0), f(r.1));
+    let lower = if contains(phase + PI) { -1. } else { a.min(b) };
+    let upper = if contains(phase) { 1. } else { a.max(b) };
+    (lower, upper)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use approx::assert_abs_diff_eq;
+
+    use super::*;
+
+    #[test]
+    fn interval_arithmetic() {
+
+        assert_eq!(interval_add((1., 2.), (-f64::INFINITY, 3.)), (-f64::INFINITY, 5.));
+
+        assert_eq!(interval_mul((-1., 2.), (3., 4.)), (-4., 8.));
+        assert_eq!(interval_mul((-2., -1.), (-3., 4.)), (-8., 6.));
+        assert_eq!(interval_mul((0., 0.), (-f64::INFINITY, f64::INFINITY)), (0., 0.));
+        assert_eq!(interval_mul((0., 1.), (2., f64::INFINITY)), (0., f64::INFINITY));
+
+        assert_eq!(interval_sqr((-3., 2.)), (0., 9.));
+        assert_eq!(interval_sqr((-3., -2.)), (4., 9.));
+        assert_eq!(interval_sqr((2., f64::INFINITY)), (4., f64::INFINITY));
+
+        assert_eq!(interval_recip((2., 4.)), (0.25, 0.5));
+        assert_eq!(interval_recip((0., 4.)), (0.25, f64::INFINITY));
+        assert_eq!(interval_recip((-4., 0.)), (-f64::INFINITY, -0.25));
+        assert_eq!(interval_recip((-4., 1.)), (-f64::INFINITY, f64::INFINITY));
+
+        assert_eq!(interval_div((1., 2.), (2., 4.)), (0.25, 1.));
+        assert_eq!(interval_div((1., 2.), (0., 4.)), (0.25, f64::INFINITY));
+        assert_eq!(interval_div((-2., -1.), (0., 4.)), (-f64::INFINITY, -0.25));
+        assert_eq!(interval_div((-2., 1.), (0., 4.)), (-f64::INFINITY, f64::INFINITY));
+        assert_eq!(interval_div((0., 0.), (-1., 1.)), (0., 0.));
+        assert_eq!(interval_div((1., 2.), (0., 0.)), (-f64::INFINITY, f64::INFINITY));
+    }
+
+    #[test]
+    fn interval_sinusoids() {
+
+        // Wide and unbounded
+        assert_eq!(interval_sin((0., 7.)), (-1., 1.));
+        assert_eq!(interval_cos((-f64::INFINITY, 0.)), (-1., 1.));
+        assert_eq!(interval_sin((1e10, 1e10 + 7.)), (-1., 1.));
+
+        // Monotone
+        let r = interval_sin((0.1, 1.));
+        assert_eq!(r, (0.1_f64.sin(), 1_f64.sin()));
+        let r = interval_cos((0.1, 1.));
+        assert_eq!(r, (1_f64.cos(), 0.1_f64.cos()));
+
+        // Extrema
+        let r = interval_sin((1., 2.));
+        assert_eq!(r, (1_f64.sin(), 1.));
+        let r = interval_cos((-1., 2.));
+        assert_eq!(r, (2_f64.cos(), 1.));
+        let r = interval_sin((4., 5.));
+        assert_eq!(r, (-1., 4_f64.sin()));
+        let r = interval_cos((3., 3.5 + 4.*PI));
+        assert_eq!(r, (-1., 1.));
+        let r = interval_sin((-8., -7.));
+        assert_eq!(r, (-1., (-7_f64).sin()));
+
+        // Points
+        let r = interval_sin((2., 2.));
+        assert_abs_diff_eq!(r.0, 2_f64.sin());
+        assert_abs_diff_eq!(r.1, 2_f64.sin());
+    }
+}
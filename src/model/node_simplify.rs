@@ -0,0 +1,381 @@
+//! Structural simplification of expression nodes.
+
+use std::collections::HashMap;
+
+use crate::model::node::Node;
+use crate::model::node_base::NodeBase;
+use crate::model::constant::ConstantScalar;
+use crate::model::function::add::FunctionAdd;
+use crate::model::function::mul::FunctionMul;
+use crate::model::function::div::FunctionDiv;
+use crate::model::function::cos::FunctionCos;
+use crate::model::function::sin::FunctionSin;
+
+/// Trait for simplifying expression nodes.
+pub trait NodeSimplify {
+
+    /// Obtains simplified expression node.
+    /// See [NodeSimplifier](struct.NodeSimplifier.html).
+    fn simplify(&self) -> Node;
+}
+
+/// Structural key of simplified expression node, in terms
+/// of the identifiers of its arguments.
+#[derive(PartialEq, Eq, Hash)]
+enum NodeKey {
+    Constant(u64),
+    Add(Vec<usize>),
+    Mul(usize, usize),
+    Div(usize, usize),
+    Cos(usize),
+    Sin(usize),
+}
+
+/// Simplifier of expression nodes.
+///
+/// It flattens sums and products, folds constants, combines like terms, and
+/// moves constant coefficients to the front of products, e.g., "7*-1*sin(x)"
+/// becomes "-7*sin(x)" and "x*y + 2*y*x" becomes "3*x*y". Simplified subtrees
+/// are hash-consed, so structurally identical subtrees of all the expressions
+/// simplified by the same simplifier are the same node, and hence compare equal.
+pub struct NodeSimplifier {
+    ids: HashMap<Node, usize>,
+    table: HashMap<NodeKey, Node>,
+    cache: HashMap<Node, Node>,
+}
+
+impl NodeSimplifier {
+
+    /// Creates new simplifier.
+    pub fn new() -> Self {
+        Self {
+            ids: HashMap::new(),
+            table: HashMap::new(),
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Obtains simplified expression node.
+    pub fn simplify(&mut self, node: &Node) -> Node {
+
+        if let Some(n) = self.cache.get(node) {
+            return n.clone();
+        }
+
+        let args = node.arguments();
+        let n = match node {
+            Node::ConstantScalar(x) => self.constant(x.value()),
+            Node::VariableScalar(_x) => {
+                let id = self.ids.len();
+                self.ids.entry(node.clone()).or_insert(id);
+                node.clone()
+            },
+            Node::FunctionAdd(_x) => {
+                let terms: Vec<Node> = args.iter().map(|a| self.simplify(a)).collect();
+                self.sum(terms)
+            },
+            Node::FunctionMul(_x) => {
+                let a0 = self.simplify(args[0]);
+                let a1 = self.simplify(args[1]);
+                let (c0, mut f0) = self.factors(&a0);
+                let (c1, f1) = self.factors(&a1);
+                f0.extend(f1);
+                self.product(c0*c1, f0)
+            },
+            Node::FunctionDiv(_x) => {
+                let a0 = self.simplify(args[0]);
+                let a1 = self.simplify(args[1]);
+                self.quotient(&a0, &a1)
+            },
+            Node::FunctionCos(_x) => {
+                let a = self.simplify(args[0]);
+                match &a {
+                    Node::ConstantScalar(x) => self.constant(x.value().cos()),
+                    _ => self.intern(NodeKey::Cos(self.ids[&a]), || FunctionCos::new(a.clone())),
+                }
+            },
+            Node::FunctionSin(_x) => {
+                let a = self.simplify(args[0]);
+                match &a {
+                    Node::ConstantScalar(x) => self.constant(x.value().sin()),
+                    _ => self.intern(NodeKey::Sin(self.ids[&a]), || FunctionSin::new(a.clone())),
+                }
+            },
+        };
+
+        self.cache.insert(node.clone(), n.clone());
+        n
+    }
+
+    /// Gets node with the given key, creating it if needed.
+    fn intern<F: FnOnce() -> Node>(&mut self, key: NodeKey, make: F) -> Node {
+        if let Some(n) = self.table.get(&key) {
+            return n.clone();
+        }
+        let n = make();
+        let id = self.ids.len();
+        self.ids.insert(n.clone(), id);
+        self.table.insert(key, n.clone());
+        n
+    }
+
+    /// Gets constant node.
+    fn constant(&mut self, value: f64) -> Node {
+        let value = if value == 0. { 0. } else { value };
+        self.intern(NodeKey::Constant(value.to_bits()), || ConstantScalar::new(value))
+    }
+
+    /// Gets node representing the reciprocal of a simplified node.
+    fn reciprocal(&mut self, node: &Node) -> Node {
+        let one = self.constant(1.);
+        let key = NodeKey::Div(self.ids[&one], self.ids[node]);
+        self.intern(key, || FunctionDiv::new(one.clone(), node.clone()))
+    }
+
+    /// Splits simplified node into constant coefficient and non-constant factors,
+    /// where divisions are represented by reciprocals.
+    fn factors(&mut self, node: &Node) -> (f64, Vec<Node>) {
+        let args = node.arguments();
+        match node {
+            Node::ConstantScalar(x) => (x.value(), Vec::new()),
+            Node::FunctionMul(_x) => {
+                let (c0, mut f0) = self.factors(args[0]);
+                let (c1, f1) = self.factors(args[1]);
+                f0.extend(f1);
+                (c0*c1, f0)
+            },
+            Node::FunctionDiv(_x) => {
+                let (c, mut f) = self.factors(args[0]);
+                f.push(self.reciprocal(args[1]));
+                (c, f)
+            },
+            _ => (1., vec![node.clone()]),
+        }
+    }
+
+    /// Builds simplified product of constant coefficient and non-constant factors.
+    fn product(&mut self, coef: f64, factors: Vec<Node>) -> Node {
+
+        // Constant
+        if factors.is_empty() || coef == 0. {
+            return self.constant(if factors.is_empty() { coef } else { 0. });
+        }
+
+        // Constant times sum
+        if factors.len() == 1 && coef != 1. {
+            if let Node::FunctionAdd(_x) = &factors[0] {
+                let terms: Vec<Node> = factors[0].arguments()
+                                                 .iter()
+                                                 .map(|t| (*t).clone())
+                                                 .collect();
+                let terms: Vec<Node> = terms.iter().map(|t| {
+                    let (c, f) = self.factors(t);
+                    self.product(coef*c, f)
+                }).collect();
+                return self.sum(terms);
+            }
+        }
+
+        // Numerator and denominator factors
+        let mut num: Vec<Node> = Vec::new();
+        let mut den: Vec<Node> = Vec::new();
+        for f in factors.into_iter() {
+            match &f {
+                Node::FunctionDiv(_x) if f.arguments()[0].is_constant_with_value(1.) => {
+                    let (_c, d) = self.factors(f.arguments()[1]);
+                    den.extend(d);
+                },
+                _ => num.push(f),
+            }
+        }
+
+        // Product
+        let n = self.chain(coef, num);
+        if den.is_empty() {
+            return n;
+        }
+        let d = self.chain(1., den);
+        let key = NodeKey::Div(self.ids[&n], self.ids[&d]);
+        self.intern(key, || FunctionDiv::new(n.clone(), d.clone()))
+    }
+
+    /// Builds product of constant coefficient and ordered factors, with
+    /// variables first.
+    fn chain(&mut self, coef: f64, mut factors: Vec<Node>) -> Node {
+        if factors.is_empty() {
+            return self.constant(coef);
+        }
+        factors.sort_by_cached_key(|f| {
+            match f {
+                Node::VariableScalar(_x) => (0, f.name().to_string(), self.ids[f]),
+                _ => (1, String::new(), self.ids[f]),
+            }
+        });
+        let mut p = factors[0].clone();
+        for f in factors.iter().skip(1) {
+            let key = NodeKey::Mul(self.ids[&p], self.ids[f]);
+            p = self.intern(key, || FunctionMul::new(p.clone(), f.clone()));
+        }
+        if coef != 1. {
+            let c = self.constant(coef);
+            let key = NodeKey::Mul(self.ids[&c], self.ids[&p]);
+            p = self.intern(key, || FunctionMul::new(c.clone(), p.clone()));
+        }
+        p
+    }
+
+    /// Builds simplified sum of simplified terms.
+    fn sum(&mut self, terms: Vec<Node>) -> Node {
+
+        // Flatten and combine like terms
+        let mut constant = 0.;
+        let mut monomials: Vec<(Node, f64, Vec<Node>)> = Vec::new();
+        let mut positions: HashMap<usize, usize> = HashMap::new();
+        let mut flat: Vec<Node> = Vec::new();
+        for t in terms.iter() {
+            match t {
+                Node::FunctionAdd(_x) => flat.extend(t.arguments().iter().map(|a| (*a).clone())),
+                _ => flat.push(t.clone()),
+            }
+        }
+        for t in flat.iter() {
+            let (c, f) = self.factors(t);
+            if f.is_empty() {
+                constant += c;
+                continue;
+            }
+            let m = self.product(1., f.clone());
+            let id = self.ids[&m];
+            match positions.get(&id) {
+                Some(k) => monomials[*k].1 += c,
+                None => {
+                    positions.insert(id, monomials.len());
+                    monomials.push((m, c, f));
+                },
+            }
+        }
+
+        // Terms
+        monomials.sort_by_key(|(m, _, _)| self.ids[m]);
+        let mut args: Vec<Node> = Vec::new();
+        for (_m, c, f) in monomials.into_iter() {
+            if c != 0. {
+                args.push(self.product(c, f));
+            }
+        }
+        if constant != 0. {
+            args.push(self.constant(constant));
+        }
+        match args.len() {
+            0 => self.constant(0.),
+            1 => args[0].clone(),
+            _ => {
+                let key = NodeKey::Add(args.iter().map(|a| self.ids[a]).collect());
+                self.intern(key, || FunctionAdd::new(args))
+            },
+        }
+    }
+
+    /// Builds simplified quotient of simplified nodes.
+    fn quotient(&mut self, num: &Node, den: &Node) -> Node {
+        let (c0, mut f0) = self.factors(num);
+        let (c1, f1) = self.factors(den);
+        if c0 == 0. {
+            return self.constant(0.);
+        }
+        if !f1.is_empty() {
+            let d = self.product(1., f1);
+            f0.push(self.reciprocal(&d));
+        }
+        self.product(c0/c1, f0)
+    }
+}
+
+impl NodeSimplify for Node {
+
+    fn simplify(&self) -> Node {
+        NodeSimplifier::new().simplify(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use maplit::hashmap;
+    use approx::assert_abs_diff_eq;
+
+    use super::*;
+    use crate::model::node_diff::NodeDiff;
+    use crate::model::node_func::NodeFunc;
+    use crate::model::variable::VariableScalar;
+
+    #[test]
+    fn node_simplify() {
+
+        let x = VariableScalar::new_continuous("x");
+        let y = VariableScalar::new_continuous("y");
+
+        // Constants and coefficients
+        let z1 = FunctionMul::new(ConstantScalar::new(7.),
+                                  FunctionMul::new(ConstantScalar::new(-1.), x.sin()));
+        assert_eq!(format!("{}", z1), "7*-1*sin(x)");
+        assert_eq!(format!("{}", z1.simplify()), "-7*sin(x)");
+        let z2 = FunctionAdd::new(vec![ConstantScalar::new(2.), ConstantScalar::new(3.)]);
+        assert!(z2.simplify().is_constant_with_value(5.));
+        assert!(FunctionCos::new(ConstantScalar::new(0.)).simplify().is_constant_with_value(1.));
+
+        // Like terms
+        let z3 = &x*&y + 2.*&y*&x;
+        assert_eq!(format!("{}", z3), "x*y + 2*y*x");
+        assert_eq!(format!("{}", z3.simplify()), "3*x*y");
+        let z4 = &x + 3.*&y - &x + 1.;
+        assert_eq!(format!("{}", z4.simplify()), "3*y + 1");
+        assert!((&x - &x).simplify().is_constant_with_value(0.));
+        assert_eq!(format!("{}", (4.*(&x + 2.*&y)/2.).simplify()), "2*x + 4*y");
+
+        // Quotients
+        let z5 = (3.*&x)/(2.*&y) + &x/&y;
+        assert_eq!(format!("{}", z5.simplify()), "2.5*x/y");
+
+        // Evaluation
+        let var_values = hashmap!{ &x => 2., &y => 3. };
+        for z in [&z1, &z3, &z4, &z5].iter() {
+            assert_abs_diff_eq!(z.evaluate(&var_values),
+                                z.simplify().evaluate(&var_values),
+                                epsilon = 1e-12);
+        }
+    }
+
+    #[test]
+    fn node_simplify_hash_consing() {
+
+        let x = VariableScalar::new_continuous("x");
+        let y = VariableScalar::new_continuous("y");
+
+        let z1 = (&x*&y).cos();
+        let z2 = (&y*&x).cos();
+        assert_ne!(z1, z2);
+
+        let mut s = NodeSimplifier::new();
+        let s1 = s.simplify(&z1);
+        let s2 = s.simplify(&z2);
+        assert_eq!(s1, s2);
+        assert_eq!(s.simplify(&(&z1 + &z2)), s.simplify(&(2.*&s1)));
+
+        // Variables are kept
+        assert_eq!(s.simplify(&x), x);
+        assert_ne!(s.simplify(&x), s.simplify(&VariableScalar::new_continuous("x")));
+    }
+
+    #[test]
+    fn node_simplify_derivatives() {
+
+        let x = VariableScalar::new_continuous("x");
+        let y = VariableScalar::new_continuous("y");
+
+        let f = 7.*x.cos() + &x*&x*&y;
+        assert_eq!(format!("{}", f.derivative(&x)), "2*x*y + -7*sin(x)");
+        assert_eq!(format!("{}", f.derivative(&y)), "x*x");
+        assert_eq!(format!("{}", f.derivative(&x).derivative(&x)), "-7*cos(x) + 2*y");
+    }
+}
@@ -192,10 +192,10 @@ mod tests {
         assert_eq!(c2.gphi.len(), 2);
         for (v, e) in c2.gphi.iter() {
             if v == &x {
-                assert_eq!(format!("{}", e), "10*y + 7*-1*sin(x)");
+                assert_eq!(format!("{}", e), "10*y + -7*sin(x)");
             }
             else if v == &y {
-                assert_eq!(format!("{}", e), "x*10");
+                assert_eq!(format!("{}", e), "10*x");
             }
             else {
                 panic!("invalid variable");
@@ -0,0 +1,155 @@
+//! Evaluation tape of expression nodes with common subexpression elimination.
+
+use std::collections::HashMap;
+
+use crate::model::node::Node;
+use crate::model::node_base::NodeBase;
+use crate::model::node_simplify::NodeSimplifier;
+
+/// Operation of evaluation tape, in terms of the tape positions of its arguments.
+enum NodeTapeOp {
+    Constant(f64),
+    Variable(usize),
+    Add(Vec<usize>),
+    Mul(usize, usize),
+    Div(usize, usize),
+    Cos(usize),
+    Sin(usize),
+}
+
+/// Evaluation tape of a set of expression nodes.
+///
+/// The expressions are simplified together, so subexpressions that are
+/// structurally identical, within an expression or across expressions,
+/// are identified and evaluated only once per evaluation.
+pub struct NodeTape {
+    ops: Vec<NodeTapeOp>,
+    roots: Vec<usize>,
+}
+
+impl NodeTape {
+
+    /// Creates evaluation tape of given expressions. Variables are read from
+    /// the positions given by the map between variables and indices.
+    pub fn new(exprs: &[Node], var2index: &HashMap<Node, usize>) -> Self {
+        let mut tape = Self {
+            ops: Vec::new(),
+            roots: Vec::with_capacity(exprs.len()),
+        };
+        let mut simplifier = NodeSimplifier::new();
+        let mut positions: HashMap<Node, usize> = HashMap::new();
+        for e in exprs.iter() {
+            let s = simplifier.simplify(e);
+            let pos = tape.add(&s, var2index, &mut positions);
+            tape.roots.push(pos);
+        }
+        tape
+    }
+
+    /// Gets number of operations of tape.
+    pub fn len(&self) -> usize { self.ops.len() }
+
+    /// Determines whether tape has no operations.
+    pub fn is_empty(&self) -> bool { self.ops.is_empty() }
+
+    /// Evaluates expressions for given variable values.
+    pub fn evaluate(&self, x: &[f64]) -> Vec<f64> {
+        let mut values: Vec<f64> = Vec::with_capacity(self.ops.len());
+        for op in self.ops.iter() {
+            let v = match op {
+                NodeTapeOp::Constant(c) => *c,
+                NodeTapeOp::Variable(i) => x[*i],
+                NodeTapeOp::Add(args) => args.iter().map(|a| values[*a]).sum(),
+                NodeTapeOp::Mul(a, b) => values[*a]*values[*b],
+                NodeTapeOp::Div(a, b) => values[*a]/values[*b],
+                NodeTapeOp::Cos(a) => values[*a].cos(),
+                NodeTapeOp::Sin(a) => values[*a].sin(),
+            };
+            values.push(v);
+        }
+        self.roots.iter().map(|r| values[*r]).collect()
+    }
+
+    /// Adds operations of expression to tape and gets position of its value.
+    fn add(&mut self,
+           node: &Node,
+           var2index: &HashMap<Node, usize>,
+           positions: &mut HashMap<Node, usize>) -> usize {
+
+        if let Some(pos) = positions.get(node) {
+            return *pos;
+        }
+
+        let args: Vec<usize> = node.arguments()
+                                   .iter()
+                                   .map(|a| self.add(a, var2index, positions))
+                                   .collect();
+        let op = match node {
+            Node::ConstantScalar(x) => NodeTapeOp::Constant(x.value()),
+            Node::VariableScalar(_x) => {
+                match var2index.get(node) {
+                    Some(i) => NodeTapeOp::Variable(*i),
+                    None => NodeTapeOp::Constant(f64::NAN),
+                }
+            },
+            Node::FunctionAdd(_x) => NodeTapeOp::Add(args),
+            Node::FunctionMul(_x) => NodeTapeOp::Mul(args[0], args[1]),
+            Node::FunctionDiv(_x) => NodeTapeOp::Div(args[0], args[1]),
+            Node::FunctionCos(_x) => NodeTapeOp::Cos(args[0]),
+            Node::FunctionSin(_x) => NodeTapeOp::Sin(args[0]),
+        };
+
+        let pos = self.ops.len();
+        self.ops.push(op);
+        positions.insert(node.clone(), pos);
+        pos
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use maplit::hashmap;
+    use approx::assert_abs_diff_eq;
+
+    use super::*;
+    use crate::model::node_func::NodeFunc;
+    use crate::model::variable::VariableScalar;
+
+    #[test]
+    fn node_tape() {
+
+        let x = VariableScalar::new_continuous("x");
+        let y = VariableScalar::new_continuous("y");
+        let w = VariableScalar::new_continuous("w");
+        let var2index = hashmap!{ x.clone() => 0, y.clone() => 1 };
+
+        // Shared subexpressions built separately
+        let e1 = (&x - &y).cos();
+        let e2 = 3.*(&x - &y).cos();
+        let e3 = (&x - &y).cos()*&y + &x/&y;
+
+        // x, y, -1, -1*y, x + -1*y, cos
+        let t1 = NodeTape::new(&[e1.clone()], &var2index);
+        assert_eq!(t1.len(), 6);
+
+        // 3, 3*cos
+        let t2 = NodeTape::new(&[e1.clone(), e2.clone()], &var2index);
+        assert_eq!(t2.len(), 8);
+
+        // cos*y, x/y, add
+        let t3 = NodeTape::new(&[e1.clone(), e2.clone(), e3.clone(), e1.clone()], &var2index);
+        assert_eq!(t3.len(), 11);
+
+        let values = t3.evaluate(&[2., 5.]);
+        let var_values = hashmap!{ &x => 2., &y => 5. };
+        assert_eq!(values.len(), 4);
+        for (v, e) in values.iter().zip([&e1, &e2, &e3, &e1].iter()) {
+            assert_abs_diff_eq!(*v, e.evaluate(&var_values), epsilon = 1e-12);
+        }
+
+        // Unknown variables
+        let t4 = NodeTape::new(&[&x + &w], &var2index);
+        assert!(t4.evaluate(&[2., 5.])[0].is_nan());
+    }
+}
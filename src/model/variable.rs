@@ -1,9 +1,8 @@
 //! Optimization variables.
 
 use std::fmt;
-use std::rc::Rc;
 
-use super::node::Node;
+use super::node::{Node, NodeRc};
 use super::node_base::NodeBase;
 use super::constant::ConstantScalar;
 
@@ -42,7 +41,7 @@ impl VariableScalar {
 
     /// Creates a new optimization variable expression node.
     pub fn new(name: &str, kind: VariableKind) -> Node {
-        Node::VariableScalar(Rc::new(
+        Node::VariableScalar(NodeRc::new(
             Self {
                 name: name.to_string(),
                 kind: kind,
@@ -18,6 +18,7 @@ pub enum Problem {
 
 /// Type that represents the evaluation function
 /// of an optimization problem.
+#[cfg(not(feature = "sync"))]
 pub type ProblemEval = Box<dyn Fn(&mut f64,              // phi
                                   &mut Vec<f64>,         // gphi
                                   &mut CooMat<f64>,      // Hphi
@@ -27,7 +28,32 @@ pub type ProblemEval = Box<dyn Fn(&mut f64,              // phi
                                   &[f64]                 // x
                                  ) -> ()>;
 
+/// Type that represents the evaluation function
+/// of an optimization problem. With the "sync" feature,
+/// it can be sent and shared across threads.
+#[cfg(feature = "sync")]
+pub type ProblemEval = Box<dyn Fn(&mut f64,              // phi
+                                  &mut Vec<f64>,         // gphi
+                                  &mut CooMat<f64>,      // Hphi
+                                  &mut Vec<f64>,         // f
+                                  &mut CooMat<f64>,      // J
+                                  &mut Vec<CooMat<f64>>, // H
+                                  &[f64]                 // x
+                                 ) -> () + Send + Sync>;
+
 /// Optimization problem solution.
+///
+/// The dual variables follow the sign convention of the Lagrangian
+/// ```ignore
+/// phi(x) - lam^T*(a*x - b) - nu^T*f(x) + mu^T*(x - u) - pi^T*(x - l),
+/// ```
+/// so at an optimal solution, the stationarity condition is
+/// ```ignore
+/// gphi - a^T*lam - J^T*nu + mu - pi = 0,
+/// ```
+/// with mu >= 0 and pi >= 0. In particular, for linear problems, the reduced
+/// costs c - a^T*lam are equal to pi - mu. The residuals of these conditions
+/// can be computed with [ProblemKkt](../kkt/trait.ProblemKkt.html).
 pub struct ProblemSol {
 
     /// Primal variable values.
@@ -44,6 +70,60 @@ pub struct ProblemSol {
 
     /// Dual variable values corresponding to variable lower limits.
     pub pi: Vec<f64>,
+
+    /// Sensitivity ranging information, if provided by the solver.
+    pub ranging: Option<ProblemSolRanging>,
+
+    /// Basis, if provided by the solver.
+    pub basis: Option<ProblemSolBasis>,
+
+    /// Solver output, if captured.
+    pub log: Option<String>,
+}
+
+/// Basis status of a variable or linear equality constraint.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BasisStatus {
+
+    /// Basic.
+    Basic,
+
+    /// Nonbasic at lower limit.
+    AtLower,
+
+    /// Nonbasic at upper limit.
+    AtUpper,
+
+    /// Nonbasic free, at zero.
+    Free,
+
+    /// Nonbasic.
+    Nonbasic,
+}
+
+/// Basis of a linear optimization problem solution.
+#[derive(Debug, Clone)]
+pub struct ProblemSolBasis {
+
+    /// Basis status of variables.
+    pub x: Vec<BasisStatus>,
+
+    /// Basis status of linear equality constraints.
+    pub a: Vec<BasisStatus>,
+}
+
+/// Sensitivity ranging information of a linear optimization problem 
+/// solution.
+#[derive(Debug, Clone)]
+pub struct ProblemSolRanging {
+
+    /// Lower and upper limits of the objective function gradient entries
+    /// over which the optimal basis remains optimal.
+    pub c: Vec<(f64, f64)>,
+
+    /// Lower and upper limits of the right-hand-side values of linear equality 
+    /// constraints over which the optimal basis remains optimal.
+    pub b: Vec<(f64, f64)>,
 }
 
 impl ProblemSol {
@@ -56,7 +136,10 @@ impl ProblemSol {
             lam: vec![0.;na],
             nu: vec![0.;nf],
             mu: vec![0.;nx],
-            pi: vec![0.;nx]
+            pi: vec![0.;nx],
+            ranging: None,
+            basis: None,
+            log: None,
         }
     }
 }
@@ -70,6 +153,9 @@ impl Debug for ProblemSol {
          .field("nu", &self.nu)
          .field("mu", &self.mu)
          .field("pi", &self.pi)
+         .field("ranging", &self.ranging)
+         .field("basis", &self.basis)
+         .field("log", &self.log)
          .finish()
     }
 }
\ No newline at end of file
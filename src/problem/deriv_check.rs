@@ -0,0 +1,449 @@
+//! Finite-difference checks of optimization problem derivatives.
+//!
+//! The objective function gradient and the nonlinear equality constraint Jacobian
+//! are compared against central differences of the function values, and the
+//! Hessians, including their linear combination obtained with unit weights, are
+//! compared against central differences of the gradient and Jacobian. Only the
+//! lower triangular parts of the Hessians are compared.
+
+use std::fmt;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use crate::problem::lp::ProblemLp;
+use crate::problem::nlp::ProblemNlp;
+use crate::problem::milp::ProblemMilp;
+use crate::problem::minlp::ProblemMinlp;
+use crate::problem::base::Problem;
+
+/// Maximum number of entries shown when displaying derivative check reports.
+pub const DERIV_REPORT_ENTRIES: usize = 10;
+
+/// Derivative compared in a derivative check.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProblemDerivKind {
+
+    /// Objective function gradient.
+    Gradient,
+
+    /// Objective function Hessian.
+    ObjectiveHessian,
+
+    /// Nonlinear equality constraint Jacobian.
+    Jacobian,
+
+    /// Hessian of the nonlinear equality constraint with the given index.
+    ConstraintHessian(usize),
+
+    /// Linear combination of nonlinear equality constraint Hessians
+    /// with unit weights.
+    CombinedHessian,
+}
+
+/// Entry of a derivative check.
+#[derive(Debug, Clone)]
+pub struct ProblemDerivEntry {
+
+    /// Derivative of entry.
+    pub kind: ProblemDerivKind,
+
+    /// Row index of entry. This is the variable index for the gradient, the
+    /// constraint index for the Jacobian, and a variable index for Hessians.
+    pub i: usize,
+
+    /// Column index of entry, which is a variable index.
+    pub j: usize,
+
+    /// Derivative value given by the problem.
+    pub value: f64,
+
+    /// Finite-difference estimate of the derivative.
+    pub estimate: f64,
+
+    /// Error of derivative value, i.e., |value - estimate|/max(1, |estimate|).
+    pub error: f64,
+}
+
+/// Report of a derivative check.
+#[derive(Debug, Clone)]
+pub struct ProblemDerivReport {
+
+    /// Compared entries that are nonzero or have nonzero estimates,
+    /// sorted by decreasing error, and then by derivative and indices.
+    pub entries: Vec<ProblemDerivEntry>,
+}
+
+/// A trait for checking optimization problem derivatives with finite differences.
+pub trait ProblemDerivCheck {
+
+    /// Compares derivatives at the given point against central differences with
+    /// steps step*max(1, |x_j|). The problem is left evaluated at the given point,
+    /// and its linear combination of constraint Hessians is restored.
+    fn check_derivatives(&mut self, x: &[f64], step: f64) -> ProblemDerivReport;
+}
+
+impl ProblemDerivReport {
+
+    /// Gets largest error, or zero if there are no entries.
+    pub fn max_error(&self) -> f64 {
+        self.entries.first().map(|e| e.error).unwrap_or(0.)
+    }
+
+    /// Gets the given number of entries with the largest errors.
+    pub fn worst(&self, n: usize) -> &[ProblemDerivEntry] {
+        &self.entries[..n.min(self.entries.len())]
+    }
+}
+
+impl ProblemDerivCheck for ProblemMinlp {
+
+    fn check_derivatives(&mut self, x: &[f64], step: f64) -> ProblemDerivReport {
+
+        assert_eq!(x.len(), self.nx());
+        let nx = self.nx();
+        let nf = self.nf();
+
+        // Derivatives
+        self.evaluate(x);
+        let mut gphi = vec![0.;nx];
+        for (k, g) in self.gphi().iter().enumerate() {
+            gphi[k] += g;
+        }
+        let j = ColumnMap::new(self.j().iter().map(|(i, j, v)| (*i, *j, *v)));
+        let mut hessians: Vec<ColumnMap> = Vec::with_capacity(nf + 2);
+        hessians.push(ColumnMap::new(lower(self.hphi().iter())));
+        for h in self.h().iter() {
+            hessians.push(ColumnMap::new(lower(h.iter())));
+        }
+        let hcomb = self.hcomb().data().to_vec();
+        self.combine_h(&vec![1.;nf]);
+        hessians.push(ColumnMap::new(lower(self.hcomb().iter())));
+        self.hcomb_mut().data_mut().copy_from_slice(&hcomb);
+        let kinds: Vec<ProblemDerivKind> = Some(ProblemDerivKind::ObjectiveHessian).into_iter()
+            .chain((0..nf).map(ProblemDerivKind::ConstraintHessian))
+            .chain(Some(ProblemDerivKind::CombinedHessian))
+            .collect();
+
+        let mut entries: Vec<ProblemDerivEntry> = Vec::new();
+        let mut xx = x.to_vec();
+        for col in 0..nx {
+
+            // Perturbed evaluations
+            let h = step*x[col].abs().max(1.);
+            xx[col] = x[col] + h;
+            let (phi_p, gphi_p, f_p, j_p) = self.values(&xx);
+            xx[col] = x[col] - h;
+            let (phi_m, gphi_m, f_m, j_m) = self.values(&xx);
+            xx[col] = x[col];
+
+            // Gradient
+            push_entry(&mut entries, ProblemDerivKind::Gradient, col, col, gphi[col], (phi_p - phi_m)/(2.*h));
+
+            // Jacobian
+            let mut fd: HashMap<usize, f64> = HashMap::new();
+            for (i, (fp, fm)) in f_p.iter().zip(f_m.iter()).enumerate() {
+                fd.insert(i, (fp - fm)/(2.*h));
+            }
+            compare(&mut entries, ProblemDerivKind::Jacobian, col, &j, &fd);
+
+            // Objective Hessian
+            let mut fd: HashMap<usize, f64> = HashMap::new();
+            for i in col..nx {
+                fd.insert(i, (gphi_p[i] - gphi_m[i])/(2.*h));
+            }
+            compare(&mut entries, kinds[0], col, &hessians[0], &fd);
+
+            // Constraint Hessians
+            let mut fd_rows: Vec<HashMap<usize, f64>> = vec![HashMap::new();nf];
+            let mut fd_comb: HashMap<usize, f64> = HashMap::new();
+            for ((r, c), vp) in j_p.iter() {
+                if *c >= col {
+                    let d = (vp - j_m.get(&(*r, *c)).unwrap_or(&0.))/(2.*h);
+                    *fd_rows[*r].entry(*c).or_insert(0.) += d;
+                    *fd_comb.entry(*c).or_insert(0.) += d;
+                }
+            }
+            for ((r, c), vm) in j_m.iter() {
+                if *c >= col && !j_p.contains_key(&(*r, *c)) {
+                    *fd_rows[*r].entry(*c).or_insert(0.) -= vm/(2.*h);
+                    *fd_comb.entry(*c).or_insert(0.) -= vm/(2.*h);
+                }
+            }
+            for (k, fd) in fd_rows.iter().enumerate() {
+                compare(&mut entries, kinds[k+1], col, &hessians[k+1], fd);
+            }
+            compare(&mut entries, kinds[nf+1], col, &hessians[nf+1], &fd_comb);
+        }
+
+        self.evaluate(x);
+        entries.sort_by(|a, b| {
+            b.error.partial_cmp(&a.error)
+                   .unwrap_or(Ordering::Equal)
+                   .then((a.kind.rank(), a.i, a.j).cmp(&(b.kind.rank(), b.i, b.j)))
+        });
+        ProblemDerivReport {
+            entries: entries,
+        }
+    }
+}
+
+impl ProblemMinlp {
+
+    /// Gets objective function value, gradient, nonlinear equality constraint
+    /// function values, and Jacobian entries at the given point.
+    fn values(&mut self, x: &[f64]) -> DerivValues {
+        self.evaluate(x);
+        let mut gphi = vec![0.;self.nx()];
+        for (k, g) in self.gphi().iter().enumerate() {
+            gphi[k] += g;
+        }
+        let mut j: HashMap<(usize, usize), f64> = HashMap::new();
+        for (r, c, v) in self.j().iter() {
+            *j.entry((*r, *c)).or_insert(0.) += v;
+        }
+        (self.phi(), gphi, self.f().to_vec(), j)
+    }
+}
+
+impl ProblemDerivKind {
+
+    /// Gets position of derivative in the order used for sorting entries
+    /// with equal errors.
+    fn rank(&self) -> usize {
+        match self {
+            ProblemDerivKind::Gradient => 0,
+            ProblemDerivKind::ObjectiveHessian => 1,
+            ProblemDerivKind::Jacobian => 2,
+            ProblemDerivKind::ConstraintHessian(k) => 3 + k,
+            ProblemDerivKind::CombinedHessian => usize::MAX,
+        }
+    }
+}
+
+impl ProblemDerivCheck for ProblemNlp {
+    fn check_derivatives(&mut self, x: &[f64], step: f64) -> ProblemDerivReport {
+        self.as_mut_minlp().check_derivatives(x, step)
+    }
+}
+
+impl ProblemDerivCheck for ProblemMilp {
+    fn check_derivatives(&mut self, x: &[f64], step: f64) -> ProblemDerivReport {
+        self.as_mut_minlp().check_derivatives(x, step)
+    }
+}
+
+impl ProblemDerivCheck for ProblemLp {
+    fn check_derivatives(&mut self, x: &[f64], step: f64) -> ProblemDerivReport {
+        self.as_mut_minlp().check_derivatives(x, step)
+    }
+}
+
+impl ProblemDerivCheck for Problem {
+    fn check_derivatives(&mut self, x: &[f64], step: f64) -> ProblemDerivReport {
+        match self {
+            Problem::Lp(p) => p.check_derivatives(x, step),
+            Problem::Milp(p) => p.check_derivatives(x, step),
+            Problem::Nlp(p) => p.check_derivatives(x, step),
+            Problem::Minlp(p) => p.check_derivatives(x, step),
+        }
+    }
+}
+
+impl fmt::Display for ProblemDerivKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProblemDerivKind::Gradient => write!(f, "gradient"),
+            ProblemDerivKind::ObjectiveHessian => write!(f, "objective hessian"),
+            ProblemDerivKind::Jacobian => write!(f, "jacobian"),
+            ProblemDerivKind::ConstraintHessian(k) => write!(f, "hessian of constraint {}", k),
+            ProblemDerivKind::CombinedHessian => write!(f, "combined hessian"),
+        }
+    }
+}
+
+impl fmt::Display for ProblemDerivEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f,
+               "{} ({}, {}) : {:.6e} vs {:.6e} (error {:.3e})",
+               self.kind,
+               self.i,
+               self.j,
+               self.value,
+               self.estimate,
+               self.error)
+    }
+}
+
+impl fmt::Display for ProblemDerivReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for e in self.worst(DERIV_REPORT_ENTRIES).iter() {
+            writeln!(f, "{}", e)?;
+        }
+        Ok(())
+    }
+}
+
+/// Objective function value, gradient, nonlinear equality constraint function
+/// values, and Jacobian entries with duplicates summed.
+type DerivValues = (f64, Vec<f64>, Vec<f64>, HashMap<(usize, usize), f64>);
+
+/// Sparse matrix with entries grouped by column and duplicates summed.
+struct ColumnMap {
+    cols: HashMap<usize, HashMap<usize, f64>>,
+}
+
+impl ColumnMap {
+
+    fn new<I: Iterator<Item = (usize, usize, f64)>>(entries: I) -> Self {
+        let mut cols: HashMap<usize, HashMap<usize, f64>> = HashMap::new();
+        for (i, j, v) in entries {
+            *cols.entry(j).or_default().entry(i).or_insert(0.) += v;
+        }
+        Self {
+            cols: cols,
+        }
+    }
+}
+
+/// Gets entries of lower triangular part of symmetric matrix.
+fn lower<'a, I>(entries: I) -> impl Iterator<Item = (usize, usize, f64)> + 'a
+    where I: Iterator<Item = (&'a usize, &'a usize, &'a f64)> + 'a {
+    entries.map(|(i, j, v)| if i >= j { (*i, *j, *v) } else { (*j, *i, *v) })
+}
+
+/// Compares column of matrix against its finite-difference estimate.
+fn compare(entries: &mut Vec<ProblemDerivEntry>,
+           kind: ProblemDerivKind,
+           col: usize,
+           mat: &ColumnMap,
+           fd: &HashMap<usize, f64>) {
+    let empty: HashMap<usize, f64> = HashMap::new();
+    let values = mat.cols.get(&col).unwrap_or(&empty);
+    for (i, v) in values.iter() {
+        push_entry(entries, kind, *i, col, *v, *fd.get(i).unwrap_or(&0.));
+    }
+    for (i, e) in fd.iter() {
+        if !values.contains_key(i) {
+            push_entry(entries, kind, *i, col, 0., *e);
+        }
+    }
+}
+
+/// Adds entry of derivative check, unless both value and estimate are zero.
+fn push_entry(entries: &mut Vec<ProblemDerivEntry>,
+              kind: ProblemDerivKind,
+              i: usize,
+              j: usize,
+              value: f64,
+              estimate: f64) {
+    if value == 0. && estimate == 0. {
+        return;
+    }
+    entries.push(ProblemDerivEntry {
+        kind: kind,
+        i: i,
+        j: j,
+        value: value,
+        estimate: estimate,
+        error: (value - estimate).abs()/estimate.abs().max(1.),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+
+    use approx::assert_abs_diff_eq;
+
+    use super::*;
+    use crate::matrix::coo::CooMat;
+
+    #[test]
+    fn deriv_check_nlp() {
+
+        // phi = x0^2*x1 + sin(x1), f0 = x0*x1 - 1, f1 = x1^3, with
+        // wrong d(f1)/d(x1) and missing d2(phi)/d(x0)d(x1)
+        let eval_fn = Box::new(move | phi: &mut f64,
+                                      gphi: &mut Vec<f64>,
+                                      hphi: &mut CooMat<f64>,
+                                      f: &mut Vec<f64>,
+                                      j: &mut CooMat<f64>,
+                                      h: &mut Vec<CooMat<f64>>,
+                                      x: &[f64] | {
+            *phi = x[0]*x[0]*x[1] + x[1].sin();
+            gphi[0] = 2.*x[0]*x[1];
+            gphi[1] = x[0]*x[0] + x[1].cos();
+            hphi.data_mut()[0] = 2.*x[1];
+            hphi.data_mut()[1] = 0.;
+            hphi.data_mut()[2] = -x[1].sin();
+            f[0] = x[0]*x[1] - 1.;
+            f[1] = x[1]*x[1]*x[1];
+            j.data_mut()[0] = x[1];
+            j.data_mut()[1] = x[0];
+            j.data_mut()[2] = 2.*x[1]*x[1];
+            h[0].data_mut()[0] = 1.;
+            h[1].data_mut()[0] = 6.*x[1];
+        });
+        let mut p = ProblemNlp::new(CooMat::from_pattern((2, 2), vec![0, 0, 1], vec![0, 1, 1]),
+                                    CooMat::from_nnz((0, 2), 0),
+                                    Vec::new(),
+                                    CooMat::from_pattern((2, 2), vec![0, 0, 1], vec![0, 1, 1]),
+                                    vec![CooMat::from_pattern((2, 2), vec![0], vec![1]),
+                                         CooMat::from_pattern((2, 2), vec![1], vec![1])],
+                                    vec![-10.;2],
+                                    vec![10.;2],
+                                    None,
+                                    eval_fn);
+
+        let x = vec![1.5, 2.];
+        p.evaluate(&x);
+        p.combine_h(&vec![2., 0.5]);
+        let report = p.check_derivatives(&x, 1e-6);
+
+        // Missing objective Hessian entry: 0 vs 3
+        let e = &report.entries[0];
+        assert_eq!(e.kind, ProblemDerivKind::ObjectiveHessian);
+        assert_eq!((e.i, e.j), (1, 0));
+        assert_abs_diff_eq!(e.value, 0.);
+        assert_abs_diff_eq!(e.estimate, 3., epsilon = 1e-5);
+        assert_abs_diff_eq!(e.error, 1., epsilon = 1e-5);
+
+        // Wrong Jacobian entry: 8 vs 12, and the Hessian entries of the
+        // constraint that are inconsistent with it: 12 vs 8
+        let mut wrong: Vec<(String, usize, usize)> = report.entries.iter()
+                                                                  .filter(|e| e.error > 1e-5)
+                                                                  .map(|e| (format!("{}", e.kind), e.i, e.j))
+                                                                  .collect();
+        wrong.sort();
+        assert_eq!(wrong, vec![("combined hessian".to_string(), 1, 1),
+                               ("hessian of constraint 1".to_string(), 1, 1),
+                               ("jacobian".to_string(), 1, 1),
+                               ("objective hessian".to_string(), 1, 0)]);
+        let e = report.entries.iter().find(|e| e.kind == ProblemDerivKind::Jacobian && e.i == 1 && e.j == 1).unwrap();
+        assert_abs_diff_eq!(e.value, 8.);
+        assert_abs_diff_eq!(e.estimate, 12., epsilon = 1e-5);
+        assert_abs_diff_eq!(e.error, 1./3., epsilon = 1e-5);
+        assert!(report.entries[4].error < 1e-5);
+
+        assert!(report.entries.iter().any(|e| e.kind == ProblemDerivKind::ConstraintHessian(1)));
+        assert!(report.entries.iter().any(|e| e.kind == ProblemDerivKind::CombinedHessian));
+        assert_eq!(report.worst(1).len(), 1);
+        assert_eq!(report.max_error(), report.entries[0].error);
+        assert!(format!("{}", report).starts_with("objective hessian (1, 0) : 0.000000e0 vs 3.0000"));
+        assert_eq!(format!("{}", report).lines().count(), DERIV_REPORT_ENTRIES);
+
+        // Problem is left evaluated at the point
+        assert_eq!(p.as_minlp().phi(), 1.5*1.5*2. + 2_f64.sin());
+        assert_eq!(p.hcomb().data(), &[2., 6.]);
+
+        // Linear problem
+        let mut p = Problem::Lp(ProblemLp::new(vec![1., -2.],
+                                               CooMat::from_nnz((0, 2), 0),
+                                               Vec::new(),
+                                               vec![0.;2],
+                                               vec![1.;2],
+                                               None));
+        let report = p.check_derivatives(&[0.5, 0.5], 1e-6);
+        assert_eq!(report.entries.len(), 2);
+        assert!(report.max_error() < 1e-8);
+    }
+}
@@ -0,0 +1,374 @@
+//! Verification of optimization problem solutions.
+//!
+//! The residuals of the Karush-Kuhn-Tucker (KKT) conditions of a solution are
+//! computed from the problem data and functions only, independently of the solver
+//! that produced the solution. The stationarity residual follows the sign
+//! convention of [ProblemSol](../base/struct.ProblemSol.html).
+
+use std::fmt;
+use simple_error::SimpleError;
+
+use crate::problem::lp::ProblemLp;
+use crate::problem::nlp::ProblemNlp;
+use crate::problem::milp::ProblemMilp;
+use crate::problem::minlp::ProblemMinlp;
+use crate::problem::base::{Problem, ProblemSol};
+
+/// Magnitude of variable limits that are treated as infinite, as the limits
+/// of free variables in standard problems of models.
+const INF: f64 = 1e8;
+
+/// Residuals of the optimality conditions of an optimization problem solution.
+/// All entries are zero for an exact optimal solution.
+#[derive(Debug, Clone)]
+pub struct ProblemKktReport {
+
+    /// Residuals a*x - b of linear equality constraints.
+    pub a: Vec<f64>,
+
+    /// Residuals f(x) of nonlinear equality constraints.
+    pub f: Vec<f64>,
+
+    /// Violations of variable limits, i.e., max(l - x, x - u, 0).
+    pub bounds: Vec<f64>,
+
+    /// Stationarity residuals gphi - a^T*lam - J^T*nu + mu - pi.
+    pub stationarity: Vec<f64>,
+
+    /// Violations of the nonnegativity of duals of variable limits, i.e., max(-mu, -pi, 0).
+    pub dual_sign: Vec<f64>,
+
+    /// Complementarity residuals of variable limits, i.e., the largest of |mu*(u - x)|
+    /// and |pi*(x - l)|. For infinite limits, i.e., of magnitude 1e8 or more,
+    /// the dual itself is used.
+    pub complementarity: Vec<f64>,
+
+    /// Distances of values of integer variables to the closest integers,
+    /// which are zero for continuous variables.
+    pub integrality: Vec<f64>,
+}
+
+/// A trait for computing residuals of the optimality conditions of
+/// optimization problem solutions.
+pub trait ProblemKkt {
+
+    /// Computes residuals of the optimality conditions of a solution. This
+    /// evaluates the problem functions at the primal values of the solution.
+    /// For problems with integer variables, the dual quantities only apply
+    /// if the solver provides duals, e.g., of the problem with fixed integers.
+    /// It fails if the dimensions of the solution do not match the problem.
+    fn kkt_residuals(&mut self, solution: &ProblemSol) -> Result<ProblemKktReport, SimpleError>;
+}
+
+impl ProblemKktReport {
+
+    /// Gets largest primal infeasibility, i.e., the largest absolute constraint
+    /// residual or violation of variable limits.
+    pub fn primal_residual(&self) -> f64 {
+        max_abs(&[&self.a, &self.f, &self.bounds])
+    }
+
+    /// Gets largest dual infeasibility, i.e., the largest absolute stationarity
+    /// residual or violation of the nonnegativity of duals of variable limits.
+    pub fn dual_residual(&self) -> f64 {
+        max_abs(&[&self.stationarity, &self.dual_sign])
+    }
+
+    /// Gets largest complementarity residual.
+    pub fn complementarity_residual(&self) -> f64 {
+        max_abs(&[&self.complementarity])
+    }
+
+    /// Gets largest integrality violation.
+    pub fn integrality_residual(&self) -> f64 {
+        max_abs(&[&self.integrality])
+    }
+
+    /// Determines whether the solution is primal feasible, including
+    /// integrality, within the given tolerance.
+    pub fn is_feasible(&self, tol: f64) -> bool {
+        self.primal_residual() <= tol && self.integrality_residual() <= tol
+    }
+
+    /// Determines whether the solution satisfies all optimality conditions
+    /// within the given tolerance.
+    pub fn is_optimal(&self, tol: f64) -> bool {
+        self.is_feasible(tol) &&
+        self.dual_residual() <= tol &&
+        self.complementarity_residual() <= tol
+    }
+}
+
+impl ProblemKkt for ProblemMinlp {
+
+    fn kkt_residuals(&mut self, solution: &ProblemSol) -> Result<ProblemKktReport, SimpleError> {
+
+        let x = &solution.x;
+        if x.len() != self.nx() ||
+           solution.lam.len() != self.na() ||
+           solution.nu.len() != self.nf() ||
+           solution.mu.len() != self.nx() ||
+           solution.pi.len() != self.nx() {
+            return Err(SimpleError::new("invalid solution dimensions"));
+        }
+
+        self.evaluate(x);
+
+        // Primal
+        let mut a: Vec<f64> = self.b().iter().map(|b| -b).collect();
+        for (i, j, v) in self.a().iter() {
+            a[*i] += v*x[*j];
+        }
+        let f = self.f().to_vec();
+        let bounds: Vec<f64> = x.iter()
+                                .zip(self.l().iter().zip(self.u().iter()))
+                                .map(|(x, (l, u))| (l - x).max(x - u).max(0.))
+                                .collect();
+
+        // Dual
+        let mut stationarity = self.gphi().to_vec();
+        for (i, j, v) in self.a().iter() {
+            stationarity[*j] -= v*solution.lam[*i];
+        }
+        for (i, j, v) in self.j().iter() {
+            stationarity[*j] -= v*solution.nu[*i];
+        }
+        for (k, r) in stationarity.iter_mut().enumerate() {
+            *r += solution.mu[k] - solution.pi[k];
+        }
+        let dual_sign: Vec<f64> = solution.mu.iter()
+                                             .zip(solution.pi.iter())
+                                             .map(|(mu, pi)| (-mu).max(-pi).max(0.))
+                                             .collect();
+
+        // Complementarity
+        let comp = |dual: f64, limit: f64, gap: f64| if limit.abs() < INF { (dual*gap).abs() } else { dual.abs() };
+        let complementarity: Vec<f64> = (0..self.nx()).map(|k| {
+            comp(solution.mu[k], self.u()[k], self.u()[k] - x[k])
+                .max(comp(solution.pi[k], self.l()[k], x[k] - self.l()[k]))
+        }).collect();
+
+        // Integrality
+        let integrality: Vec<f64> = x.iter()
+                                     .zip(self.p().iter())
+                                     .map(|(x, p)| if *p { (x - x.round()).abs() } else { 0. })
+                                     .collect();
+
+        Ok(ProblemKktReport {
+            a: a,
+            f: f,
+            bounds: bounds,
+            stationarity: stationarity,
+            dual_sign: dual_sign,
+            complementarity: complementarity,
+            integrality: integrality,
+        })
+    }
+}
+
+impl ProblemKkt for ProblemNlp {
+    fn kkt_residuals(&mut self, solution: &ProblemSol) -> Result<ProblemKktReport, SimpleError> {
+        self.as_mut_minlp().kkt_residuals(solution)
+    }
+}
+
+impl ProblemKkt for ProblemMilp {
+    fn kkt_residuals(&mut self, solution: &ProblemSol) -> Result<ProblemKktReport, SimpleError> {
+        self.as_mut_minlp().kkt_residuals(solution)
+    }
+}
+
+impl ProblemKkt for ProblemLp {
+    fn kkt_residuals(&mut self, solution: &ProblemSol) -> Result<ProblemKktReport, SimpleError> {
+        self.as_mut_minlp().kkt_residuals(solution)
+    }
+}
+
+impl ProblemKkt for Problem {
+    fn kkt_residuals(&mut self, solution: &ProblemSol) -> Result<ProblemKktReport, SimpleError> {
+        match self {
+            Problem::Lp(x) => x.kkt_residuals(solution),
+            Problem::Milp(x) => x.kkt_residuals(solution),
+            Problem::Nlp(x) => x.kkt_residuals(solution),
+            Problem::Minlp(x) => x.kkt_residuals(solution),
+        }
+    }
+}
+
+impl fmt::Display for ProblemKktReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let line = |f: &mut fmt::Formatter<'_>, name: &str, values: &[f64]| -> fmt::Result {
+            match argmax_abs(values) {
+                Some(k) => writeln!(f, "{:<16}: {:.3e} (index {})", name, values[k].abs(), k),
+                None => writeln!(f, "{:<16}: {:.3e}", name, 0.),
+            }
+        };
+        line(f, "linear", &self.a)?;
+        line(f, "nonlinear", &self.f)?;
+        line(f, "bounds", &self.bounds)?;
+        line(f, "stationarity", &self.stationarity)?;
+        line(f, "dual sign", &self.dual_sign)?;
+        line(f, "complementarity", &self.complementarity)?;
+        line(f, "integrality", &self.integrality)
+    }
+}
+
+/// Gets largest absolute value of the given vectors.
+fn max_abs(values: &[&Vec<f64>]) -> f64 {
+    values.iter().flat_map(|v| v.iter()).fold(0., |m, v| v.abs().max(m))
+}
+
+/// Gets index of value with largest absolute value, if any.
+fn argmax_abs(values: &[f64]) -> Option<usize> {
+    let mut best: Option<usize> = None;
+    for (k, v) in values.iter().enumerate() {
+        match best {
+            Some(b) if v.abs() <= values[b].abs() => (),
+            _ => best = Some(k),
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+
+    use approx::assert_abs_diff_eq;
+
+    use super::*;
+    use crate::assert_vec_approx_eq;
+    use crate::matrix::coo::CooMat;
+
+    #[test]
+    fn kkt_lp() {
+
+        // Problem of clp_solve_lp test
+        let mut p = Problem::Lp(ProblemLp::new(
+            vec![180.,160., 0., 0., 0.],
+            CooMat::new(
+                (3, 5),
+                vec![0,0,0,1,1,1,2,2,2],
+                vec![0,1,2,0,1,3,0,1,4],
+                vec![6.,1.,1.,3.,1.,1.,4.,6.,1.]),
+            vec![12.,8.,24.],
+            vec![0.,0.,-1e8,-1e8,-1e8],
+            vec![5.,5.,0.,0.,0.],
+            None,
+        ));
+
+        let mut sol = ProblemSol::new(5, 3, 0);
+        sol.x = vec![12./7., 20./7., -8./7., 0., 0.];
+        sol.lam = vec![0., 220./7., 150./7.];
+        sol.mu = vec![0., 0., 0., 220./7., 150./7.];
+
+        let report = p.kkt_residuals(&sol).unwrap();
+        assert!(report.is_optimal(1e-10));
+        assert_vec_approx_eq!(report.a, vec![0.;3], epsilon = 1e-12);
+        assert_vec_approx_eq!(report.stationarity, vec![0.;5], epsilon = 1e-12);
+        assert!(report.f.is_empty());
+
+        // Dual of limit -1e8 taken as infinite
+        sol.pi = vec![0., 0., 0.5, 0., 0.];
+        let report = p.kkt_residuals(&sol).unwrap();
+        assert_abs_diff_eq!(report.complementarity[2], 0.5);
+        sol.pi = vec![0.; 5];
+
+        // Wrong dual signs give stationarity residuals
+        let mut sol_neg = ProblemSol::new(5, 3, 0);
+        sol_neg.x = sol.x.clone();
+        sol_neg.lam = sol.lam.iter().map(|x| -x).collect();
+        sol_neg.mu = sol.mu.clone();
+        let report = p.kkt_residuals(&sol_neg).unwrap();
+        assert!(report.is_feasible(1e-10));
+        assert!(!report.is_optimal(1e-10));
+        assert_abs_diff_eq!(report.stationarity[1], 2.*(220./7. + 6.*150./7.), epsilon = 1e-9);
+
+        // Primal infeasible and not complementary
+        let mut sol_inf = ProblemSol::new(5, 3, 0);
+        sol_inf.x = vec![6., 0., 0., 0., 0.];
+        sol_inf.pi = vec![0., 2., 0., 0., 0.];
+        sol_inf.mu = vec![0., 0., -1., 0., 0.];
+        let report = p.kkt_residuals(&sol_inf).unwrap();
+        assert!(!report.is_feasible(1e-10));
+        assert_vec_approx_eq!(report.a, vec![24., 10., 0.], epsilon = 1e-12);
+        assert_vec_approx_eq!(report.bounds, vec![1., 0., 0., 0., 0.], epsilon = 1e-12);
+        assert_vec_approx_eq!(report.dual_sign, vec![0., 0., 1., 0., 0.], epsilon = 1e-12);
+        assert_vec_approx_eq!(report.complementarity, vec![0., 0., 0., 0., 0.], epsilon = 1e-12);
+        assert_abs_diff_eq!(report.primal_residual(), 24.);
+        assert_abs_diff_eq!(report.dual_residual(), 180.);
+
+        let text = format!("{}", report);
+        assert!(text.contains("linear          : 2.400e1 (index 0)"));
+        assert!(text.contains("bounds          : 1.000e0 (index 0)"));
+        assert!(text.contains("nonlinear       : 0.000e0\n"));
+
+        // Invalid dimensions
+        let sol_dim = ProblemSol::new(5, 2, 0);
+        assert_eq!(format!("{}", p.kkt_residuals(&sol_dim).err().unwrap()), "invalid solution dimensions");
+    }
+
+    #[test]
+    fn kkt_nlp_and_milp() {
+
+        // minimize   x0^2 + x1
+        // subject to x0*x1 - 2 = 0
+        //            x1 <= 4
+        let eval_fn = Box::new(move | phi: &mut f64,
+                                      gphi: &mut Vec<f64>,
+                                      hphi: &mut CooMat<f64>,
+                                      f: &mut Vec<f64>,
+                                      j: &mut CooMat<f64>,
+                                      h: &mut Vec<CooMat<f64>>,
+                                      x: &[f64] | {
+            *phi = x[0]*x[0] + x[1];
+            gphi[0] = 2.*x[0];
+            gphi[1] = 1.;
+            hphi.data_mut()[0] = 2.;
+            f[0] = x[0]*x[1] - 2.;
+            j.data_mut()[0] = x[1];
+            j.data_mut()[1] = x[0];
+            h[0].data_mut()[0] = 1.;
+        });
+        let mut p = ProblemNlp::new(CooMat::from_pattern((2, 2), vec![0], vec![0]),
+                                    CooMat::from_nnz((0, 2), 0),
+                                    Vec::new(),
+                                    CooMat::from_pattern((1, 2), vec![0, 0], vec![0, 1]),
+                                    vec![CooMat::from_pattern((2, 2), vec![1], vec![0])],
+                                    vec![-f64::INFINITY, -f64::INFINITY],
+                                    vec![f64::INFINITY, 4.],
+                                    None,
+                                    eval_fn);
+
+        // Stationary point x = (1/2, 4) with negative dual of upper limit of x1:
+        // 2*x0 - nu*x1 = 0 and 1 - nu*x0 + mu = 0
+        let mut sol = ProblemSol::new(2, 0, 1);
+        sol.x = vec![0.5, 4.];
+        sol.nu = vec![0.25];
+        sol.mu = vec![0., -0.875];
+        let report = p.kkt_residuals(&sol).unwrap();
+        assert!(report.is_feasible(1e-12));
+        assert_vec_approx_eq!(report.stationarity, vec![0.;2], epsilon = 1e-12);
+        assert_vec_approx_eq!(report.dual_sign, vec![0., 0.875], epsilon = 1e-12);
+
+        // Dual of infinite limit
+        sol.mu = vec![1., -0.875];
+        let report = p.kkt_residuals(&sol).unwrap();
+        assert_abs_diff_eq!(report.complementarity[0], 1.);
+
+        // Integrality
+        let mut p = ProblemMilp::new(vec![1., 1.],
+                                     CooMat::new((1, 2), vec![0, 0], vec![0, 1], vec![1., 1.]),
+                                     vec![2.5],
+                                     vec![0., 0.],
+                                     vec![5., 5.],
+                                     vec![true, false],
+                                     None);
+        let mut sol = ProblemSol::new(2, 1, 0);
+        sol.x = vec![1.25, 1.25];
+        let report = p.kkt_residuals(&sol).unwrap();
+        assert_vec_approx_eq!(report.integrality, vec![0.25, 0.], epsilon = 1e-12);
+        assert!(!report.is_feasible(1e-6));
+        sol.x = vec![1., 1.5];
+        assert!(p.kkt_residuals(&sol).unwrap().is_feasible(1e-12));
+    }
+}
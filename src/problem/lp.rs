@@ -87,6 +87,9 @@ impl ProblemLp {
     /// Returns a mutable reference to the problem cast as a Milp.
     pub fn as_mut_milp(&mut self) -> &mut ProblemMilp { &mut self.base_milp }
     
+    /// Returns a reference to the problem cast as a Minlp.
+    pub fn as_minlp(&self) -> &ProblemMinlp { self.base_milp.as_minlp() }
+
     /// Returns a mutable reference to the problem cast as a Minlp.
     pub fn as_mut_minlp(&mut self) -> &mut ProblemMinlp { self.base_milp.as_mut_minlp() } 
     
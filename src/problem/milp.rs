@@ -29,6 +29,10 @@ pub trait ProblemMilpIO {
 
     /// Writes problem to LP file.
     fn write_to_lp_file(&self, filename: &str) -> io::Result<()>;
+
+    /// Writes problem to free MPS file. Variables and linear equality
+    /// constraints are written in order with names "x_i" and "c_i".
+    fn write_to_mps_file(&self, filename: &str) -> io::Result<()>;
 }
 
 impl ProblemMilp {
@@ -98,6 +102,9 @@ impl ProblemMilp {
     /// Number of linear equality cosntraints.
     pub fn na(&self) -> usize { self.b().len() }
 
+    /// Returns a reference to the problem cast as a Minlp.
+    pub fn as_minlp(&self) -> &ProblemMinlp { &self.base }
+
     /// Returns a mutable reference to the problem cast as a Minlp.
     pub fn as_mut_minlp(&mut self) -> &mut ProblemMinlp { &mut self.base }
     
@@ -200,4 +207,157 @@ impl ProblemMilpIO for ProblemMilp {
 
         Ok(())
     }
+
+    fn write_to_mps_file(&self, filename: &str) -> io::Result<()> {
+
+        let f = File::create(filename)?;
+
+        let mut w = BufWriter::new(f);
+
+        // Columns of constraint matrix
+        let mut a = self.a().to_csr();
+        a.sum_duplicates();
+        let mut cols: Vec<Vec<(usize, f64)>> = vec![Vec::new(); self.nx()];
+        for i in 0..a.rows() {
+            for k in a.indptr()[i]..a.indptr()[i+1] {
+                if a.data()[k] != 0. {
+                    cols[a.indices()[k]].push((i, a.data()[k]));
+                }
+            }
+        }
+
+        // Name
+        writeln!(w, "NAME numopt")?;
+
+        // Rows
+        writeln!(w, "ROWS")?;
+        writeln!(w, " N obj")?;
+        for i in 0..self.na() {
+            writeln!(w, " E c_{}", i)?;
+        }
+
+        // Columns
+        let mut integer = false;
+        writeln!(w, "COLUMNS")?;
+        for (j, col) in cols.iter().enumerate() {
+            if self.p()[j] != integer {
+                integer = self.p()[j];
+                writeln!(w, " M{} 'MARKER' '{}'", j, if integer { "INTORG" } else { "INTEND" })?;
+            }
+            writeln!(w, " x_{} obj {:.10e}", j, self.c()[j])?;
+            for (i, d) in col.iter() {
+                writeln!(w, " x_{} c_{} {:.10e}", j, i, d)?;
+            }
+        }
+        if integer {
+            writeln!(w, " M{} 'MARKER' 'INTEND'", self.nx())?;
+        }
+
+        // Rhs
+        writeln!(w, "RHS")?;
+        for (i, b) in self.b().iter().enumerate() {
+            if *b != 0. {
+                writeln!(w, " rhs c_{} {:.10e}", i, b)?;
+            }
+        }
+
+        // Bounds
+        writeln!(w, "BOUNDS")?;
+        for j in 0..self.nx() {
+            if self.l()[j] == self.u()[j] {
+                writeln!(w, " FX bnd x_{} {:.10e}", j, self.l()[j])?;
+            }
+            else {
+                writeln!(w, " LO bnd x_{} {:.10e}", j, self.l()[j])?;
+                writeln!(w, " UP bnd x_{} {:.10e}", j, self.u()[j])?;
+            }
+        }
+
+        // End
+        writeln!(w, "ENDATA")?;
+
+        w.flush()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::fs;
+    use tempfile::NamedTempFile;
+
+    use crate::matrix::coo::CooMat;
+    use crate::problem::milp::{ProblemMilp, ProblemMilpIO};
+
+    #[test]
+    fn milp_write_to_mps_file() {
+
+        // min        -x0 - x1 
+        // subject to -2*x0 +  2*x1 + x2 == 1
+        //            -8*x0 + 10*x1 + x3 ==  13
+        //            x2 <= 0
+        //            x3 >= 0
+        //            x0 integer
+        //            x1 integer
+
+        let p = ProblemMilp::new(
+            vec![-1.,-1., 0., 0.],
+            CooMat::new(
+                (2, 4),
+                vec![0,0,0,1,1,1],
+                vec![0,1,2,0,1,3],
+                vec![-2.,2.,1.,-8.,10.,1.]),
+            vec![1.,13.],
+            vec![-1e8,-1e8,-1e8,0.],
+            vec![1e8,1e8,0.,1e8],
+            vec![true, true, false, false],
+            None,
+        );
+
+        let f = NamedTempFile::new().unwrap();
+        let fname = f.path().to_str().unwrap();
+        p.write_to_mps_file(fname).unwrap();
+
+        let lines: Vec<Vec<String>> = fs::read_to_string(fname).unwrap()
+                                                               .lines()
+                                                               .map(|l| l.split_ascii_whitespace()
+                                                                         .map(|s| s.to_string())
+                                                                         .collect())
+                                                               .collect();
+        let sections: Vec<&str> = lines.iter()
+                                       .filter(|l| l.len() == 1)
+                                       .map(|l| l[0].as_ref())
+                                       .collect();
+        assert_eq!(sections, vec!["ROWS", "COLUMNS", "RHS", "BOUNDS", "ENDATA"]);
+        
+        let markers: Vec<&str> = lines.iter()
+                                      .filter(|l| l.len() == 3 && l[1] == "'MARKER'")
+                                      .map(|l| l[2].as_ref())
+                                      .collect();
+        assert_eq!(markers, vec!["'INTORG'", "'INTEND'"]);
+
+        let entries: Vec<(String, String, f64)> = lines.iter()
+                                                       .filter(|l| l.len() == 3 && l[0].starts_with("x_"))
+                                                       .map(|l| (l[0].clone(), l[1].clone(), l[2].parse().unwrap()))
+                                                       .collect();
+        assert_eq!(entries.len(), 10);
+        assert!(entries.contains(&("x_1".to_string(), "c_1".to_string(), 10.)));
+        assert!(entries.contains(&("x_3".to_string(), "obj".to_string(), 0.)));
+
+        let rhs: Vec<f64> = lines.iter()
+                                 .filter(|l| l[0] == "rhs")
+                                 .map(|l| l[2].parse().unwrap())
+                                 .collect();
+        assert_eq!(rhs, vec![1., 13.]);
+
+        let bounds: Vec<(String, String, f64)> = lines.iter()
+                                                      .filter(|l| l.len() == 4 && l[1] == "bnd")
+                                                      .map(|l| (l[0].clone(), l[2].clone(), l[3].parse().unwrap()))
+                                                      .collect();
+        assert_eq!(bounds.len(), 8);
+        assert!(bounds.contains(&("UP".to_string(), "x_2".to_string(), 0.)));
+        assert!(bounds.contains(&("LO".to_string(), "x_3".to_string(), 0.)));
+    }
 }
\ No newline at end of file
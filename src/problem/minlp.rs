@@ -141,6 +141,10 @@ impl ProblemMinlp {
     /// (lower triangular parts).
     pub fn hcomb(&self) -> &CooMat<f64> { &self.hcomb }
 
+    /// Mutable linear combination of nonlinear equality constraint function Hessian
+    /// values (lower triangular parts).
+    pub(crate) fn hcomb_mut(&mut self) -> &mut CooMat<f64> { &mut self.hcomb }
+
     /// Vector of optimization variable lower limits.
     pub fn l(&self) -> &[f64] { &self.l }
 
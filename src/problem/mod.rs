@@ -5,4 +5,9 @@ pub mod minlp;
 pub mod milp;
 pub mod nlp;
 pub mod lp;
+pub mod presolve;
+pub mod scaling;
+pub mod kkt;
+pub mod deriv_check;
+pub mod ranging;
 
@@ -89,6 +89,9 @@ impl ProblemNlp {
     /// function Hessians.    
     pub fn combine_h(&mut self, nu: &[f64]) -> () { self.base.combine_h(nu) }
     
+    /// Returns a reference to the problem cast as a Minlp.
+    pub fn as_minlp(&self) -> &ProblemMinlp { &self.base }
+
     // Returns a mutable reference to the problem cast as a Minlp.
     pub fn as_mut_minlp(&mut self) -> &mut ProblemMinlp { &mut self.base }
 }
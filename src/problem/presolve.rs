@@ -0,0 +1,653 @@
+//! Presolve and postsolve of linear and mixed-integer linear optimization problems.
+//!
+//! Presolve removes empty, singleton and duplicate rows, fixes variables with
+//! equal bounds, merges duplicate columns, rounds the bounds of integer variables,
+//! and fixes dominated columns. Postsolve maps a solution of the presolved problem
+//! back to a solution of the original problem, including the primal values and
+//! the duals of all constraints and variable limits. Duals of removed variables are
+//! obtained from their reduced costs c - a^T*lam, which equal pi - mu.
+//! Variable limits with magnitude 1e8 or more are treated as infinite.
+
+use std::collections::HashMap;
+use simple_error::SimpleError;
+
+use crate::matrix::coo::CooMat;
+use crate::problem::lp::ProblemLp;
+use crate::problem::milp::ProblemMilp;
+use crate::problem::base::ProblemSol;
+
+/// Tolerance for presolve feasibility checks.
+const TOL: f64 = 1e-9;
+
+/// Magnitude of variable limits that are treated as infinite, as the limits
+/// of free variables in standard problems of models.
+const INF: f64 = 1e8;
+
+/// A trait for presolving linear and mixed-integer linear optimization problems.
+pub trait ProblemPresolve: Sized {
+
+    /// Obtains presolved problem and postsolve data for mapping solutions of the
+    /// presolved problem to solutions of this problem. It fails if the problem
+    /// is found to be infeasible.
+    fn presolve(&self) -> Result<(Self, ProblemPostsolve), SimpleError>;
+}
+
+/// Presolve reduction, in terms of original row and column indices.
+#[derive(Debug, Clone)]
+enum PresolveOp {
+    EmptyRow(usize),
+    DuplicateRow(usize),
+    SingletonRow(usize, usize),
+    FixedCol(usize, f64),
+    MergedCols(usize, usize, (f64, f64), (f64, f64)),
+}
+
+/// Postsolve data of a presolved problem.
+#[derive(Debug, Clone)]
+pub struct ProblemPostsolve {
+    c: Vec<f64>,
+    cols: Vec<Vec<(usize, f64)>>,
+    na: usize,
+    kept_rows: Vec<usize>,
+    kept_cols: Vec<usize>,
+    ops: Vec<PresolveOp>,
+    offset: f64,
+}
+
+/// Problem data during presolve.
+struct PresolveData {
+    c: Vec<f64>,
+    b: Vec<f64>,
+    l: Vec<f64>,
+    u: Vec<f64>,
+    p: Vec<bool>,
+    x0: Option<Vec<f64>>,
+    rows: Vec<HashMap<usize, f64>>,
+    cols: Vec<HashMap<usize, f64>>,
+    row_active: Vec<bool>,
+    col_active: Vec<bool>,
+    ops: Vec<PresolveOp>,
+    offset: f64,
+}
+
+impl ProblemPostsolve {
+
+    /// Gets the objective function constant of the removed variables, which
+    /// needs to be added to the objective value of the presolved problem.
+    pub fn offset(&self) -> f64 { self.offset }
+
+    /// Gets the number of linear equality constraints removed by presolve.
+    pub fn removed_rows(&self) -> usize { self.na - self.kept_rows.len() }
+
+    /// Gets the number of variables removed by presolve.
+    pub fn removed_cols(&self) -> usize { self.c.len() - self.kept_cols.len() }
+
+    /// Obtains solution of the original problem from a solution of the presolved
+    /// problem. Ranging and basis information are not mapped.
+    pub fn postsolve(&self, solution: &ProblemSol) -> ProblemSol {
+
+        let nx = self.c.len();
+        let mut s = ProblemSol::new(nx, self.na, 0);
+        s.log = solution.log.clone();
+
+        // Kept rows and columns
+        for (k, i) in self.kept_rows.iter().enumerate() {
+            s.lam[*i] = solution.lam[k];
+        }
+        for (k, j) in self.kept_cols.iter().enumerate() {
+            s.x[*j] = solution.x[k];
+            s.mu[*j] = solution.mu[k];
+            s.pi[*j] = solution.pi[k];
+        }
+
+        // Reductions in reverse order
+        let mut recompute: Vec<usize> = Vec::new();
+        for op in self.ops.iter().rev() {
+            match op {
+                PresolveOp::EmptyRow(i) | PresolveOp::DuplicateRow(i) => s.lam[*i] = 0.,
+                PresolveOp::SingletonRow(i, j) => {
+                    let mut a = 0.;
+                    let mut d = self.c[*j];
+                    for (r, val) in self.cols[*j].iter() {
+                        if *r == *i {
+                            a = *val;
+                        }
+                        else {
+                            d -= val*s.lam[*r];
+                        }
+                    }
+                    s.lam[*i] = d/a;
+                },
+                PresolveOp::FixedCol(j, value) => {
+                    s.x[*j] = *value;
+                    recompute.push(*j);
+                },
+                PresolveOp::MergedCols(j, k, (lj, uj), (lk, uk)) => {
+                    let x = s.x[*j];
+                    let lo = lj.max(x - uk);
+                    let hi = uj.min(x - lk);
+                    s.x[*j] = lo.max(hi.min(0.));
+                    s.x[*k] = x - s.x[*j];
+                    recompute.push(*j);
+                    recompute.push(*k);
+                },
+            }
+        }
+
+        // Duals of limits of removed variables from reduced costs
+        for j in recompute.into_iter() {
+            let d = self.cols[j].iter().fold(self.c[j], |d, (r, val)| d - val*s.lam[*r]);
+            s.pi[j] = d.max(0.);
+            s.mu[j] = (-d).max(0.);
+        }
+
+        s
+    }
+}
+
+impl PresolveData {
+
+    /// Creates presolve data from problem data.
+    fn new(c: &[f64],
+           a: &CooMat<f64>,
+           b: &[f64],
+           l: &[f64],
+           u: &[f64],
+           p: &[bool],
+           x0: Option<&[f64]>) -> Self {
+        let mut rows: Vec<HashMap<usize, f64>> = vec![HashMap::new(); b.len()];
+        let mut cols: Vec<HashMap<usize, f64>> = vec![HashMap::new(); c.len()];
+        for (i, j, val) in a.iter() {
+            *rows[*i].entry(*j).or_insert(0.) += *val;
+            *cols[*j].entry(*i).or_insert(0.) += *val;
+        }
+        for row in rows.iter_mut() {
+            row.retain(|_, val| *val != 0.);
+        }
+        for col in cols.iter_mut() {
+            col.retain(|_, val| *val != 0.);
+        }
+        Self {
+            c: c.to_vec(),
+            b: b.to_vec(),
+            l: l.to_vec(),
+            u: u.to_vec(),
+            p: p.to_vec(),
+            x0: x0.map(|x| x.to_vec()),
+            rows: rows,
+            cols: cols,
+            row_active: vec![true; b.len()],
+            col_active: vec![true; c.len()],
+            ops: Vec::new(),
+            offset: 0.,
+        }
+    }
+
+    /// Applies reductions until no more reductions are found.
+    fn run(&mut self) -> Result<(), SimpleError> {
+        let mut changed = true;
+        while changed {
+            changed = self.round_bounds()?;
+            changed |= self.fix_cols();
+            changed |= self.reduce_rows()?;
+            changed |= self.remove_duplicate_rows()?;
+            changed |= self.merge_duplicate_cols();
+            changed |= self.fix_dominated_cols();
+        }
+        Ok(())
+    }
+
+    /// Rounds bounds of integer variables and checks bounds.
+    fn round_bounds(&mut self) -> Result<bool, SimpleError> {
+        let mut changed = false;
+        for j in 0..self.c.len() {
+            if !self.col_active[j] {
+                continue;
+            }
+            if self.p[j] {
+                let l = (self.l[j] - TOL).ceil();
+                let u = (self.u[j] + TOL).floor();
+                changed |= l != self.l[j] || u != self.u[j];
+                self.l[j] = l;
+                self.u[j] = u;
+            }
+            if self.l[j] > self.u[j] + TOL {
+                return Err(SimpleError::new(format!("infeasible limits of variable {}", j)));
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Removes variables with equal lower and upper limits.
+    fn fix_cols(&mut self) -> bool {
+        let mut changed = false;
+        for j in 0..self.c.len() {
+            if self.col_active[j] && self.u[j] - self.l[j] <= TOL {
+                self.fix_col(j, self.l[j]);
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Removes variable at the given value.
+    fn fix_col(&mut self, j: usize, value: f64) {
+        for (i, val) in self.cols[j].drain() {
+            self.b[i] -= val*value;
+            self.rows[i].remove(&j);
+        }
+        self.offset += self.c[j]*value;
+        self.col_active[j] = false;
+        self.ops.push(PresolveOp::FixedCol(j, value));
+    }
+
+    /// Removes empty rows, and singleton rows along with the variables they fix.
+    fn reduce_rows(&mut self) -> Result<bool, SimpleError> {
+        let mut changed = false;
+        for i in 0..self.b.len() {
+            if !self.row_active[i] {
+                continue;
+            }
+            match self.rows[i].len() {
+                0 => {
+                    if self.b[i].abs() > TOL*(1. + self.b[i].abs()) {
+                        return Err(SimpleError::new(format!("infeasible empty constraint {}", i)));
+                    }
+                    self.row_active[i] = false;
+                    self.ops.push(PresolveOp::EmptyRow(i));
+                    changed = true;
+                },
+                1 => {
+                    let (j, a) = self.rows[i].iter().map(|(j, a)| (*j, *a)).next().unwrap();
+                    let mut value = self.b[i]/a;
+                    if value < self.l[j] - TOL*(1. + value.abs()) ||
+                       value > self.u[j] + TOL*(1. + value.abs()) ||
+                       (self.p[j] && (value - value.round()).abs() > TOL) {
+                        return Err(SimpleError::new(format!("infeasible singleton constraint {}", i)));
+                    }
+                    if self.p[j] {
+                        value = value.round();
+                    }
+                    value = value.max(self.l[j]).min(self.u[j]);
+                    self.rows[i].clear();
+                    self.cols[j].remove(&i);
+                    self.row_active[i] = false;
+                    self.ops.push(PresolveOp::SingletonRow(i, j));
+                    self.fix_col(j, value);
+                    changed = true;
+                },
+                _ => (),
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Removes rows that are multiples of other rows.
+    fn remove_duplicate_rows(&mut self) -> Result<bool, SimpleError> {
+        let mut changed = false;
+        let mut keys: HashMap<Vec<(usize, u64)>, (usize, f64)> = HashMap::new();
+        for i in 0..self.b.len() {
+            if !self.row_active[i] || self.rows[i].is_empty() {
+                continue;
+            }
+            let mut entries: Vec<(usize, f64)> = self.rows[i].iter().map(|(j, a)| (*j, *a)).collect();
+            entries.sort_by_key(|(j, _)| *j);
+            let scale = entries[0].1;
+            let key: Vec<(usize, u64)> = entries.iter().map(|(j, a)| (*j, (a/scale).to_bits())).collect();
+            match keys.get(&key) {
+                Some((k, scale_k)) => {
+                    let t = scale/scale_k;
+                    if (self.b[i] - t*self.b[*k]).abs() > TOL*(1. + self.b[i].abs()) {
+                        return Err(SimpleError::new(format!("inconsistent duplicate constraints {} and {}", k, i)));
+                    }
+                    for (j, _) in self.rows[i].drain() {
+                        self.cols[j].remove(&i);
+                    }
+                    self.row_active[i] = false;
+                    self.ops.push(PresolveOp::DuplicateRow(i));
+                    changed = true;
+                },
+                None => {
+                    keys.insert(key, (i, scale));
+                },
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Merges continuous variables with identical columns and objective coefficients.
+    fn merge_duplicate_cols(&mut self) -> bool {
+        let mut changed = false;
+        let mut keys: HashMap<(Vec<(usize, u64)>, u64), usize> = HashMap::new();
+        for k in 0..self.c.len() {
+            if !self.col_active[k] || self.p[k] || self.cols[k].is_empty() {
+                continue;
+            }
+            let mut entries: Vec<(usize, u64)> = self.cols[k].iter().map(|(i, a)| (*i, a.to_bits())).collect();
+            entries.sort_by_key(|(i, _)| *i);
+            let key = (entries, self.c[k].to_bits());
+            match keys.get(&key) {
+                Some(j) => {
+                    let j = *j;
+                    self.ops.push(PresolveOp::MergedCols(j, k, (self.l[j], self.u[j]), (self.l[k], self.u[k])));
+                    self.l[j] += self.l[k];
+                    self.u[j] += self.u[k];
+                    if let Some(x0) = self.x0.as_mut() {
+                        x0[j] += x0[k];
+                    }
+                    for (i, _) in self.cols[k].drain() {
+                        self.rows[i].remove(&k);
+                    }
+                    self.col_active[k] = false;
+                    changed = true;
+                },
+                None => {
+                    keys.insert(key, k);
+                },
+            }
+        }
+        changed
+    }
+
+    /// Fixes variables whose reduced costs have a strict sign for all duals that are
+    /// feasible for the limits of the continuous singleton columns. For problems with
+    /// integer variables, only empty columns are fixed.
+    fn fix_dominated_cols(&mut self) -> bool {
+
+        // Dual bounds from singleton columns
+        let mut lam: Vec<(f64, f64)> = vec![(-f64::INFINITY, f64::INFINITY); self.b.len()];
+        if !self.p.iter().any(|p| *p) {
+            for s in 0..self.c.len() {
+                if !self.col_active[s] || self.cols[s].len() != 1 {
+                    continue;
+                }
+                let (i, a) = self.cols[s].iter().map(|(i, a)| (*i, *a)).next().unwrap();
+                let t = self.c[s]/a;
+                // c_s - a*lam_i >= 0 without upper limit, <= 0 without lower limit
+                if self.u[s] >= INF {
+                    if a > 0. { lam[i].1 = lam[i].1.min(t) } else { lam[i].0 = lam[i].0.max(t) }
+                }
+                if self.l[s] <= -INF {
+                    if a > 0. { lam[i].0 = lam[i].0.max(t) } else { lam[i].1 = lam[i].1.min(t) }
+                }
+            }
+        }
+
+        // Reduced cost ranges
+        let mut changed = false;
+        for j in 0..self.c.len() {
+            if !self.col_active[j] {
+                continue;
+            }
+            let mut d = (self.c[j], self.c[j]);
+            for (i, a) in self.cols[j].iter() {
+                let r = if *a > 0. { (a*lam[*i].0, a*lam[*i].1) } else { (a*lam[*i].1, a*lam[*i].0) };
+                d = (d.0 - r.1, d.1 - r.0);
+            }
+            if d.0.is_nan() || d.1.is_nan() || d.0 > d.1 {
+                continue;
+            }
+            if d.0 > TOL && self.l[j] > -INF {
+                self.fix_col(j, self.l[j]);
+                changed = true;
+            }
+            else if d.1 < -TOL && self.u[j] < INF {
+                self.fix_col(j, self.u[j]);
+                changed = true;
+            }
+            else if self.cols[j].is_empty() && d.0 == 0. && d.1 == 0. {
+                self.fix_col(j, self.l[j].max(self.u[j].min(0.)));
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Gets presolved problem and postsolve data, given the original matrix
+    /// of linear equality constraints.
+    fn reduced(&self, a: &CooMat<f64>) -> (ProblemMilp, ProblemPostsolve) {
+
+        let kept_rows: Vec<usize> = (0..self.b.len()).filter(|i| self.row_active[*i]).collect();
+        let kept_cols: Vec<usize> = (0..self.c.len()).filter(|j| self.col_active[*j]).collect();
+        let row_index: HashMap<usize, usize> = kept_rows.iter().enumerate().map(|(k, i)| (*i, k)).collect();
+
+        let mut row_inds: Vec<usize> = Vec::new();
+        let mut col_inds: Vec<usize> = Vec::new();
+        let mut data: Vec<f64> = Vec::new();
+        for (k, j) in kept_cols.iter().enumerate() {
+            let mut entries: Vec<(usize, f64)> = self.cols[*j].iter().map(|(i, a)| (*i, *a)).collect();
+            entries.sort_by_key(|(i, _)| *i);
+            for (i, val) in entries.into_iter() {
+                row_inds.push(row_index[&i]);
+                col_inds.push(k);
+                data.push(val);
+            }
+        }
+
+        let mut cols: Vec<Vec<(usize, f64)>> = vec![Vec::new(); self.c.len()];
+        for (i, j, val) in a.iter() {
+            cols[*j].push((*i, *val));
+        }
+
+        let postsolve = ProblemPostsolve {
+            c: self.c.clone(),
+            cols: cols,
+            na: self.b.len(),
+            kept_rows: kept_rows.clone(),
+            kept_cols: kept_cols.clone(),
+            ops: self.ops.clone(),
+            offset: self.offset,
+        };
+
+        let problem = ProblemMilp::new(kept_cols.iter().map(|j| self.c[*j]).collect(),
+                                       CooMat::new((kept_rows.len(), kept_cols.len()), row_inds, col_inds, data),
+                                       kept_rows.iter().map(|i| self.b[*i]).collect(),
+                                       kept_cols.iter().map(|j| self.l[*j]).collect(),
+                                       kept_cols.iter().map(|j| self.u[*j]).collect(),
+                                       kept_cols.iter().map(|j| self.p[*j]).collect(),
+                                       self.x0.as_ref().map(|x0| kept_cols.iter().map(|j| x0[*j]).collect()));
+
+        (problem, postsolve)
+    }
+}
+
+impl ProblemPresolve for ProblemMilp {
+
+    fn presolve(&self) -> Result<(Self, ProblemPostsolve), SimpleError> {
+        let mut data = PresolveData::new(self.c(), self.a(), self.b(), self.l(), self.u(), self.p(), self.x0());
+        data.run()?;
+        Ok(data.reduced(self.a()))
+    }
+}
+
+impl ProblemPresolve for ProblemLp {
+
+    fn presolve(&self) -> Result<(Self, ProblemPostsolve), SimpleError> {
+        let p = vec![false; self.nx()];
+        let mut data = PresolveData::new(self.c(), self.a(), self.b(), self.l(), self.u(), &p, self.x0());
+        data.run()?;
+        let (p, postsolve) = data.reduced(self.a());
+        Ok((ProblemLp::new(p.c().to_vec(),
+                           p.a().clone(),
+                           p.b().to_vec(),
+                           p.l().to_vec(),
+                           p.u().to_vec(),
+                           p.x0().map(|x| x.to_vec())), postsolve))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::assert_vec_approx_eq;
+    use crate::problem::base::Problem;
+    use crate::model::node_cmp::NodeCmp;
+    use crate::model::variable::VariableScalar;
+    use crate::model::model::{Model, Objective};
+    use crate::model::model_std::ModelStd;
+
+    // Checks primal feasibility and stationarity c - a^T*lam + mu - pi = 0
+    fn check_solution(c: &[f64], a: &CooMat<f64>, b: &[f64], l: &[f64], u: &[f64], s: &ProblemSol) {
+        let mut ax = vec![0.; b.len()];
+        let mut r = c.to_vec();
+        for (i, j, val) in a.iter() {
+            ax[*i] += val*s.x[*j];
+            r[*j] -= val*s.lam[*i];
+        }
+        assert_vec_approx_eq!(ax, b, epsilon = 1e-10);
+        for j in 0..c.len() {
+            assert!(l[j] - 1e-10 <= s.x[j] && s.x[j] <= u[j] + 1e-10);
+            assert!(s.mu[j] >= 0. && s.pi[j] >= 0.);
+            assert!(s.mu[j] == 0. || (s.x[j] - u[j]).abs() < 1e-10);
+            assert!(s.pi[j] == 0. || (s.x[j] - l[j]).abs() < 1e-10);
+            r[j] += s.mu[j] - s.pi[j];
+        }
+        assert_vec_approx_eq!(r, vec![0.; c.len()], epsilon = 1e-10);
+    }
+
+    #[test]
+    fn presolve_lp_reductions() {
+
+        // x0 + x1 + x2 = 4, duplicate row, 3*x3 = 6, x1 + x4 = 3, empty row,
+        // fixed x4, empty column x5
+        let c = vec![1., 2., 0., 1., 5., -1.];
+        let a = CooMat::new((5, 6),
+                            vec![0, 0, 0, 1, 1, 1, 2, 3, 3],
+                            vec![0, 1, 2, 0, 1, 2, 3, 1, 4],
+                            vec![1., 1., 1., 2., 2., 2., 3., 1., 1.]);
+        let b = vec![4., 8., 6., 3., 0.];
+        let l = vec![0., 0., 0., 0., 1., 0.];
+        let u = vec![10., 10., f64::INFINITY, 5., 1., 7.];
+        let p = ProblemLp::new(c.clone(), a.clone(), b.clone(), l.clone(), u.clone(), None);
+
+        let (pp, post) = p.presolve().unwrap();
+        assert_eq!(pp.nx(), 0);
+        assert_eq!(pp.na(), 0);
+        assert_eq!(post.removed_rows(), 5);
+        assert_eq!(post.removed_cols(), 6);
+        assert_eq!(post.offset(), 4.);
+
+        let s = post.postsolve(&ProblemSol::new(0, 0, 0));
+        assert_vec_approx_eq!(s.x, vec![0., 2., 2., 2., 1., 7.], epsilon = 1e-12);
+        assert_vec_approx_eq!(s.lam, vec![0., 0., 1./3., 2., 0.], epsilon = 1e-12);
+        assert_vec_approx_eq!(s.pi, vec![1., 0., 0., 0., 3., 0.], epsilon = 1e-12);
+        assert_vec_approx_eq!(s.mu, vec![0., 0., 0., 0., 0., 1.], epsilon = 1e-12);
+        check_solution(&c, &a, &b, &l, &u, &s);
+    }
+
+    #[test]
+    fn presolve_lp_duplicate_cols() {
+
+        // x0 + x1 + x2 = 5, x0 + x1 - x2 = 1 with duplicate columns x0 and x1
+        let c = vec![1., 1., 2.];
+        let a = CooMat::new((2, 3),
+                            vec![0, 0, 0, 1, 1, 1],
+                            vec![0, 1, 2, 0, 1, 2],
+                            vec![1., 1., 1., 1., 1., -1.]);
+        let b = vec![5., 1.];
+        let l = vec![0., 0., 0.];
+        let u = vec![1., 2., 10.];
+        let p = ProblemLp::new(c.clone(), a.clone(), b.clone(), l.clone(), u.clone(), Some(vec![1., 1., 1.]));
+
+        let (pp, post) = p.presolve().unwrap();
+        assert_eq!(pp.nx(), 2);
+        assert_eq!(pp.na(), 2);
+        assert_eq!(pp.c(), &[1., 2.]);
+        assert_eq!(pp.l(), &[0., 0.]);
+        assert_eq!(pp.u(), &[3., 10.]);
+        assert_eq!(pp.x0().unwrap(), &[2., 1.]);
+        assert_eq!(post.removed_cols(), 1);
+        assert_eq!(post.offset(), 0.);
+
+        // Merged variable at upper limit
+        let mut sol = ProblemSol::new(2, 2, 0);
+        sol.x = vec![3., 2.];
+        sol.lam = vec![2., 0.];
+        sol.mu = vec![1., 0.];
+        let s = post.postsolve(&sol);
+        assert_vec_approx_eq!(s.x, vec![1., 2., 2.], epsilon = 1e-12);
+        assert_vec_approx_eq!(s.mu, vec![1., 1., 0.], epsilon = 1e-12);
+        check_solution(&c, &a, &b, &l, &u, &s);
+    }
+
+    #[test]
+    fn presolve_milp() {
+
+        // Integer bounds, singleton row and empty column
+        let c = vec![1., 1., 3., 1.];
+        let a = CooMat::new((2, 4),
+                            vec![0, 0, 0, 1],
+                            vec![0, 1, 3, 1],
+                            vec![1., 1., 1., 2.]);
+        let l = vec![0.5, -10., 0.2, 0.];
+        let u = vec![3.7, 10., 4.5, 10.];
+        let p = vec![true, true, true, false];
+        let prob = ProblemMilp::new(c.clone(), a.clone(), vec![3.5, 4.], l.clone(), u.clone(),
+                                    p.clone(), None);
+        let (pp, post) = prob.presolve().unwrap();
+        assert_eq!(pp.nx(), 2);
+        assert_eq!(pp.na(), 1);
+        assert_eq!(pp.l(), &[1., 0.]);
+        assert_eq!(pp.u(), &[3., 10.]);
+        assert_eq!(pp.p(), &[true, false]);
+        assert_eq!(pp.b(), &[1.5]);
+        assert_eq!(post.offset(), 5.);
+        let mut sol = ProblemSol::new(2, 1, 0);
+        sol.x = vec![1., 0.5];
+        assert_vec_approx_eq!(post.postsolve(&sol).x, vec![1., 2., 1., 0.5], epsilon = 1e-12);
+
+        // Non-integer value fixed by singleton row
+        let prob = ProblemMilp::new(c.clone(), a.clone(), vec![3.5, 3.], l.clone(), u.clone(),
+                                    p.clone(), None);
+        assert_eq!(format!("{}", prob.presolve().err().unwrap()), "infeasible singleton constraint 1");
+
+        // Infeasible empty row and inconsistent duplicate rows
+        let a = CooMat::new((3, 2),
+                            vec![0, 0, 1, 1],
+                            vec![0, 1, 0, 1],
+                            vec![1., 1., -2., -2.]);
+        let prob = ProblemMilp::new(vec![1., 1.], a.clone(), vec![3., -6., 1.], vec![0., 0.], vec![5., 5.],
+                                    vec![false; 2], None);
+        assert_eq!(format!("{}", prob.presolve().err().unwrap()), "infeasible empty constraint 2");
+        let prob = ProblemMilp::new(vec![1., 1.], a.clone(), vec![3., -5., 0.], vec![0., 0.], vec![5., 5.],
+                                    vec![false; 2], None);
+        assert_eq!(format!("{}", prob.presolve().err().unwrap()),
+                   "inconsistent duplicate constraints 0 and 1");
+    }
+
+    #[test]
+    fn presolve_lp_model_free_cols() {
+
+        // Free variable z is a singleton column with zero cost, so the dual of
+        // x + z = 4 is zero and x is fixed at its lower limit
+        let x = VariableScalar::new_continuous("x");
+        let y = VariableScalar::new_continuous("y");
+        let z = VariableScalar::new_continuous("z");
+
+        let mut m = Model::new();
+        m.set_objective(Objective::minimize(&(&x + &y)));
+        m.add_constraint(&(&x + &z).equal(4.));
+        m.add_constraint(&(&x).geq(0.));
+        m.add_constraint(&(&y).geq(1.));
+
+        let std_prob = m.std_problem();
+        let p = match &std_prob.prob {
+            Problem::Lp(p) => p,
+            _ => panic!("invalid problem type"),
+        };
+        assert_eq!(p.u()[std_prob.var2index[&z]], 1e8);
+        assert_eq!(p.l()[std_prob.var2index[&z]], -1e8);
+
+        let (pp, post) = p.presolve().unwrap();
+        assert_eq!(pp.nx(), 0);
+        assert_eq!(pp.na(), 0);
+        assert_eq!(post.offset(), 1.);
+
+        let s = post.postsolve(&ProblemSol::new(0, 0, 0));
+        assert_eq!(s.x[std_prob.var2index[&x]], 0.);
+        assert_eq!(s.x[std_prob.var2index[&y]], 1.);
+        assert_eq!(s.x[std_prob.var2index[&z]], 4.);
+        check_solution(p.c(), p.a(), p.b(), p.l(), p.u(), &s);
+    }
+}
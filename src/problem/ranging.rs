@@ -0,0 +1,405 @@
+//! Sensitivity ranging of linear optimization problem solutions.
+//!
+//! Ranging is computed from the basis of a solution. Basic linear equality
+//! constraints are represented by artificial columns that are fixed at zero.
+//! For a basic variable, the range of its objective function coefficient is
+//! the one over which the reduced costs of the nonbasic variables keep their
+//! signs, and for a linear equality constraint, the range of its right-hand-side
+//! value is the one over which the basic variables stay within their limits.
+//! The basis matrix is factorized in dense form, which takes time cubic and memory
+//! quadratic in the number of constraints, so ranging is only computed for problems
+//! with at most [RANGING_MAX_ROWS](constant.RANGING_MAX_ROWS.html) linear equality
+//! constraints. For larger problems, ranging provided by the solver, e.g., by
+//! [SolverClpCmd](../../solver/clp_cmd/struct.SolverClpCmd.html), should be used.
+
+use simple_error::SimpleError;
+
+use crate::problem::lp::ProblemLp;
+use crate::problem::base::{BasisStatus, ProblemSol, ProblemSolRanging};
+
+/// Tolerance for pivots of basis factorization and for entries of
+/// basis inverse rows and columns.
+const PIVOT_TOL: f64 = 1e-12;
+
+/// Maximum number of linear equality constraints of problems whose ranging is computed.
+pub const RANGING_MAX_ROWS: usize = 2000;
+
+/// A trait for sensitivity ranging of linear optimization problem solutions.
+pub trait ProblemLpRanging {
+
+    /// Computes the ranges of the objective function gradient entries and of the
+    /// right-hand-side values of linear equality constraints over which the basis
+    /// of the given solution remains optimal. The solution needs to have a basis,
+    /// and the primal values of its nonbasic variables are taken as they are.
+    /// It fails for problems with more than [RANGING_MAX_ROWS](constant.RANGING_MAX_ROWS.html)
+    /// linear equality constraints.
+    fn ranging(&self, solution: &ProblemSol) -> Result<ProblemSolRanging, SimpleError>;
+}
+
+/// Column of a basis matrix.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BasisCol {
+    Var(usize),
+    Row(usize),
+}
+
+/// Limit at which a nonbasic variable is held.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum NonbasicSide {
+    Lower,
+    Upper,
+    Fixed,
+    Free,
+}
+
+/// Dense LU factorization with partial pivoting.
+struct DenseLu {
+    n: usize,
+    lu: Vec<f64>,
+    perm: Vec<usize>,
+}
+
+impl ProblemLpRanging for ProblemLp {
+
+    fn ranging(&self, solution: &ProblemSol) -> Result<ProblemSolRanging, SimpleError> {
+
+        let nx = self.nx();
+        let na = self.na();
+        let basis = match &solution.basis {
+            Some(b) => b,
+            None => return Err(SimpleError::new("solution has no basis")),
+        };
+        if basis.x.len() != nx || basis.a.len() != na || solution.x.len() != nx {
+            return Err(SimpleError::new("invalid solution dimensions"));
+        }
+        if na > RANGING_MAX_ROWS {
+            return Err(SimpleError::new("too many constraints for dense basis factorization"));
+        }
+
+        // Columns
+        let mut cols: Vec<Vec<(usize, f64)>> = vec![Vec::new();nx];
+        for (i, j, v) in self.a().iter() {
+            cols[*j].push((*i, *v));
+        }
+
+        // Basis
+        let basic: Vec<BasisCol> = (0..nx).filter(|j| basis.x[*j] == BasisStatus::Basic)
+                                          .map(BasisCol::Var)
+                                          .chain((0..na).filter(|i| basis.a[*i] == BasisStatus::Basic)
+                                                        .map(BasisCol::Row))
+                                          .collect();
+        if basic.len() != na {
+            return Err(SimpleError::new("invalid number of basic variables and constraints"));
+        }
+        let mut bmat = vec![0.;na*na];
+        for (p, bc) in basic.iter().enumerate() {
+            match bc {
+                BasisCol::Var(j) => {
+                    for (i, v) in cols[*j].iter() {
+                        bmat[*i*na + p] += v;
+                    }
+                },
+                BasisCol::Row(i) => bmat[*i*na + p] = 1.,
+            }
+        }
+        let lu = match DenseLu::new(na, bmat) {
+            Some(lu) => lu,
+            None => return Err(SimpleError::new("singular basis")),
+        };
+
+        // Nonbasic variables
+        let (c, l, u, x) = (self.c(), self.l(), self.u(), &solution.x);
+        let side = |j: usize| -> NonbasicSide {
+            if l[j] == u[j] {
+                return NonbasicSide::Fixed;
+            }
+            match basis.x[j] {
+                BasisStatus::AtLower => NonbasicSide::Lower,
+                BasisStatus::AtUpper => NonbasicSide::Upper,
+                BasisStatus::Free => NonbasicSide::Free,
+                _ if (x[j] - l[j]).abs() <= (x[j] - u[j]).abs() => NonbasicSide::Lower,
+                _ => NonbasicSide::Upper,
+            }
+        };
+        let nonbasic: Vec<(usize, NonbasicSide)> = (0..nx).filter(|j| basis.x[*j] != BasisStatus::Basic)
+                                                          .map(|j| (j, side(j)))
+                                                          .filter(|(_j, s)| *s != NonbasicSide::Fixed)
+                                                          .collect();
+
+        // Duals and reduced costs
+        let cb: Vec<f64> = basic.iter().map(|bc| match bc {
+            BasisCol::Var(j) => c[*j],
+            BasisCol::Row(_i) => 0.,
+        }).collect();
+        let y = lu.solve_transpose(&cb);
+        let dot = |v: &[f64], j: usize| -> f64 { cols[j].iter().map(|(i, a)| v[*i]*a).sum() };
+        let d: Vec<f64> = (0..nx).map(|j| c[j] - dot(&y, j)).collect();
+
+        // Basic variable values
+        let mut r = self.b().to_vec();
+        for j in (0..nx).filter(|j| basis.x[*j] != BasisStatus::Basic) {
+            for (i, v) in cols[j].iter() {
+                r[*i] -= v*x[j];
+            }
+        }
+        let xb = lu.solve(&r);
+
+        // Objective ranging
+        let mut rc: Vec<(f64, f64)> = Vec::with_capacity(nx);
+        for j in 0..nx {
+            rc.push(match side(j) {
+                NonbasicSide::Fixed => (-f64::INFINITY, f64::INFINITY),
+                NonbasicSide::Lower => (c[j] - d[j].max(0.), f64::INFINITY),
+                NonbasicSide::Upper => (-f64::INFINITY, c[j] - d[j].min(0.)),
+                NonbasicSide::Free => (c[j] - d[j], c[j] - d[j]),
+            });
+        }
+        for (p, bc) in basic.iter().enumerate() {
+            let j = match bc {
+                BasisCol::Var(j) => *j,
+                BasisCol::Row(_i) => continue,
+            };
+            let mut e = vec![0.;na];
+            e[p] = 1.;
+            let rho = lu.solve_transpose(&e);
+            let (mut lo, mut hi) = (-f64::INFINITY, f64::INFINITY);
+            for (k, s) in nonbasic.iter() {
+                let alpha = dot(&rho, *k);
+                if alpha.abs() <= PIVOT_TOL {
+                    continue;
+                }
+
+                // Reduced cost d_k - delta*alpha must keep its sign
+                let (dk, keep_nonneg) = match s {
+                    NonbasicSide::Lower => (d[*k].max(0.), true),
+                    NonbasicSide::Upper => (d[*k].min(0.), false),
+                    _ => {
+                        lo = lo.max(0.);
+                        hi = hi.min(0.);
+                        continue;
+                    }
+                };
+                let t = dk/alpha;
+                if keep_nonneg == (alpha > 0.) {
+                    hi = hi.min(t);
+                }
+                else {
+                    lo = lo.max(t);
+                }
+            }
+            rc[j] = (c[j] + lo, c[j] + hi);
+        }
+
+        // Right-hand-side ranging
+        let mut rb: Vec<(f64, f64)> = Vec::with_capacity(na);
+        for (i, b) in self.b().iter().enumerate() {
+            let mut e = vec![0.;na];
+            e[i] = 1.;
+            let beta = lu.solve(&e);
+            let (mut lo, mut hi) = (-f64::INFINITY, f64::INFINITY);
+            for (p, bc) in basic.iter().enumerate() {
+                if beta[p].abs() <= PIVOT_TOL {
+                    continue;
+                }
+                let (lj, uj) = match bc {
+                    BasisCol::Var(j) => (l[*j], u[*j]),
+                    BasisCol::Row(_i) => (0., 0.),
+                };
+
+                // Basic value xb + delta*beta must stay within limits
+                let down = (lj - xb[p]).min(0.)/beta[p];
+                let up = (uj - xb[p]).max(0.)/beta[p];
+                if beta[p] > 0. {
+                    lo = lo.max(down);
+                    hi = hi.min(up);
+                }
+                else {
+                    lo = lo.max(up);
+                    hi = hi.min(down);
+                }
+            }
+            rb.push((b + lo, b + hi));
+        }
+
+        Ok(ProblemSolRanging {
+            c: rc,
+            b: rb,
+        })
+    }
+}
+
+impl DenseLu {
+
+    /// Factorizes square matrix given in row-major order, if it is nonsingular.
+    fn new(n: usize, mut lu: Vec<f64>) -> Option<Self> {
+        let mut perm: Vec<usize> = (0..n).collect();
+        for k in 0..n {
+            let p = (k..n).max_by(|a, b| lu[*a*n + k].abs().partial_cmp(&lu[*b*n + k].abs()).unwrap())?;
+            if lu[p*n + k].abs() <= PIVOT_TOL {
+                return None;
+            }
+            if p != k {
+                for j in 0..n {
+                    lu.swap(p*n + j, k*n + j);
+                }
+                perm.swap(p, k);
+            }
+            for i in k+1..n {
+                let m = lu[i*n + k]/lu[k*n + k];
+                lu[i*n + k] = m;
+                for j in k+1..n {
+                    lu[i*n + j] -= m*lu[k*n + j];
+                }
+            }
+        }
+        Some(Self {
+            n: n,
+            lu: lu,
+            perm: perm,
+        })
+    }
+
+    /// Solves system with factorized matrix.
+    fn solve(&self, b: &[f64]) -> Vec<f64> {
+        let n = self.n;
+        let mut x: Vec<f64> = self.perm.iter().map(|i| b[*i]).collect();
+        for i in 0..n {
+            for j in 0..i {
+                x[i] -= self.lu[i*n + j]*x[j];
+            }
+        }
+        for i in (0..n).rev() {
+            for j in i+1..n {
+                x[i] -= self.lu[i*n + j]*x[j];
+            }
+            x[i] /= self.lu[i*n + i];
+        }
+        x
+    }
+
+    /// Solves system with transpose of factorized matrix.
+    fn solve_transpose(&self, b: &[f64]) -> Vec<f64> {
+        let n = self.n;
+        let mut z = b.to_vec();
+        for i in 0..n {
+            for j in 0..i {
+                z[i] -= self.lu[j*n + i]*z[j];
+            }
+            z[i] /= self.lu[i*n + i];
+        }
+        for i in (0..n).rev() {
+            for j in i+1..n {
+                z[i] -= self.lu[j*n + i]*z[j];
+            }
+        }
+        let mut x = vec![0.;n];
+        for (k, i) in self.perm.iter().enumerate() {
+            x[*i] = z[k];
+        }
+        x
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use approx::assert_abs_diff_eq;
+
+    use super::*;
+    use crate::assert_vec_approx_eq;
+    use crate::matrix::coo::CooMat;
+    use crate::problem::base::ProblemSolBasis;
+
+    #[test]
+    fn dense_lu() {
+
+        let a = vec![0., 2., 1.,
+                     1., 1., 0.,
+                     3., 0., 4.];
+        let lu = DenseLu::new(3, a.clone()).unwrap();
+        let x = lu.solve(&[1., 2., 3.]);
+        for i in 0..3 {
+            let ax: f64 = (0..3).map(|j| a[i*3 + j]*x[j]).sum();
+            assert_abs_diff_eq!(ax, (i + 1) as f64, epsilon = 1e-12);
+        }
+        let x = lu.solve_transpose(&[1., 2., 3.]);
+        for j in 0..3 {
+            let atx: f64 = (0..3).map(|i| a[i*3 + j]*x[i]).sum();
+            assert_abs_diff_eq!(atx, (j + 1) as f64, epsilon = 1e-12);
+        }
+        assert!(DenseLu::new(2, vec![1., 2., 2., 4.]).is_none());
+    }
+
+    #[test]
+    fn lp_ranging() {
+
+        // Problem of clp_solve_lp test
+        // min        180*x0 + 160*x1
+        // subject to 6*x0 +   x1 + x2 == 12
+        //            3*x0 +   x1 + x3 ==  8
+        //            4*x0 + 6*x1 + x4 == 24
+        //            0 <= x0 <= 5
+        //            0 <= x1 <= 5
+        //            x2 <= 0
+        //            x3 <= 0
+        //            x4 <= 0
+        let p = ProblemLp::new(
+            vec![180.,160., 0., 0., 0.],
+            CooMat::new(
+                (3, 5),
+                vec![0,0,0,1,1,1,2,2,2],
+                vec![0,1,2,0,1,3,0,1,4],
+                vec![6.,1.,1.,3.,1.,1.,4.,6.,1.]),
+            vec![12.,8.,24.],
+            vec![0.,0.,-1e8,-1e8,-1e8],
+            vec![5.,5.,0.,0.,0.],
+            None,
+        );
+
+        let mut sol = ProblemSol::new(5, 3, 0);
+        sol.x = vec![12./7., 20./7., -8./7., 0., 0.];
+        assert!(p.ranging(&sol).is_err());
+
+        sol.basis = Some(ProblemSolBasis {
+            x: vec![BasisStatus::Basic,
+                    BasisStatus::Basic,
+                    BasisStatus::Basic,
+                    BasisStatus::AtUpper,
+                    BasisStatus::AtUpper],
+            a: vec![BasisStatus::AtLower;3],
+        });
+        let r = p.ranging(&sol).unwrap();
+
+        // Duals y = (c2, (6*c0 - 4*c1 - 32*c2)/14, (3*c1 - c0 + 3*c2)/14) and
+        // reduced costs -y1 and -y2 of x3 and x4 at upper limits
+        assert_vec_approx_eq!(vec![r.c[0].0, r.c[0].1], vec![320./3., 480.], epsilon = 1e-9);
+        assert_vec_approx_eq!(vec![r.c[1].0, r.c[1].1], vec![60., 270.], epsilon = 1e-9);
+        assert_vec_approx_eq!(vec![r.c[2].0, r.c[2].1], vec![-100., 13.75], epsilon = 1e-9);
+        assert_eq!(r.c[3].0, -f64::INFINITY);
+        assert_abs_diff_eq!(r.c[3].1, 220./7., epsilon = 1e-9);
+        assert_eq!(r.c[4].0, -f64::INFINITY);
+        assert_abs_diff_eq!(r.c[4].1, 150./7., epsilon = 1e-9);
+
+        // Basic variables x0, x1 and x2 = b0 - 6*x0 - x1 within limits
+        assert_abs_diff_eq!(r.b[0].0, 12. - (1e8 - 8./7.), epsilon = 1e-6);
+        assert_abs_diff_eq!(r.b[0].1, 12. + 8./7., epsilon = 1e-9);
+        assert_vec_approx_eq!(vec![r.b[1].0, r.b[1].1], vec![7.5, 47./3.], epsilon = 1e-9);
+        assert_vec_approx_eq!(vec![r.b[2].0, r.b[2].1], vec![32./3., 88./3.], epsilon = 1e-9);
+
+        // Too many constraints
+        let na = RANGING_MAX_ROWS + 1;
+        let p = ProblemLp::new(vec![1.],
+                               CooMat::from_nnz((na, 1), 0),
+                               vec![0.;na],
+                               vec![0.],
+                               vec![1.],
+                               None);
+        let mut sol = ProblemSol::new(1, na, 0);
+        sol.basis = Some(ProblemSolBasis {
+            x: vec![BasisStatus::AtLower],
+            a: vec![BasisStatus::Basic;na],
+        });
+        assert_eq!(format!("{}", p.ranging(&sol).err().unwrap()),
+                   "too many constraints for dense basis factorization");
+    }
+}
@@ -0,0 +1,617 @@
+//! Scaling of optimization problems.
+//!
+//! Scale factors are computed for the variables (columns) and for the linear and
+//! nonlinear equality constraints (rows) from the matrix formed by stacking the
+//! Jacobian a of the linear equality constraints and the Jacobian j of the nonlinear
+//! equality constraints, evaluated at the initial point. Geometric-mean scaling
+//! passes are applied until they stop reducing the spread of the matrix entries,
+//! followed by an equilibration pass that brings the largest entry of each row and
+//! column close to one. The objective function is scaled down, if needed, so that
+//! the entries of its scaled gradient at the initial point are at most one. All
+//! factors are powers of two, and variables constrained to be integers are not scaled.
+//!
+//! With column factors dc, row factors r and objective factor s, the scaled
+//! problem has variables x/dc, objective function s*phi, linear equality constraints
+//! r*(a*x - b) = 0 and nonlinear equality constraints r*f(x) = 0. Its solutions are
+//! mapped back with x = dc*x', lam = r*lam'/s, nu = r*nu'/s, mu = mu'/(s*dc) and
+//! pi = pi'/(s*dc).
+
+use std::mem;
+use std::sync::Mutex;
+
+use crate::matrix::coo::CooMat;
+use crate::problem::lp::ProblemLp;
+use crate::problem::nlp::ProblemNlp;
+use crate::problem::milp::ProblemMilp;
+use crate::problem::minlp::ProblemMinlp;
+use crate::problem::base::{Problem, ProblemEval, ProblemSol, ProblemSolRanging};
+
+#[cfg(not(feature = "sync"))]
+use std::rc::Rc as ProblemRc;
+
+#[cfg(feature = "sync")]
+use std::sync::Arc as ProblemRc;
+
+/// Maximum number of geometric-mean scaling passes.
+const MAX_PASSES: usize = 20;
+
+/// Relative reduction of the spread of matrix entries required to
+/// continue with geometric-mean scaling passes.
+const PASS_IMPROVEMENT: f64 = 0.9;
+
+/// Row, column and objective scale factors of an optimization problem.
+#[derive(Debug, Clone)]
+pub struct ProblemScaling {
+    col: Vec<f64>,
+    row_a: Vec<f64>,
+    row_f: Vec<f64>,
+    obj: f64,
+}
+
+/// Scaled optimization problem, which holds the original problem.
+pub struct ProblemScaled {
+    problem: Problem,
+    original: ProblemRc<Mutex<Problem>>,
+}
+
+impl ProblemScaling {
+
+    /// Computes scale factors of an optimization problem. The problem functions
+    /// are evaluated at the initial point, or at the point closest to zero
+    /// within the variable limits if there is no initial point.
+    pub fn new(problem: &mut Problem) -> Self {
+
+        let p = base_mut(problem);
+        let nx = p.nx();
+        let na = p.na();
+        let nf = p.nf();
+
+        let x: Vec<f64> = match p.x0() {
+            Some(x0) => x0.to_vec(),
+            None => p.l().iter().zip(p.u().iter()).map(|(l, u)| 0_f64.max(*l).min(*u)).collect(),
+        };
+        p.evaluate(&x);
+
+        let mut entries: Vec<(usize, usize, f64)> = Vec::with_capacity(p.a().nnz() + p.j().nnz());
+        for (i, j, v) in p.a().iter() {
+            entries.push((*i, *j, v.abs()));
+        }
+        for (i, j, v) in p.j().iter() {
+            entries.push((na + *i, *j, v.abs()));
+        }
+        entries.retain(|e| e.2 > 0. && e.2.is_finite());
+
+        let (row, col) = scale_matrix(na + nf, nx, &entries, p.p());
+
+        let gmax = p.gphi().iter()
+                           .zip(col.iter())
+                           .map(|(g, d)| (g*d).abs())
+                           .filter(|g| g.is_finite())
+                           .fold(0., f64::max);
+        let obj = if gmax > 1. { power_of_two(1./gmax) } else { 1. };
+
+        Self {
+            col: col,
+            row_a: row[..na].to_vec(),
+            row_f: row[na..].to_vec(),
+            obj: obj,
+        }
+    }
+
+    /// Creates scale factors that leave a problem of given dimensions unchanged.
+    pub fn identity(nx: usize, na: usize, nf: usize) -> Self {
+        Self {
+            col: vec![1.;nx],
+            row_a: vec![1.;na],
+            row_f: vec![1.;nf],
+            obj: 1.,
+        }
+    }
+
+    /// Scale factors of optimization variables.
+    pub fn col(&self) -> &[f64] { &self.col }
+
+    /// Scale factors of linear equality constraints.
+    pub fn row_a(&self) -> &[f64] { &self.row_a }
+
+    /// Scale factors of nonlinear equality constraints.
+    pub fn row_f(&self) -> &[f64] { &self.row_f }
+
+    /// Scale factor of objective function.
+    pub fn obj(&self) -> f64 { self.obj }
+
+    /// Obtains scaled problem. Linear problems are scaled directly, while the
+    /// evaluation function of nonlinear problems is wrapped so that evaluations
+    /// take place in the scaled space. The original problem can be recovered
+    /// from the scaled problem.
+    pub fn scale(&self, problem: Problem) -> ProblemScaled {
+
+        let (a, b, l, u, x0) = {
+            let p = base(&problem);
+            assert_eq!(p.nx(), self.col.len());
+            assert_eq!(p.na(), self.row_a.len());
+            assert_eq!(p.nf(), self.row_f.len());
+            (scale_mat(p.a(), &self.row_a, &self.col, 1.),
+             p.b().iter().zip(self.row_a.iter()).map(|(b, r)| b*r).collect::<Vec<f64>>(),
+             p.l().iter().zip(self.col.iter()).map(|(l, d)| l/d).collect::<Vec<f64>>(),
+             p.u().iter().zip(self.col.iter()).map(|(u, d)| u/d).collect::<Vec<f64>>(),
+             p.x0().map(|x0| {
+                 x0.iter().zip(self.col.iter()).map(|(x, d)| x/d).collect::<Vec<f64>>()
+             }))
+        };
+
+        // Linear problems
+        let linear = match &problem {
+            Problem::Lp(x) => Some((self.scale_c(x.c()), None)),
+            Problem::Milp(x) => Some((self.scale_c(x.c()), Some(x.p().to_vec()))),
+            _ => None,
+        };
+        if let Some((c, p)) = linear {
+            let scaled = match p {
+                Some(p) => Problem::Milp(ProblemMilp::new(c, a, b, l, u, p, x0)),
+                None => Problem::Lp(ProblemLp::new(c, a, b, l, u, x0)),
+            };
+            return ProblemScaled {
+                problem: scaled,
+                original: ProblemRc::new(Mutex::new(problem)),
+            };
+        }
+
+        // Nonlinear problems
+        let (hphi, j, h, p) = {
+            let p = base(&problem);
+            (p.hphi().clone(), p.j().clone(), p.h().clone(), p.p().to_vec())
+        };
+        let is_nlp = matches!(problem, Problem::Nlp(_));
+        let original = ProblemRc::new(Mutex::new(problem));
+        let orig = original.clone();
+        let col = self.col.clone();
+        let row_f = self.row_f.clone();
+        let obj = self.obj;
+        let eval_fn: ProblemEval = Box::new(move | phi: &mut f64,
+                                                   gphi: &mut Vec<f64>,
+                                                   hphi: &mut CooMat<f64>,
+                                                   f: &mut Vec<f64>,
+                                                   j: &mut CooMat<f64>,
+                                                   h: &mut Vec<CooMat<f64>>,
+                                                   x: &[f64] | {
+            let xx: Vec<f64> = x.iter().zip(col.iter()).map(|(x, d)| x*d).collect();
+            let mut guard = orig.lock().unwrap();
+            let p = base_mut(&mut guard);
+            p.evaluate(&xx);
+            *phi = obj*p.phi();
+            for (g, (gg, d)) in gphi.iter_mut().zip(p.gphi().iter().zip(col.iter())) {
+                *g = obj*gg*d;
+            }
+            scale_values(hphi, p.hphi(), &col, &col, obj);
+            for (ff, (fo, r)) in f.iter_mut().zip(p.f().iter().zip(row_f.iter())) {
+                *ff = fo*r;
+            }
+            scale_values(j, p.j(), &row_f, &col, 1.);
+            for (k, (hh, ho)) in h.iter_mut().zip(p.h().iter()).enumerate() {
+                scale_values(hh, ho, &col, &col, row_f[k]);
+            }
+        });
+
+        let scaled = if is_nlp {
+            Problem::Nlp(ProblemNlp::new(hphi, a, b, j, h, l, u, x0, eval_fn))
+        }
+        else {
+            Problem::Minlp(ProblemMinlp::new(hphi, a, b, j, h, l, u, p, x0, eval_fn))
+        };
+
+        ProblemScaled {
+            problem: scaled,
+            original: original,
+        }
+    }
+
+    /// Obtains solution of the original problem from a solution of the scaled problem.
+    pub fn unscale(&self, solution: &ProblemSol) -> ProblemSol {
+
+        let s = self.obj;
+        let col_dual = |v: &[f64]| -> Vec<f64> {
+            v.iter().zip(self.col.iter()).map(|(v, d)| v/(s*d)).collect()
+        };
+        let row_dual = |v: &[f64], r: &[f64]| -> Vec<f64> {
+            v.iter().zip(r.iter()).map(|(v, r)| v*r/s).collect()
+        };
+
+        ProblemSol {
+            x: solution.x.iter().zip(self.col.iter()).map(|(x, d)| x*d).collect(),
+            lam: row_dual(&solution.lam, &self.row_a),
+            nu: row_dual(&solution.nu, &self.row_f),
+            mu: col_dual(&solution.mu),
+            pi: col_dual(&solution.pi),
+            ranging: solution.ranging.as_ref().map(|r| {
+                ProblemSolRanging {
+                    c: r.c.iter().zip(self.col.iter()).map(|(c, d)| (c.0/(s*d), c.1/(s*d))).collect(),
+                    b: r.b.iter().zip(self.row_a.iter()).map(|(b, r)| (b.0/r, b.1/r)).collect(),
+                }
+            }),
+            basis: solution.basis.clone(),
+            log: solution.log.clone(),
+        }
+    }
+
+    /// Scales objective function gradient of linear problem.
+    fn scale_c(&self, c: &[f64]) -> Vec<f64> {
+        c.iter().zip(self.col.iter()).map(|(c, d)| self.obj*c*d).collect()
+    }
+}
+
+impl ProblemScaled {
+
+    /// Scaled problem.
+    pub fn problem(&self) -> &Problem { &self.problem }
+
+    /// Mutable reference of scaled problem, e.g., for passing it to a solver.
+    pub fn problem_mut(&mut self) -> &mut Problem { &mut self.problem }
+
+    /// Drops scaled problem and recovers the original problem.
+    pub fn into_original(self) -> Problem {
+        let original = self.original;
+        mem::drop(self.problem);
+        match ProblemRc::try_unwrap(original) {
+            Ok(m) => m.into_inner().unwrap_or_else(|e| e.into_inner()),
+            Err(_) => unreachable!("original problem is only shared with scaled problem"),
+        }
+    }
+}
+
+/// Gets base Minlp of problem.
+fn base(problem: &Problem) -> &ProblemMinlp {
+    match problem {
+        Problem::Lp(x) => x.as_minlp(),
+        Problem::Milp(x) => x.as_minlp(),
+        Problem::Nlp(x) => x.as_minlp(),
+        Problem::Minlp(x) => x,
+    }
+}
+
+/// Gets mutable base Minlp of problem.
+fn base_mut(problem: &mut Problem) -> &mut ProblemMinlp {
+    match problem {
+        Problem::Lp(x) => x.as_mut_minlp(),
+        Problem::Milp(x) => x.as_mut_minlp(),
+        Problem::Nlp(x) => x.as_mut_minlp(),
+        Problem::Minlp(x) => x,
+    }
+}
+
+/// Gets matrix with rows and columns scaled by the given factors, and all
+/// entries scaled by the given constant.
+fn scale_mat(m: &CooMat<f64>, row: &[f64], col: &[f64], s: f64) -> CooMat<f64> {
+    let mut scaled = m.clone();
+    scale_values(&mut scaled, m, row, col, s);
+    scaled
+}
+
+/// Sets values of matrix to those of a matrix with the same sparsity pattern,
+/// with rows and columns scaled by the given factors, and all entries scaled
+/// by the given constant.
+fn scale_values(dst: &mut CooMat<f64>, src: &CooMat<f64>, row: &[f64], col: &[f64], s: f64) {
+    for (v, (i, j, val)) in dst.data_mut().iter_mut().zip(src.iter()) {
+        *v = s*row[*i]*col[*j]*val;
+    }
+}
+
+/// Computes row and column scale factors of matrix given by (row, column, absolute value)
+/// triplets of its nonzero entries. Columns flagged in p are not scaled.
+fn scale_matrix(nrows: usize,
+                ncols: usize,
+                entries: &[(usize, usize, f64)],
+                p: &[bool]) -> (Vec<f64>, Vec<f64>) {
+
+    let mut row = vec![1.;nrows];
+    let mut col = vec![1.;ncols];
+
+    // Geometric-mean passes
+    let mut spread = matrix_spread(entries, &row, &col);
+    for _ in 0..MAX_PASSES {
+        if spread <= 1. {
+            break;
+        }
+        for (i, (lo, hi)) in extremes(nrows, entries, |e| (e.0, e.2*col[e.1])).iter().enumerate() {
+            if *hi > 0. {
+                row[i] = 1./(lo*hi).sqrt();
+            }
+        }
+        for (j, (lo, hi)) in extremes(ncols, entries, |e| (e.1, row[e.0]*e.2)).iter().enumerate() {
+            if *hi > 0. && !p[j] {
+                col[j] = 1./(lo*hi).sqrt();
+            }
+        }
+        let new_spread = matrix_spread(entries, &row, &col);
+        if new_spread > PASS_IMPROVEMENT*spread {
+            break;
+        }
+        spread = new_spread;
+    }
+
+    // Equilibration pass
+    for (i, (_lo, hi)) in extremes(nrows, entries, |e| (e.0, row[e.0]*e.2*col[e.1])).iter().enumerate() {
+        if *hi > 0. {
+            row[i] /= hi;
+        }
+    }
+    for (j, (_lo, hi)) in extremes(ncols, entries, |e| (e.1, row[e.0]*e.2*col[e.1])).iter().enumerate() {
+        if *hi > 0. && !p[j] {
+            col[j] /= hi;
+        }
+    }
+
+    (row.iter().map(|r| power_of_two(*r)).collect(),
+     col.iter().map(|d| power_of_two(*d)).collect())
+}
+
+/// Gets smallest and largest values of matrix entries grouped by index,
+/// where the index and value of each entry are given by a function.
+/// Groups without entries have values (0, 0).
+fn extremes<F>(n: usize, entries: &[(usize, usize, f64)], f: F) -> Vec<(f64, f64)>
+    where F: Fn(&(usize, usize, f64)) -> (usize, f64) {
+    let mut ext: Vec<(f64, f64)> = vec![(f64::INFINITY, 0.);n];
+    for e in entries.iter() {
+        let (k, v) = f(e);
+        ext[k] = (ext[k].0.min(v), ext[k].1.max(v));
+    }
+    for e in ext.iter_mut() {
+        if e.1 == 0. {
+            e.0 = 0.;
+        }
+    }
+    ext
+}
+
+/// Gets ratio between largest and smallest scaled matrix entries.
+fn matrix_spread(entries: &[(usize, usize, f64)], row: &[f64], col: &[f64]) -> f64 {
+    let mut lo = f64::INFINITY;
+    let mut hi: f64 = 0.;
+    for e in entries.iter() {
+        let v = row[e.0]*e.2*col[e.1];
+        lo = lo.min(v);
+        hi = hi.max(v);
+    }
+    if hi > 0. { hi/lo } else { 1. }
+}
+
+/// Gets power of two closest to the given positive value.
+fn power_of_two(x: f64) -> f64 {
+    2_f64.powi(x.log2().round() as i32)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use approx::assert_abs_diff_eq;
+
+    use super::*;
+    use crate::assert_vec_approx_eq;
+
+    fn is_power_of_two(x: f64) -> bool {
+        x > 0. && x.log2().fract() == 0.
+    }
+
+    #[test]
+    fn scaling_lp() {
+
+        // minimize   2*x0 + 1e-4*x1
+        // subject to 1000*x0 + 0.01*x1 = 10
+        //            2e5*x1 + 3*x2 = 4e3
+        //            0 <= x <= 1e3
+        let a = CooMat::new((2, 3),
+                            vec![0, 0, 1, 1],
+                            vec![0, 1, 1, 2],
+                            vec![1000., 0.01, 2e5, 3.]);
+        let c = vec![2., 1e-4, 0.];
+        let b = vec![10., 4e3];
+        let l = vec![0.;3];
+        let u = vec![1e3;3];
+        let mut problem = Problem::Lp(ProblemLp::new(c.clone(),
+                                                     a.clone(),
+                                                     b.clone(),
+                                                     l.clone(),
+                                                     u.clone(),
+                                                     None));
+
+        let scaling = ProblemScaling::new(&mut problem);
+        assert_eq!(scaling.col().len(), 3);
+        assert_eq!(scaling.row_a().len(), 2);
+        assert!(scaling.row_f().is_empty());
+        assert!(scaling.col().iter().chain(scaling.row_a().iter()).all(|x| is_power_of_two(*x)));
+        assert!(is_power_of_two(scaling.obj()));
+        assert!(scaling.obj() <= 1.);
+
+        let mut scaled = scaling.scale(problem);
+        let (cs, as_, bs, ls, us) = match scaled.problem() {
+            Problem::Lp(x) => (x.c().to_vec(), x.a().clone(), x.b().to_vec(), x.l().to_vec(), x.u().to_vec()),
+            _ => panic!("invalid scaled problem type"),
+        };
+
+        // Entries are equilibrated
+        let spread = |m: &CooMat<f64>| {
+            let v: Vec<f64> = m.data().iter().map(|x| x.abs()).collect();
+            v.iter().cloned().fold(0., f64::max)/v.iter().cloned().fold(f64::INFINITY, f64::min)
+        };
+        assert!(spread(&as_) < 1e-3*spread(&a));
+        assert!(as_.data().iter().all(|x| x.abs() <= 2.));
+        assert!(cs.iter().all(|x| x.abs() <= 1.));
+
+        // Scaled data
+        let (dc, r, s) = (scaling.col(), scaling.row_a(), scaling.obj());
+        for k in 0..3 {
+            assert_eq!(cs[k], s*dc[k]*c[k]);
+            assert_eq!(ls[k], l[k]/dc[k]);
+            assert_eq!(us[k], u[k]/dc[k]);
+        }
+        for k in 0..2 {
+            assert_eq!(bs[k], r[k]*b[k]);
+        }
+
+        // Scaled problem is evaluated in scaled space
+        let xs = vec![1., 2., 3.];
+        let p = base_mut(scaled.problem_mut());
+        p.evaluate(&xs);
+        let x: Vec<f64> = xs.iter().zip(dc.iter()).map(|(x, d)| x*d).collect();
+        assert_abs_diff_eq!(p.phi(), s*(2.*x[0] + 1e-4*x[1]), epsilon = 1e-12);
+
+        // Optimal point of scaled problem, obtained from the one of the
+        // original problem: x = (0.01, 0, 4e3/3), lam = (2e-3, 0), pi = (0, 8e-5, 0)
+        let mut sol = ProblemSol::new(3, 2, 0);
+        sol.x = vec![0.01/dc[0], 0., (4e3/3.)/dc[2]];
+        sol.lam = vec![s*2e-3/r[0], 0.];
+        sol.pi = vec![0., s*dc[1]*8e-5, 0.];
+
+        // Stationarity and feasibility of scaled problem
+        let mut res = cs.clone();
+        for (i, j, v) in as_.iter() {
+            res[*j] -= v*sol.lam[*i];
+        }
+        for k in 0..3 {
+            res[k] -= sol.pi[k];
+        }
+        assert_vec_approx_eq!(res, vec![0.;3], epsilon = 1e-12);
+        let mut ax = vec![0.;2];
+        for (i, j, v) in as_.iter() {
+            ax[*i] += v*sol.x[*j];
+        }
+        assert_vec_approx_eq!(ax, bs, epsilon = 1e-9);
+
+        let orig = scaling.unscale(&sol);
+        assert_vec_approx_eq!(orig.x, vec![0.01, 0., 4e3/3.], epsilon = 1e-12);
+        assert_vec_approx_eq!(orig.lam, vec![2e-3, 0.], epsilon = 1e-12);
+        assert_vec_approx_eq!(orig.mu, vec![0.;3], epsilon = 1e-12);
+        assert_vec_approx_eq!(orig.pi, vec![0., 8e-5, 0.], epsilon = 1e-12);
+
+        // Original problem
+        match scaled.into_original() {
+            Problem::Lp(x) => {
+                assert_eq!(x.c(), &c[..]);
+                assert_eq!(x.b(), &b[..]);
+                assert_eq!(x.a().data(), a.data());
+            },
+            _ => panic!("invalid original problem type"),
+        }
+    }
+
+    #[test]
+    fn scaling_milp() {
+
+        // Integer variables are not scaled
+        let a = CooMat::new((1, 2),
+                            vec![0, 0],
+                            vec![0, 1],
+                            vec![1e4, 1e-2]);
+        let mut problem = Problem::Milp(ProblemMilp::new(vec![1., 1.],
+                                                         a,
+                                                         vec![1.],
+                                                         vec![0., 0.],
+                                                         vec![1e3, 1e3],
+                                                         vec![true, false],
+                                                         Some(vec![1., 2.])));
+        let scaling = ProblemScaling::new(&mut problem);
+        assert_eq!(scaling.col()[0], 1.);
+        assert!(scaling.col()[1] > 1.);
+
+        let scaled = scaling.scale(problem);
+        match scaled.problem() {
+            Problem::Milp(x) => {
+                assert_eq!(x.p(), &[true, false]);
+                assert_eq!(x.x0().unwrap(), &[1., 2./scaling.col()[1]]);
+            },
+            _ => panic!("invalid scaled problem type"),
+        }
+
+        // Identity
+        let identity = ProblemScaling::identity(2, 1, 0);
+        let mut sol = ProblemSol::new(2, 1, 0);
+        sol.x = vec![1., 2.];
+        sol.lam = vec![3.];
+        sol.mu = vec![4., 5.];
+        let orig = identity.unscale(&sol);
+        assert_eq!(orig.x, sol.x);
+        assert_eq!(orig.lam, sol.lam);
+        assert_eq!(orig.mu, sol.mu);
+    }
+
+    #[test]
+    fn scaling_nlp() {
+
+        // minimize   1e3*x0^2 + x0*x1
+        // subject to 1e-3*x1^2 - 50*x0 = 0
+        //            1e2 <= x1 <= 1e4
+        let eval_fn = Box::new(move | phi: &mut f64,
+                                      gphi: &mut Vec<f64>,
+                                      hphi: &mut CooMat<f64>,
+                                      f: &mut Vec<f64>,
+                                      j: &mut CooMat<f64>,
+                                      h: &mut Vec<CooMat<f64>>,
+                                      x: &[f64] | {
+            *phi = 1e3*x[0]*x[0] + x[0]*x[1];
+            gphi[0] = 2e3*x[0] + x[1];
+            gphi[1] = x[0];
+            hphi.data_mut()[0] = 2e3;
+            hphi.data_mut()[1] = 1.;
+            f[0] = 1e-3*x[1]*x[1] - 50.*x[0];
+            j.data_mut()[0] = -50.;
+            j.data_mut()[1] = 2e-3*x[1];
+            h[0].data_mut()[0] = 2e-3;
+        });
+        let mut problem = Problem::Nlp(ProblemNlp::new(CooMat::from_pattern((2, 2), vec![0, 1], vec![0, 0]),
+                                                       CooMat::from_nnz((0, 2), 0),
+                                                       Vec::new(),
+                                                       CooMat::from_pattern((1, 2), vec![0, 0], vec![0, 1]),
+                                                       vec![CooMat::from_pattern((2, 2), vec![1], vec![1])],
+                                                       vec![-f64::INFINITY, 1e2],
+                                                       vec![f64::INFINITY, 1e4],
+                                                       Some(vec![1., 1e3]),
+                                                       eval_fn));
+
+        let scaling = ProblemScaling::new(&mut problem);
+        let (dc, r, s) = (scaling.col().to_vec(), scaling.row_f()[0], scaling.obj());
+        assert!(s < 1.);
+        assert!(dc[1] > dc[0]);
+
+        let mut scaled = scaling.scale(problem);
+        assert!(matches!(scaled.problem(), Problem::Nlp(_)));
+        {
+            let p = base_mut(scaled.problem_mut());
+            assert_eq!(p.l()[1], 1e2/dc[1]);
+            assert_eq!(p.x0().unwrap()[1], 1e3/dc[1]);
+
+            let xs = vec![0.5/dc[0], 300./dc[1]];
+            p.evaluate(&xs);
+            let x = vec![0.5, 300.];
+            assert_abs_diff_eq!(p.phi(), s*(1e3*x[0]*x[0] + x[0]*x[1]), epsilon = 1e-9);
+            assert_vec_approx_eq!(p.gphi(),
+                                  vec![s*dc[0]*(2e3*x[0] + x[1]), s*dc[1]*x[0]],
+                                  epsilon = 1e-9);
+            assert_vec_approx_eq!(p.hphi().data(),
+                                  vec![s*dc[0]*dc[0]*2e3, s*dc[1]*dc[0]],
+                                  epsilon = 1e-9);
+            assert_abs_diff_eq!(p.f()[0], r*(1e-3*x[1]*x[1] - 50.*x[0]), epsilon = 1e-9);
+            assert_vec_approx_eq!(p.j().data(),
+                                  vec![-50.*r*dc[0], 2e-3*x[1]*r*dc[1]],
+                                  epsilon = 1e-9);
+            assert_vec_approx_eq!(p.h()[0].data(),
+                                  vec![2e-3*r*dc[1]*dc[1]],
+                                  epsilon = 1e-9);
+        }
+
+        // Duals
+        let mut sol = ProblemSol::new(2, 0, 1);
+        sol.nu = vec![3.];
+        sol.pi = vec![0., 4.];
+        let orig = scaling.unscale(&sol);
+        assert_abs_diff_eq!(orig.nu[0], 3.*r/s);
+        assert_abs_diff_eq!(orig.pi[1], 4./(s*dc[1]));
+
+        match scaled.into_original() {
+            Problem::Nlp(mut x) => {
+                x.as_mut_minlp().evaluate(&[1., 2.]);
+                assert_eq!(x.as_minlp().phi(), 1002.);
+            },
+            _ => panic!("invalid original problem type"),
+        }
+    }
+}
@@ -0,0 +1,267 @@
+//! Interface to AMPL-compatible solver executables.
+
+use std::ffi::OsStr;
+use std::path::Path;
+use simple_error::SimpleError;
+use std::collections::HashMap;
+
+use crate::model::model_std::ModelStdProb;
+use crate::model::model_solver::ModelSolver;
+use crate::model::model_nl::ModelStdNl;
+use crate::solver::base::{Solver,
+                          SolverParam,
+                          SolverStatus};
+use crate::solver::cmd::{self, SolverCmd, SolverCmdCancel, SolverCmdDir, SolverCmdExecutable};
+use crate::problem::base::{Problem,
+                           ProblemSol};
+
+/// Interface to optimization solvers that utilize AMPL-compatible executables,
+/// e.g., "ipopt", "bonmin", "couenne", or "cbc".
+///
+/// The executable needs to be on the system path, or its location needs to be
+/// given with [set_executable](../cmd/trait.SolverCmd.html#method.set_executable)
+/// or the environment variable "NUMOPT_<SOLVER>", e.g., "NUMOPT_IPOPT".
+/// Problems are passed to the
+/// solver as NL files, and hence they can only be solved through
+/// [Model::solve](../../model/model/struct.Model.html#method.solve), since their
+/// expressions are needed.
+///
+/// Parameters: any parameter can be set, and they are all passed to the solver
+/// as "name=value" options through the environment variable "<solver>_options",
+/// e.g., "ipopt_options", except for "timeout" (float), which is the wall-clock
+/// time in seconds after which the solver process is killed, and "logLevel" (int),
+/// which controls whether the solver output is echoed (if positive, default 1).
+pub struct SolverAmplCmd {
+    executable: SolverCmdExecutable,
+    cancel: SolverCmdCancel,
+    parameters: HashMap<String, SolverParam>,
+}
+
+impl SolverAmplCmd {
+
+    /// Creates solver instance for a given AMPL-compatible executable.
+    pub fn new(executable: &str) -> Self {
+        let env_var = format!("NUMOPT_{}", Self::stem(executable).to_uppercase());
+        let mut parameters: HashMap<String, SolverParam> = HashMap::new();
+        parameters.insert("logLevel".to_string(), SolverParam::IntParam(1));
+        Self {
+            executable: SolverCmdExecutable::new(executable, &env_var, &["-v"]),
+            cancel: SolverCmdCancel::new(),
+            parameters: parameters,
+        }
+    }
+
+    /// Gets name of environment variable used for passing options to the solver.
+    pub fn options_name(&self) -> String {
+        format!("{}_options", Self::stem(self.executable.name()))
+    }
+
+    /// Gets value of environment variable used for passing options to the solver.
+    pub fn options_value(&self) -> String {
+        let mut names: Vec<&String> = self.parameters.keys()
+                                                        .filter(|n| *n != "timeout" && *n != "logLevel")
+                                                        .collect();
+        names.sort();
+        let options: Vec<String> = names.iter().map(|name| {
+            match self.parameters.get(*name).unwrap() {
+                SolverParam::IntParam(i) => format!("{}={}", name, i),
+                SolverParam::FloatParam(f) => format!("{}={}", name, f),
+                SolverParam::StrParam(s) => format!("{}={}", name, s),
+            }
+        }).collect();
+        options.join(" ")
+    }
+
+    /// Gets file stem of executable, e.g., "bonmin" for "/usr/local/bin/bonmin".
+    fn stem(executable: &str) -> String {
+        Path::new(executable).file_stem()
+                             .and_then(OsStr::to_str)
+                             .unwrap_or("")
+                             .to_string()
+    }
+
+    /// Interprets AMPL solve result number and solver message as solver status.
+    pub fn status_from_result(solve_result_num: Option<i32>, message: &str) -> SolverStatus {
+        match solve_result_num {
+            Some(0..=99) => SolverStatus::Solved,
+            Some(200..=299) => SolverStatus::Infeasible,
+            Some(400..=499) => {
+                if message.to_lowercase().contains("time") {
+                    SolverStatus::TimeLimit
+                }
+                else {
+                    SolverStatus::IterationLimit
+                }
+            },
+            Some(n) if n >= 500 => SolverStatus::Error,
+            _ => SolverStatus::Unknown,
+        }
+    }
+}
+
+impl SolverCmd for SolverAmplCmd {
+
+    fn executable(&self) -> &SolverCmdExecutable { &self.executable }
+    fn executable_mut(&mut self) -> &mut SolverCmdExecutable { &mut self.executable }
+    fn cancel_token(&self) -> &SolverCmdCancel { &self.cancel }
+    fn cancel_token_mut(&mut self) -> &mut SolverCmdCancel { &mut self.cancel }
+}
+
+impl Solver for SolverAmplCmd {
+
+    fn get_params(&self) -> &HashMap<String, SolverParam> { &self.parameters }
+    fn get_params_mut(&mut self) -> &mut HashMap<String, SolverParam> { &mut self.parameters }
+
+    fn set_param(&mut self, name: &str, value: SolverParam) -> Result<(), SimpleError> {
+        self.parameters.insert(name.to_string(), value);
+        Ok(())
+    }
+
+    fn solve(&self, _problem: &mut Problem) -> Result<(SolverStatus, ProblemSol), SimpleError> {
+        Err(SimpleError::new("problem type requires model expressions"))
+    }
+}
+
+impl ModelSolver for SolverAmplCmd {
+
+    fn solve_std(&self, std_prob: &mut ModelStdProb) -> Result<(SolverStatus, ProblemSol), SimpleError> {
+
+        // Parameters
+        let log_level = match self.get_param("logLevel") {
+            Some(SolverParam::IntParam(i)) => *i,
+            _ => return Err(SimpleError::new("unable to get parameter logLevel"))
+        };
+
+        // Working directory and filenames
+        let dir = SolverCmdDir::new("ampl")?;
+        let input_filename = dir.file("problem.nl");
+        let output_filename = dir.file("problem.sol");
+
+        // Write input file
+        match std_prob.write_to_nl_file(&input_filename) {
+            Ok(()) => (),
+            Err(_e) => return Err(SimpleError::new("failed to write nl file")),
+        };
+
+        // Call solver command
+        let output = cmd::run(self.executable.command()
+                                      .env(self.options_name(), self.options_value())
+                                      .args([&input_filename, "-AMPL"]),
+                              log_level > 0,
+                              self.timeout(),
+                              &self.cancel)?;
+
+        // Read output file
+        let read = std_prob.read_sol_file(&output_filename)
+                           .map(|s| (Self::status_from_result(s.solve_result_num, &s.message), s.solution));
+        let (status, mut solution) = output.solution(read,
+                                                     || {
+                                                         let p = std_prob.as_minlp();
+                                                         ProblemSol::new(p.nx(), p.na(), p.nf())
+                                                     },
+                                                     "failed to read solver solution file")?;
+        solution.log = Some(output.log());
+
+        // All good
+        Ok((status, solution))
+    }
+
+    fn uses_model_expressions(&self, _problem: &Problem) -> bool { true }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::fs;
+    use tempfile::TempDir;
+    #[cfg(unix)]
+    use std::os::unix::fs::PermissionsExt;
+
+    use crate::model::node_cmp::NodeCmp;
+    use crate::model::variable::VariableScalar;
+    use crate::model::model::{Model, Objective};
+    use crate::solver::base::{Solver, SolverParam, SolverStatus};
+    use crate::solver::cmd::SolverCmd;
+    use crate::solver::ampl_cmd::SolverAmplCmd;
+
+    #[test]
+    fn ampl_options() {
+
+        let mut s = SolverAmplCmd::new("/usr/local/bin/bonmin");
+        assert_eq!(s.executable().path(), "/usr/local/bin/bonmin");
+        assert_eq!(s.executable().env_var(), "NUMOPT_BONMIN");
+        assert_eq!(s.options_name(), "bonmin_options");
+        assert_eq!(s.options_value(), "");
+        match s.get_param("logLevel") {
+            Some(SolverParam::IntParam(1)) => (),
+            _ => panic!("invalid logLevel"),
+        }
+        s.set_param("logLevel", SolverParam::IntParam(0)).unwrap();
+        s.set_param("timeout", SolverParam::FloatParam(10.)).unwrap();
+        assert_eq!(s.options_value(), "");
+        s.set_param("bonmin.algorithm", SolverParam::StrParam("B-BB".to_string())).unwrap();
+        s.set_param("max_iter", SolverParam::IntParam(100)).unwrap();
+        s.set_param("tol", SolverParam::FloatParam(1e-9)).unwrap();
+        assert_eq!(s.options_value(), "bonmin.algorithm=B-BB max_iter=100 tol=0.000000001");
+
+        // Invalid log level
+        let x = VariableScalar::new_continuous("x");
+        let mut m = Model::new();
+        m.set_objective(Objective::minimize(&(&x*&x)));
+        s.set_param("logLevel", SolverParam::FloatParam(1.)).unwrap();
+        assert_eq!(m.solve(&s).unwrap_err().to_string(), "unable to get parameter logLevel");
+    }
+
+    #[test]
+    fn ampl_status_from_result() {
+
+        assert_eq!(SolverAmplCmd::status_from_result(Some(0), ""), SolverStatus::Solved);
+        assert_eq!(SolverAmplCmd::status_from_result(Some(200), ""), SolverStatus::Infeasible);
+        assert_eq!(SolverAmplCmd::status_from_result(Some(400), "Maximum Number of Iterations Exceeded."),
+                   SolverStatus::IterationLimit);
+        assert_eq!(SolverAmplCmd::status_from_result(Some(400), "Maximum CPU Time Exceeded."),
+                   SolverStatus::TimeLimit);
+        assert_eq!(SolverAmplCmd::status_from_result(Some(300), ""), SolverStatus::Unknown);
+        assert_eq!(SolverAmplCmd::status_from_result(Some(500), ""), SolverStatus::Error);
+        assert_eq!(SolverAmplCmd::status_from_result(None, ""), SolverStatus::Unknown);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn ampl_solve_nlp_stub() {
+
+        // min   x*x + y
+        // s.t.  x + y == 2
+
+        let x = VariableScalar::new_continuous("x");
+        let y = VariableScalar::new_continuous("y");
+        let c = (&x + &y).equal(2.);
+        let mut m = Model::new();
+        m.set_objective(Objective::minimize(&(&x*&x + &y)));
+        m.add_constraint(&c);
+
+        // Stub ipopt that records options and writes a fixed solution
+        let dir = TempDir::new().unwrap();
+        let options = dir.path().join("options");
+        let stub = dir.path().join("ipopt");
+        fs::write(&stub,
+                  format!("#!/bin/sh\n\
+                           echo \"$ipopt_options\" > {}\n\
+                           [ \"$2\" = \"-AMPL\" ] || exit 1\n\
+                           printf 'Ipopt: Optimal Solution Found\\n\\nOptions\\n3\\n1\\n1\\n0\\n1\\n1\\n2\\n2\\n1\\n0.5\\n1.5\\nobjno 0 0\\n' \
+                           > \"${{1%.nl}}.sol\"\n", options.to_str().unwrap())).unwrap();
+        fs::set_permissions(&stub, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut s = SolverAmplCmd::new("ipopt");
+        s.set_executable(stub.to_str().unwrap());
+        s.set_param("logLevel", SolverParam::IntParam(0)).unwrap();
+        s.set_param("print_level", SolverParam::IntParam(0)).unwrap();
+        s.set_param("tol", SolverParam::FloatParam(1e-8)).unwrap();
+        m.solve(&s).unwrap();
+        assert_eq!(fs::read_to_string(&options).unwrap(), "print_level=0 tol=0.00000001\n");
+        assert_eq!(*m.solver_status().unwrap(), SolverStatus::Solved);
+        assert_eq!(*m.final_primals().get(&x).unwrap(), 0.5);
+        assert_eq!(*m.final_primals().get(&y).unwrap(), 1.5);
+        assert_eq!(*m.final_duals().get(&c).unwrap(), 1.);
+    }
+}
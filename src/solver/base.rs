@@ -7,7 +7,7 @@ use std::collections::HashMap;
 use crate::problem::base::{Problem, ProblemSol};
 
 /// Optimization solver status.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum SolverStatus {
 
     /// Optimization solver successfully solved problem 
@@ -18,6 +18,18 @@ pub enum SolverStatus {
     /// is infeasible.
     Infeasible,
 
+    /// Optimization solver stopped because it reached
+    /// its time limit. The solution holds the best point found.
+    TimeLimit,
+
+    /// Optimization solver stopped because it reached
+    /// its iteration or node limit. The solution holds the best point found.
+    IterationLimit,
+
+    /// Optimization solver was stopped because it was cancelled.
+    /// The solution holds the best point found.
+    Interrupted,
+
     /// Optimization solver has unknown status.
     Unknown,
 
@@ -88,7 +100,10 @@ impl fmt::Display for SolverStatus {
             SolverStatus::Error => write!(f, "error"),
             SolverStatus::Unknown => write!(f, "unknown"),
             SolverStatus::Solved => write!(f, "solved"),
-            SolverStatus::Infeasible => write!(f, "infeasible")
+            SolverStatus::Infeasible => write!(f, "infeasible"),
+            SolverStatus::TimeLimit => write!(f, "time limit"),
+            SolverStatus::IterationLimit => write!(f, "iteration limit"),
+            SolverStatus::Interrupted => write!(f, "interrupted"),
         }
     }
 }
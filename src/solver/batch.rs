@@ -0,0 +1,197 @@
+//! Batch solving of optimization problems and models on a pool of worker threads.
+//!
+//! Each worker thread creates its own solver instance with a given factory,
+//! so the solver only needs to be safe to run concurrently with other
+//! instances of itself, as is the case for the command-line solvers.
+//! This module requires the "sync" feature.
+
+use std::thread;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use simple_error::SimpleError;
+
+use crate::model::model::Model;
+use crate::model::model_solver::ModelSolver;
+use crate::solver::base::{Solver, SolverStatus};
+use crate::problem::base::{Problem, ProblemSol};
+
+/// Progress callback of batch solves. It receives the number of completed
+/// instances and the total number of instances, and it is called from the
+/// worker threads after each instance is completed.
+pub type SolverBatchProgress<'a> = &'a (dyn Fn(usize, usize) + Sync);
+
+/// Solves optimization problems on a pool of worker threads, each using a solver
+/// created by the given factory. The number of threads is capped by the number
+/// of problems, and zero means the available parallelism of the system.
+/// The results are returned in the order of the problems.
+pub fn solve_batch<S, F>(problems: &mut [Problem],
+                         factory: F,
+                         num_threads: usize,
+                         progress: Option<SolverBatchProgress>)
+                         -> Vec<Result<(SolverStatus, ProblemSol), SimpleError>>
+                         where S: Solver, F: Fn() -> S + Sync {
+    run(problems, factory, num_threads, progress, |s, p| s.solve(p))
+}
+
+/// Solves optimization models on a pool of worker threads, each using a solver
+/// created by the given factory. The solutions are stored in the models, as with
+/// [Model::solve](../../model/model/struct.Model.html#method.solve), and the
+/// results are returned in the order of the models. The number of threads is
+/// as in [solve_batch](fn.solve_batch.html).
+pub fn solve_batch_models<S, F>(models: &mut [Model],
+                                factory: F,
+                                num_threads: usize,
+                                progress: Option<SolverBatchProgress>)
+                                -> Vec<Result<(), SimpleError>>
+                                where S: ModelSolver, F: Fn() -> S + Sync {
+    run(models, factory, num_threads, progress, |s, m| m.solve(s))
+}
+
+/// Runs the given solve function on all items using a pool of worker threads.
+fn run<T, R, S, F, G>(items: &mut [T],
+                      factory: F,
+                      num_threads: usize,
+                      progress: Option<SolverBatchProgress>,
+                      solve: G) -> Vec<R>
+                      where T: Send,
+                            R: Send,
+                            S: Solver,
+                            F: Fn() -> S + Sync,
+                            G: Fn(&S, &mut T) -> R + Sync {
+
+    let total = items.len();
+    let num_threads = match num_threads {
+        0 => thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+        n => n,
+    }.min(total).max(1);
+
+    // Shared queue of items and completion counter
+    let queue = Mutex::new(items.iter_mut().enumerate());
+    let completed = AtomicUsize::new(0);
+
+    // Workers
+    let mut results: Vec<(usize, R)> = thread::scope(|scope| {
+        let handles: Vec<thread::ScopedJoinHandle<Vec<(usize, R)>>> = (0..num_threads).map(|_| {
+            scope.spawn(|| {
+                let solver = factory();
+                let mut results: Vec<(usize, R)> = Vec::new();
+                loop {
+                    let next = queue.lock().unwrap().next();
+                    let (index, item) = match next {
+                        Some(x) => x,
+                        None => break,
+                    };
+                    results.push((index, solve(&solver, item)));
+                    let done = completed.fetch_add(1, Ordering::SeqCst) + 1;
+                    if let Some(f) = progress {
+                        f(done, total);
+                    }
+                }
+                results
+            })
+        }).collect();
+        handles.into_iter().flat_map(|h| h.join().unwrap()).collect()
+    });
+
+    // Input order
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, r)| r).collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use simple_error::SimpleError;
+
+    use crate::matrix::coo::CooMat;
+    use crate::model::node_cmp::NodeCmp;
+    use crate::model::variable::VariableScalar;
+    use crate::model::model::{Model, Objective};
+    use crate::problem::lp::ProblemLp;
+    use crate::problem::base::{Problem, ProblemSol};
+    use crate::solver::base::{Solver, SolverParam, SolverStatus};
+    use crate::solver::batch::{solve_batch, solve_batch_models};
+    use crate::model::model_solver::ModelSolver;
+
+    // Solver that sets each variable to its upper bound
+    struct SolverUpper {
+        parameters: HashMap<String, SolverParam>,
+    }
+
+    impl Solver for SolverUpper {
+
+        fn get_params(&self) -> &HashMap<String, SolverParam> { &self.parameters }
+        fn get_params_mut(&mut self) -> &mut HashMap<String, SolverParam> { &mut self.parameters }
+
+        fn solve(&self, problem: &mut Problem) -> Result<(SolverStatus, ProblemSol), SimpleError> {
+            let p = match problem {
+                Problem::Lp(x) => x,
+                _ => return Err(SimpleError::new("problem type not supported")),
+            };
+            let mut solution = ProblemSol::new(p.nx(), p.na(), 0);
+            solution.x.copy_from_slice(p.u());
+            Ok((SolverStatus::Solved, solution))
+        }
+    }
+
+    impl ModelSolver for SolverUpper {}
+
+    fn new_solver() -> SolverUpper {
+        SolverUpper { parameters: HashMap::new() }
+    }
+
+    #[test]
+    fn batch_solve_problems() {
+
+        let mut problems: Vec<Problem> = (0..20).map(|i| {
+            Problem::Lp(ProblemLp::new(vec![-1.],
+                                       CooMat::from_nnz((0, 1), 0),
+                                       Vec::new(),
+                                       vec![0.],
+                                       vec![i as f64],
+                                       None))
+        }).collect();
+
+        let calls = Mutex::new(Vec::new());
+        let progress = |done: usize, total: usize| calls.lock().unwrap().push((done, total));
+
+        for num_threads in [0, 1, 4, 50].iter() {
+            calls.lock().unwrap().clear();
+            let results = solve_batch(&mut problems, new_solver, *num_threads, Some(&progress));
+            assert_eq!(results.len(), 20);
+            for (i, r) in results.iter().enumerate() {
+                let (status, solution) = r.as_ref().unwrap();
+                assert_eq!(*status, SolverStatus::Solved);
+                assert_eq!(solution.x, vec![i as f64]);
+            }
+            let mut calls = calls.lock().unwrap().clone();
+            calls.sort();
+            assert_eq!(calls, (1..21).map(|n| (n, 20)).collect::<Vec<(usize, usize)>>());
+        }
+
+        // Empty batch
+        assert!(solve_batch(&mut Vec::new(), new_solver, 4, None).is_empty());
+    }
+
+    #[test]
+    fn batch_solve_models() {
+
+        let x = VariableScalar::new_continuous("x");
+        let mut models: Vec<Model> = (0..10).map(|i| {
+            let mut m = Model::new();
+            m.set_objective(Objective::maximize(&(2.*&x)));
+            m.add_constraint(&(&x).leq(i as f64));
+            m.add_constraint(&(&x).geq(0.));
+            m
+        }).collect();
+
+        let results = solve_batch_models(&mut models, new_solver, 3, None);
+        for (i, (r, m)) in results.iter().zip(models.iter()).enumerate() {
+            assert!(r.is_ok());
+            assert_eq!(*m.solver_status().unwrap(), SolverStatus::Solved);
+            assert_eq!(*m.final_primals().get(&x).unwrap(), i as f64);
+        }
+    }
+}
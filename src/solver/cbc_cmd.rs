@@ -1,18 +1,16 @@
 //! Cbc solver interface.
 
 use std::fs::File;
-use std::ffi::OsStr;
-use tempfile::Builder;
 use std::io::prelude::*;
-use std::fs::remove_file;
-use std::process::Command;
 use simple_error::SimpleError;
-use std::io::{self, BufReader};
+use std::io::{self, BufReader, BufWriter};
 use std::collections::HashMap;
 
+use crate::model::model_solver::ModelSolver;
 use crate::solver::base::{Solver, 
                           SolverParam,
                           SolverStatus};
+use crate::solver::cmd::{self, SolverCmd, SolverCmdCancel, SolverCmdDir, SolverCmdExecutable};
 use crate::problem::base::{Problem,
                            ProblemSol};
 use crate::problem::milp::{ProblemMilp,
@@ -21,11 +19,29 @@ use crate::problem::milp::{ProblemMilp,
 /// Interface to the optimization solver Cbc from COIN-OR 
 /// that utilzes the command-line tool "cbc". 
 ///
-/// The command-line tool "cbc" needs to be on the system path.
+/// The command-line tool "cbc" needs to be on the system path, or its location
+/// needs to be given with [set_executable](../cmd/trait.SolverCmd.html#method.set_executable)
+/// or the environment variable "NUMOPT_CBC".
 /// 
 /// It can solve problems of type [ProblemLp](../../problem/lp/struct.ProblemLp.html) 
 /// and [ProblemMilp](../../problem/milp/struct.ProblemMilp.html).
+///
+/// Parameters:
+/// * "logLevel" (integer): amount of output printed by cbc.
+/// * "sec" (float): time limit in seconds.
+/// * "ratioGap" (float): relative gap at which to stop.
+/// * "allowableGap" (float): absolute gap at which to stop.
+/// * "threads" (integer): number of threads.
+/// * "maxNodes" (integer): maximum number of branch-and-bound nodes.
+/// * "presolve" (string): "on" or "off".
+/// * "cuts" (string): "on", "off", "root", "ifmove" or "forceOn".
+/// * "heuristics" (string): "on" or "off".
+/// * "timeout" (float): wall-clock time in seconds after which the solver process is killed.
+///
+/// If the problem has an initial point, it is passed to cbc as a MIP start.
 pub struct SolverCbcCmd {
+    executable: SolverCmdExecutable,
+    cancel: SolverCmdCancel,
     parameters: HashMap<String, SolverParam>,
 }
 
@@ -36,13 +52,27 @@ impl SolverCbcCmd {
 
         let mut parameters: HashMap<String, SolverParam> = HashMap::new();
         parameters.insert("logLevel".to_string(), SolverParam::IntParam(5));
+        parameters.insert("sec".to_string(), SolverParam::FloatParam(1e8));
+        parameters.insert("ratioGap".to_string(), SolverParam::FloatParam(0.));
+        parameters.insert("allowableGap".to_string(), SolverParam::FloatParam(1e-10));
+        parameters.insert("threads".to_string(), SolverParam::IntParam(0));
+        parameters.insert("maxNodes".to_string(), SolverParam::IntParam(2147483647));
+        parameters.insert("presolve".to_string(), SolverParam::StrParam("on".to_string()));
+        parameters.insert("cuts".to_string(), SolverParam::StrParam("on".to_string()));
+        parameters.insert("heuristics".to_string(), SolverParam::StrParam("on".to_string()));
+        parameters.insert("timeout".to_string(), SolverParam::FloatParam(f64::INFINITY));
 
         Self {
+            executable: SolverCmdExecutable::new("cbc", "NUMOPT_CBC", &["-quit"]),
+            cancel: SolverCmdCancel::new(),
             parameters: parameters,
         } 
     }
 
-    /// Reads cbc solver solution file.
+    /// Reads cbc solver solution file, or clp solver solution file if "cbc" is false.
+    /// Cbc node limits are taken as iteration limits.
+    /// Clp reports limits as "stopped on iterations or time", which is taken as
+    /// an iteration limit unless only time is mentioned.
     pub fn read_sol_file(fname: &str, 
                          p: &ProblemMilp, 
                          cbc: bool) -> io::Result<(SolverStatus, ProblemSol)> {
@@ -64,7 +94,8 @@ impl SolverCbcCmd {
 
         // Status
         r.read_line(&mut line)?;
-        match line.split_ascii_whitespace().next() {
+        let header = line.trim().to_string();
+        match header.split_ascii_whitespace().next() {
             Some(s) => {
                 if !cbc && s == "optimal" {
                     status = SolverStatus::Solved;
@@ -72,21 +103,42 @@ impl SolverCbcCmd {
                 else if !cbc && s == "infeasible" {
                     status = SolverStatus::Infeasible;
                 }
+                else if !cbc && header.starts_with("stopped on time") {
+                    status = SolverStatus::TimeLimit;
+                }
+                else if !cbc && header.starts_with("stopped on iterations") {
+                    status = SolverStatus::IterationLimit;
+                }
                 else if cbc && s == "Optimal" {
                     status = SolverStatus::Solved;
                 }
                 else if cbc && s == "Infeasible" {
                     status = SolverStatus::Infeasible;
                 }
+                else if cbc && header.starts_with("Stopped on time") {
+                    status = SolverStatus::TimeLimit;
+                }
+                else if cbc && (header.starts_with("Stopped on iterations") ||
+                                header.starts_with("Stopped on nodes")) {
+                    status = SolverStatus::IterationLimit;
+                }
             },
             None => {
                 status = SolverStatus::Error;
             }
         }
 
-        // Not solved
-        if status != SolverStatus::Solved {
-            return Ok((status, solution))
+        // No point to read
+        match status {
+            SolverStatus::Solved => (),
+            SolverStatus::TimeLimit | SolverStatus::IterationLimit => {
+                
+                // Limit reached before finding an integer solution
+                if header.contains("no integer solution") {
+                    return Ok((status, solution))
+                }
+            },
+            _ => return Ok((status, solution))
         }
 
         // Objective value
@@ -143,6 +195,29 @@ impl SolverCbcCmd {
 
         Ok((status, solution))
     }
+
+    /// Writes cbc MIP start file with the values of a given point.
+    pub fn write_mipstart_file(fname: &str, x: &[f64]) -> io::Result<()> {
+
+        let f = File::create(fname)?;
+        let mut w = BufWriter::new(f);
+
+        for (i, val) in x.iter().enumerate() {
+            writeln!(w, "{} x_{} {:.10e}", i, i, val)?;
+        }
+
+        w.flush()?;
+
+        Ok(())
+    }
+}
+
+impl SolverCmd for SolverCbcCmd {
+
+    fn executable(&self) -> &SolverCmdExecutable { &self.executable }
+    fn executable_mut(&mut self) -> &mut SolverCmdExecutable { &mut self.executable }
+    fn cancel_token(&self) -> &SolverCmdCancel { &self.cancel }
+    fn cancel_token_mut(&mut self) -> &mut SolverCmdCancel { &mut self.cancel }
 }
 
 impl Solver for SolverCbcCmd {
@@ -159,96 +234,192 @@ impl Solver for SolverCbcCmd {
             _ => return Err(SimpleError::new("problem type not supported"))
         };
 
-        // Input filename
-        let input_file = Builder::new()
-            .prefix("cbc")
-            .suffix(".lp")
-            .tempfile();
-        let input_filename = match input_file {
-            Ok(f) => f.path().file_name().and_then(OsStr::to_str).unwrap().to_string(),
-            Err(_e) => return Err(SimpleError::new("failed to create input filename")),
-        };
-
-        // Output filename
-        let output_file = Builder::new()
-            .prefix("cbc")
-            .suffix(".sol")
-            .tempfile();
-        let output_filename = match output_file {
-            Ok(f) => f.path().file_name().and_then(OsStr::to_str).unwrap().to_string(),
-            Err(_e) => return Err(SimpleError::new("failed to create output filename")),
-        };
+        // Working directory and filenames
+        let dir = SolverCmdDir::new("cbc")?;
+        let input_filename = dir.file("problem.lp");
+        let output_filename = dir.file("problem.sol");
+        let mipstart_filename = dir.file("problem.mst");
 
         // Write input file
         match p.write_to_lp_file(&input_filename) {
             Ok(()) => (),
-            Err(_e) => {
-                remove_file(&input_filename).ok();
-                remove_file(&output_filename).ok();
-                return Err(SimpleError::new("failed to write lp file"));
-            }
+            Err(_e) => return Err(SimpleError::new("failed to write lp file")),
         };
 
         // Parameters
-        let log_level = match self.get_param("logLevel") {
-            Some(SolverParam::IntParam(i)) => i,
-            _ => return Err(SimpleError::new("unable to get parameter logLevel"))
+        let mut args: Vec<String> = vec![input_filename.clone()];
+        for name in ["logLevel", 
+                     "sec", 
+                     "ratioGap", 
+                     "allowableGap", 
+                     "threads", 
+                     "maxNodes", 
+                     "presolve", 
+                     "cuts", 
+                     "heuristics"].iter() {
+            let value = match self.get_param(name) {
+                Some(SolverParam::IntParam(i)) => format!("{}", i),
+                Some(SolverParam::FloatParam(f)) => format!("{}", f),
+                Some(SolverParam::StrParam(s)) => s.clone(),
+                None => return Err(SimpleError::new(format!("unable to get parameter {}", name)))
+            };
+            args.push(name.to_string());
+            args.push(value);
+        }
+        let echo = match self.get_param("logLevel") {
+            Some(SolverParam::IntParam(i)) => *i > 0,
+            _ => false,
         };
 
-        // Call Cbc command
-        match Command::new("cbc")
-                      .args(&[&input_filename,
-                              "logLevel",
-                              format!("{}", log_level).as_ref(),
-                              "printingOptions",
-                              "all", 
-                              "solve", 
-                              "solution",
-                              &output_filename])
-                      .spawn()
-                      .and_then(|mut cmd| cmd.wait()) {
-            Ok(_s) => (),
-            Err(_e) => {
-                remove_file(&input_filename).ok();
-                remove_file(&output_filename).ok();
-                return Err(SimpleError::new("failed executing cbc command"));
-            }
+        // Write MIP start file
+        if let Some(x0) = p.x0() {
+            match Self::write_mipstart_file(&mipstart_filename, x0) {
+                Ok(()) => (),
+                Err(_e) => return Err(SimpleError::new("failed to write mipstart file")),
+            };
+            args.push("-mips".to_string());
+            args.push(mipstart_filename.clone());
         }
-        
-        // Clean up input file
-        remove_file(&input_filename).ok();
+        args.extend(["printingOptions", 
+                     "all", 
+                     "solve", 
+                     "solution"].iter().map(|s| s.to_string()));
+        args.push(output_filename.clone());
+
+        // Call Cbc command
+        let output = cmd::run(self.executable.command().args(&args),
+                              echo,
+                              self.timeout(),
+                              &self.cancel)?;
 
         // Read output file
-        let (status, solution) = match Self::read_sol_file(&output_filename, &p, true) {
-            Ok((s, sol)) => (s, sol),
-            Err(_e) => {
-                remove_file(&output_filename).ok();
-                return Err(SimpleError::new("failed to read cbc solution file"))
-            }
-        };
+        let (status, mut solution) = output.solution(Self::read_sol_file(&output_filename, &p, true),
+                                                     || ProblemSol::new(p.nx(), p.na(), 0),
+                                                     "failed to read cbc solution file")?;
+        solution.log = Some(output.log());
 
-        // Clean up output file
-        remove_file(&output_filename).ok();
-        
         // All good
         Ok((status, solution))
     }
 }
 
+impl ModelSolver for SolverCbcCmd {}
+
 #[cfg(test)]
 mod tests {
 
+    use std::fs;
+    use std::thread;
+    use std::time::{Duration, Instant};
     use serial_test::serial;
+    use tempfile::{NamedTempFile, TempDir};
+    #[cfg(unix)]
+    use std::os::unix::fs::PermissionsExt;
 
     use crate::matrix::coo::CooMat;
     use crate::problem::base::Problem;
     use crate::problem::lp::ProblemLp;
     use crate::problem::milp::ProblemMilp;
     use crate::solver::base::{Solver, SolverParam, SolverStatus};
+    use crate::solver::cmd::{SolverCmd, SolverCmdCancel};
     use crate::solver::cbc_cmd::SolverCbcCmd;
     use crate::assert_vec_approx_eq;
 
+    fn milp_construct() -> ProblemMilp {
+
+        // Sample problem 
+        // min        -x0 - x1 
+        // subject to -2*x0 +  2*x1 + x2 == 1
+        //            -8*x0 + 10*x1 + x3 ==  13
+        //            x2 <= 0
+        //            x3 >= 0
+        //            x0 integer
+        //            x1 integer
+
+        ProblemMilp::new(
+            vec![-1.,-1., 0., 0.],
+            CooMat::new(
+                (2, 4),
+                vec![0,0,0,1,1,1],
+                vec![0,1,2,0,1,3],
+                vec![-2.,2.,1.,-8.,10.,1.]),
+            vec![1.,13.],
+            vec![-1e8,-1e8,-1e8,0.],
+            vec![1e8,1e8,0.,1e8],
+            vec![true, true, false, false],
+            None,
+        )
+    }
+
+    #[test]
+    fn cbc_read_sol_file_time_limit() {
+
+        let p = milp_construct();
+        let f = NamedTempFile::new().unwrap();
+        let fname = f.path().to_str().unwrap();
+
+        // Incumbent available
+        fs::write(fname, 
+                  "Stopped on time - objective value -2.00000000\n\
+                         0 c_0                       1                      0\n\
+                         1 c_1                      13                      0\n\
+                         0 x_0                       0                     -1\n\
+                         1 x_1                       2                     -1\n\
+                         2 x_2                      -3                      0\n\
+                         3 x_3                      -7                      0\n").unwrap();
+        let (status, solution) = SolverCbcCmd::read_sol_file(fname, &p, true).unwrap();
+        assert_eq!(status, SolverStatus::TimeLimit);
+        assert_vec_approx_eq!(solution.x, &vec![0., 2., -3., -7.], epsilon=0.);
+
+        // Iteration limit
+        fs::write(fname, 
+                  "Stopped on iterations - objective value -3.00000000\n\
+                         0 x_0                       1                     -1\n\
+                         1 x_1                       2                     -1\n").unwrap();
+        let (status, solution) = SolverCbcCmd::read_sol_file(fname, &p, true).unwrap();
+        assert_eq!(status, SolverStatus::IterationLimit);
+        assert_vec_approx_eq!(solution.x, &vec![1., 2., 0., 0.], epsilon=0.);
+
+        // Node limit
+        fs::write(fname, 
+                  "Stopped on nodes - objective value -2.00000000\n\
+                         0 x_0                       0                     -1\n\
+                         1 x_1                       2                     -1\n").unwrap();
+        let (status, solution) = SolverCbcCmd::read_sol_file(fname, &p, true).unwrap();
+        assert_eq!(status, SolverStatus::IterationLimit);
+        assert_vec_approx_eq!(solution.x, &vec![0., 2., 0., 0.], epsilon=0.);
+
+        // No incumbent
+        fs::write(fname, 
+                  "Stopped on time (no integer solution - continuous used) - objective value -3.5\n\
+                         0 x_0                     1.5                     -1\n").unwrap();
+        let (status, solution) = SolverCbcCmd::read_sol_file(fname, &p, true).unwrap();
+        assert_eq!(status, SolverStatus::TimeLimit);
+        assert_vec_approx_eq!(solution.x, &vec![0.;4], epsilon=0.);
+    }
+
     #[test]
+    fn cbc_write_mipstart_file() {
+
+        let f = NamedTempFile::new().unwrap();
+        let fname = f.path().to_str().unwrap();
+
+        SolverCbcCmd::write_mipstart_file(fname, &vec![1., -2.5, 0.]).unwrap();
+        
+        let lines: Vec<Vec<String>> = fs::read_to_string(fname).unwrap()
+                                                               .lines()
+                                                               .map(|l| l.split_ascii_whitespace()
+                                                                         .map(|s| s.to_string())
+                                                                         .collect())
+                                                               .collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1][0], "1");
+        assert_eq!(lines[1][1], "x_1");
+        assert_eq!(lines[1][2].parse::<f64>().unwrap(), -2.5);
+    }
+
+    #[test]
+    #[ignore]
     #[serial]
     fn cbc_solve_milp() {
 
@@ -287,6 +458,33 @@ mod tests {
     }
 
     #[test]
+    #[ignore]
+    #[serial]
+    fn cbc_solve_milp_params() {
+
+        let mut p = Problem::Milp(milp_construct());
+
+        let mut s = SolverCbcCmd::new();
+        s.set_param("logLevel", SolverParam::IntParam(0)).unwrap();
+        s.set_param("sec", SolverParam::FloatParam(60.)).unwrap();
+        s.set_param("ratioGap", SolverParam::FloatParam(1e-6)).unwrap();
+        s.set_param("threads", SolverParam::IntParam(1)).unwrap();
+        s.set_param("maxNodes", SolverParam::IntParam(1000)).unwrap();
+        s.set_param("presolve", SolverParam::StrParam("off".to_string())).unwrap();
+        s.set_param("cuts", SolverParam::StrParam("root".to_string())).unwrap();
+        s.set_param("heuristics", SolverParam::StrParam("off".to_string())).unwrap();
+        assert!(s.set_param("sec", SolverParam::IntParam(60)).is_err());
+
+        let (status, solution) = s.solve(&mut p).unwrap();
+
+        assert_eq!(status, SolverStatus::Solved);
+        assert_vec_approx_eq!(solution.x, 
+                              &vec![1., 2., -1., 1.0], 
+                              epsilon=1e-8);
+    }
+
+    #[test]
+    #[ignore]
     #[serial]
     fn cbc_solve_lp() {
 
@@ -333,4 +531,58 @@ mod tests {
                               epsilon=1e-8);
 
     }
-}
\ No newline at end of file
+
+    #[cfg(unix)]
+    #[test]
+    fn cbc_solve_interrupt_stub() {
+
+        // Stub cbc that writes an incumbent to the solution file and hangs
+        let dir = TempDir::new().unwrap();
+        let stub = dir.path().join("cbc");
+        fs::write(&stub,
+                  "#!/bin/sh\n\
+                   for a; do out=$a; done\n\
+                   printf 'Stopped on time - objective value -3.00000000\\n\
+                   0 x_0 1 0\\n1 x_1 2 0\\n2 x_2 -1 0\\n3 x_3 1 0\\n' > \"$out\"\n\
+                   sleep 10\n").unwrap();
+        fs::set_permissions(&stub, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut s = SolverCbcCmd::new();
+        s.set_executable(stub.to_str().unwrap());
+        s.set_param("logLevel", SolverParam::IntParam(0)).unwrap();
+
+        // Timeout
+        s.set_param("timeout", SolverParam::FloatParam(0.5)).unwrap();
+        let start = Instant::now();
+        let (status, solution) = s.solve(&mut Problem::Milp(milp_construct())).unwrap();
+        assert!(start.elapsed().as_secs_f64() < 5.);
+        assert_eq!(status, SolverStatus::TimeLimit);
+        assert_vec_approx_eq!(solution.x, &vec![1., 2., -1., 1.], epsilon=0.);
+
+        // Cancellation
+        let token = SolverCmdCancel::new();
+        let other = token.clone();
+        s.set_param("timeout", SolverParam::FloatParam(f64::INFINITY)).unwrap();
+        s.set_cancel_token(&token);
+        let handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(500));
+            other.cancel();
+        });
+        let start = Instant::now();
+        let (status, solution) = s.solve(&mut Problem::Milp(milp_construct())).unwrap();
+        handle.join().unwrap();
+        assert!(start.elapsed().as_secs_f64() < 5.);
+        assert_eq!(status, SolverStatus::Interrupted);
+        assert_vec_approx_eq!(solution.x, &vec![1., 2., -1., 1.], epsilon=0.);
+
+        // Timeout before writing the solution file
+        fs::write(&stub, "#!/bin/sh
+sleep 10
+").unwrap();
+        token.reset();
+        s.set_param("timeout", SolverParam::FloatParam(0.5)).unwrap();
+        let (status, solution) = s.solve(&mut Problem::Milp(milp_construct())).unwrap();
+        assert_eq!(status, SolverStatus::Error);
+        assert_vec_approx_eq!(solution.x, &vec![0.;4], epsilon=0.);
+    }
+}
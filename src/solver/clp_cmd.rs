@@ -1,27 +1,47 @@
 //! Clp solver interface.
 
-use std::ffi::OsStr;
-use tempfile::Builder;
-use std::fs::remove_file;
-use std::process::{Command, Stdio};
+use std::fs::File;
+use std::io::prelude::*;
+use std::io::{self, BufReader};
 use simple_error::SimpleError;
 use std::collections::HashMap;
 
+use crate::model::model_solver::ModelSolver;
 use crate::solver::base::{Solver, 
                           SolverParam,
                           SolverStatus};
 use crate::solver::cbc_cmd::SolverCbcCmd;
+use crate::solver::cmd::{self, SolverCmd, SolverCmdCancel, SolverCmdDir, SolverCmdExecutable};
 use crate::problem::base::{Problem,
-                          ProblemSol};
-use crate::problem::milp::ProblemMilpIO;
+                           ProblemSol,
+                           ProblemSolRanging};
+use crate::problem::milp::{ProblemMilp,
+                           ProblemMilpIO};
 
 /// Interface to the optimization solver Clp from COIN-OR 
 /// that utilzes the command-line tool "clp". 
 /// 
-/// The command-line tool "clp" needs to be on the system path.
+/// The command-line tool "clp" needs to be on the system path, or its location
+/// needs to be given with [set_executable](../cmd/trait.SolverCmd.html#method.set_executable)
+/// or the environment variable "NUMOPT_CLP".
 /// 
 /// It can solve problems of type [ProblemLp](../../problem/lp/struct.ProblemLp.html). 
+///
+/// Parameters:
+/// * "logLevel" (integer): amount of output printed by clp.
+/// * "algorithm" (string): "auto", "primal", "dual" or "barrier".
+/// * "maxIterations" (integer): maximum number of iterations.
+/// * "seconds" (float): time limit in seconds.
+/// * "presolve" (string): "on" or "off".
+/// * "scaling" (string): "automatic", "off", "equilibrium", "geometric" or "dynamic".
+/// * "dualTolerance" (float): dual feasibility tolerance.
+/// * "basisIn" (string): name of basis file to read before solving (empty for none).
+/// * "basisOut" (string): name of basis file to write after solving (empty for none).
+/// * "ranging" (string): "yes" to obtain objective and right-hand-side ranging.
+/// * "timeout" (float): wall-clock time in seconds after which the solver process is killed.
 pub struct SolverClpCmd {
+    executable: SolverCmdExecutable,
+    cancel: SolverCmdCancel,
     parameters: HashMap<String, SolverParam>,
 }
 
@@ -32,11 +52,84 @@ impl SolverClpCmd {
 
         let mut parameters: HashMap<String, SolverParam> = HashMap::new();
         parameters.insert("logLevel".to_string(), SolverParam::IntParam(5));
+        parameters.insert("algorithm".to_string(), SolverParam::StrParam("auto".to_string()));
+        parameters.insert("maxIterations".to_string(), SolverParam::IntParam(2147483647));
+        parameters.insert("seconds".to_string(), SolverParam::FloatParam(1e8));
+        parameters.insert("presolve".to_string(), SolverParam::StrParam("on".to_string()));
+        parameters.insert("scaling".to_string(), SolverParam::StrParam("automatic".to_string()));
+        parameters.insert("dualTolerance".to_string(), SolverParam::FloatParam(1e-7));
+        parameters.insert("basisIn".to_string(), SolverParam::StrParam("".to_string()));
+        parameters.insert("basisOut".to_string(), SolverParam::StrParam("".to_string()));
+        parameters.insert("ranging".to_string(), SolverParam::StrParam("no".to_string()));
+        parameters.insert("timeout".to_string(), SolverParam::FloatParam(f64::INFINITY));
 
         Self {
+            executable: SolverCmdExecutable::new("clp", "NUMOPT_CLP", &["-quit"]),
+            cancel: SolverCmdCancel::new(),
             parameters: parameters,
         } 
     }
+
+    /// Reads clp ranging files written with printing options "objective" and "rhs".
+    ///
+    /// Each line of these files has the index, name, value and dual value 
+    /// of a variable or constraint followed by the amounts by which its objective 
+    /// coefficient (objective ranging) or right-hand-side value (rhs ranging)
+    /// can decrease and increase while the basis remains optimal.
+    pub fn read_ranging_files(obj_fname: &str, 
+                              rhs_fname: &str,
+                              p: &ProblemMilp) -> io::Result<ProblemSolRanging> {
+
+        let mut ranging = ProblemSolRanging {
+            c: p.c().iter().map(|c| (*c, *c)).collect(),
+            b: p.b().iter().map(|b| (*b, *b)).collect(),
+        };
+
+        for (fname, dtype_ranged) in [(obj_fname, "x"), (rhs_fname, "c")].iter() {
+            
+            let r = BufReader::new(File::open(fname)?);
+            for l in r.lines() {
+                let line = l?;
+                let tokens: Vec<&str> = line.split_ascii_whitespace().collect();
+
+                // Status and objective lines
+                if tokens.len() < 6 {
+                    continue;
+                }
+
+                let e = io::Error::other("bad ranging file");
+                let mut name_iter = tokens[1].split('_');
+                let dtype = match name_iter.next() {
+                    Some(s) => s,
+                    None => return Err(e)
+                };
+                let index: usize = match name_iter.next() {
+                    Some(s) => match s.parse() { Ok(n) => n, Err(_e) => return Err(e) },
+                    None => return Err(e)
+                };
+                if dtype != *dtype_ranged {
+                    continue;
+                }
+                let down: f64 = match tokens[4].parse() { Ok(f) => f, Err(_e) => return Err(e) };
+                let up: f64 = match tokens[5].parse() { Ok(f) => f, Err(_e) => return Err(e) };
+                let range = if dtype == "x" { &mut ranging.c } else { &mut ranging.b };
+                if index >= range.len() {
+                    return Err(e);
+                }
+                range[index] = (range[index].0 - down, range[index].1 + up);
+            }
+        }
+
+        Ok(ranging)
+    }
+}
+
+impl SolverCmd for SolverClpCmd {
+
+    fn executable(&self) -> &SolverCmdExecutable { &self.executable }
+    fn executable_mut(&mut self) -> &mut SolverCmdExecutable { &mut self.executable }
+    fn cancel_token(&self) -> &SolverCmdCancel { &self.cancel }
+    fn cancel_token_mut(&mut self) -> &mut SolverCmdCancel { &mut self.cancel }
 }
 
 impl Solver for SolverClpCmd {
@@ -52,98 +145,316 @@ impl Solver for SolverClpCmd {
             _ => return Err(SimpleError::new("problem type not supported"))
         };
 
-        // Input filename
-        let input_file = Builder::new()
-            .prefix("clp")
-            .suffix(".lp")
-            .tempfile();
-        let input_filename = match input_file {
-            Ok(f) => f.path().file_name().and_then(OsStr::to_str).unwrap().to_string(),
-            Err(_e) => return Err(SimpleError::new("failed to create input filename")),
+        // Parameters
+        let log_level = match self.get_param("logLevel") {
+            Some(SolverParam::IntParam(i)) => *i,
+            _ => return Err(SimpleError::new("unable to get parameter logLevel"))
         };
-
-        // Output filename
-        let output_file = Builder::new()
-            .prefix("clp")
-            .suffix(".sol")
-            .tempfile();
-        let output_filename = match output_file {
-            Ok(f) => f.path().file_name().and_then(OsStr::to_str).unwrap().to_string(),
-            Err(_e) => return Err(SimpleError::new("failed to create output filename")),
+        let algorithm = match self.get_param("algorithm") {
+            Some(SolverParam::StrParam(s)) => match s.as_ref() {
+                "auto" => "solve",
+                "primal" => "primalSimplex",
+                "dual" => "dualSimplex",
+                "barrier" => "barrier",
+                _ => return Err(SimpleError::new("invalid value of parameter algorithm"))
+            },
+            _ => return Err(SimpleError::new("unable to get parameter algorithm"))
+        };
+        let basis_in = match self.get_param("basisIn") {
+            Some(SolverParam::StrParam(s)) => s.clone(),
+            _ => return Err(SimpleError::new("unable to get parameter basisIn"))
         };
+        let basis_out = match self.get_param("basisOut") {
+            Some(SolverParam::StrParam(s)) => s.clone(),
+            _ => return Err(SimpleError::new("unable to get parameter basisOut"))
+        };
+        let ranging = match self.get_param("ranging") {
+            Some(SolverParam::StrParam(s)) => s == "yes",
+            _ => return Err(SimpleError::new("unable to get parameter ranging"))
+        };
+
+        // Working directory and filenames
+        let dir = SolverCmdDir::new("clp")?;
+        let input_filename = dir.file("problem.lp");
+        let output_filename = dir.file("problem.sol");
+        let obj_ranging_filename = dir.file("problem.obj");
+        let rhs_ranging_filename = dir.file("problem.rhs");
 
         // Write input file
         match p.as_mut_milp().write_to_lp_file(&input_filename) {
             Ok(()) => (),
-            Err(_e) => {
-                remove_file(&input_filename).ok();
-                remove_file(&output_filename).ok();
-                return Err(SimpleError::new("failed to write lp file"));
-            }
+            Err(_e) => return Err(SimpleError::new("failed to write lp file")),
         };
 
-        // Parameters
-        let log_level = match self.get_param("logLevel") {
-            Some(SolverParam::IntParam(i)) => i,
-            _ => return Err(SimpleError::new("unable to get parameter logLevel"))
-        };
+        // Arguments
+        let mut args: Vec<String> = vec![input_filename.clone()];
+        for name in ["logLevel",
+                     "maxIterations",
+                     "seconds",
+                     "presolve",
+                     "scaling",
+                     "dualTolerance"].iter() {
+            let value = match self.get_param(name) {
+                Some(SolverParam::IntParam(i)) => format!("{}", i),
+                Some(SolverParam::FloatParam(f)) => format!("{}", f),
+                Some(SolverParam::StrParam(s)) => s.clone(),
+                None => return Err(SimpleError::new(format!("unable to get parameter {}", name)))
+            };
+            args.push(name.to_string());
+            args.push(value);
+        }
+        if !basis_in.is_empty() {
+            args.push("basisIn".to_string());
+            args.push(basis_in);
+        }
+        args.push(algorithm.to_string());
+        if !basis_out.is_empty() {
+            args.push("basisOut".to_string());
+            args.push(basis_out);
+        }
+        args.extend(["printingOptions",
+                     "all",
+                     "solution"].iter().map(|s| s.to_string()));
+        args.push(output_filename.clone());
+        if ranging {
+            args.extend(["printingOptions",
+                         "objective",
+                         "solution"].iter().map(|s| s.to_string()));
+            args.push(obj_ranging_filename.clone());
+            args.extend(["printingOptions",
+                         "rhs",
+                         "solution"].iter().map(|s| s.to_string()));
+            args.push(rhs_ranging_filename.clone());
+        }
 
         // Call Clp command
-        match Command::new("clp")
-                      .stdout(if *log_level == 0 { Stdio::null() } else { Stdio::inherit() })
-                      .args(&[&input_filename, 
-                              "logLevel",
-                              format!("{}", log_level).as_ref(),
-                              "printingOptions",
-                              "all",
-                              "solve", 
-                              "solution",
-                              &output_filename])
-                      .spawn()
-                      .and_then(|mut cmd| cmd.wait()) {
-            Ok(_s) => (),
-            Err(_e) => {
-                remove_file(&input_filename).ok();
-                remove_file(&output_filename).ok();
-                return Err(SimpleError::new("failed executing clp command"));
-            }
-        }
-        
-        // Clean up input file
-        remove_file(&input_filename).ok();
+        let output = cmd::run(self.executable.command().args(&args),
+                              log_level > 0,
+                              self.timeout(),
+                              &self.cancel)?;
 
         // Read output file
-        let (status, solution) = match SolverCbcCmd::read_sol_file(&output_filename, 
-                                                                   p.as_mut_milp(), 
-                                                                   false) {
-            Ok((s, sol)) => (s, sol),
-            Err(_e) => {
-                remove_file(&output_filename).ok();
-                return Err(SimpleError::new("failed to read clp solution file"))
-            }
-        };
+        let (status, mut solution) = output.solution(SolverCbcCmd::read_sol_file(&output_filename,
+                                                                                 p.as_mut_milp(),
+                                                                                 false),
+                                                     || ProblemSol::new(p.nx(), p.na(), 0),
+                                                     "failed to read clp solution file")?;
+        solution.log = Some(output.log());
+
+        // Read ranging files
+        if ranging && status == SolverStatus::Solved {
+            solution.ranging = match Self::read_ranging_files(&obj_ranging_filename,
+                                                              &rhs_ranging_filename,
+                                                              p.as_mut_milp()) {
+                Ok(r) => Some(r),
+                Err(_e) => return Err(SimpleError::new("failed to read clp ranging files")),
+            };
+        }
 
-        // Clean up output file
-        remove_file(&output_filename).ok();
-        
         // All good
         Ok((status, solution))
     }
 }
 
+impl ModelSolver for SolverClpCmd {}
+
 #[cfg(test)]
 mod tests {
 
+    use std::fs;
     use serial_test::serial;
+    use tempfile::NamedTempFile;
 
     use crate::matrix::coo::CooMat;
     use crate::problem::base::Problem;
     use crate::problem::lp::ProblemLp;
     use crate::solver::base::{Solver, SolverParam, SolverStatus};
     use crate::solver::clp_cmd::SolverClpCmd;
+    use crate::solver::cbc_cmd::SolverCbcCmd;
     use crate::assert_vec_approx_eq;
 
+    fn lp_construct() -> ProblemLp {
+
+        // Sample problem 
+        // min        180*x0 + 160*x1 
+        // subject to 6*x0 +   x1 + x2 == 12
+        //            3*x0 +   x1 + x3 ==  8
+        //            4*x0 + 6*x1 + x4 == 24
+        //            0 <= x0 <= 5
+        //            0 <= x1 <= 5
+        //            x2 <= 0
+        //            x3 <= 0
+        //            x4 <= 0
+
+        ProblemLp::new(
+            vec![180.,160., 0., 0., 0.],
+            CooMat::new(
+                (3, 5),
+                vec![0,0,0,1,1,1,2,2,2],
+                vec![0,1,2,0,1,3,0,1,4],
+                vec![6.,1.,1.,3.,1.,1.,4.,6.,1.]),
+            vec![12.,8.,24.],
+            vec![0.,0.,-1e8,-1e8,-1e8],
+            vec![5.,5.,0.,0.,0.],
+            None,
+        )
+    }
+
+    #[test]
+    fn clp_read_ranging_files() {
+
+        let mut p = lp_construct();
+        let fobj = NamedTempFile::new().unwrap();
+        let frhs = NamedTempFile::new().unwrap();
+        let obj_fname = fobj.path().to_str().unwrap();
+        let rhs_fname = frhs.path().to_str().unwrap();
+
+        fs::write(obj_fname,
+                  "optimal\n\
+                   Objective value 765.71428571\n\
+                         0 c_0       12        0   1e+30   1e+30\n\
+                         0 x_0  1.7142857        0     20      140\n\
+                         1 x_1  2.8571429        0     40      20\n").unwrap();
+        fs::write(rhs_fname,
+                  "optimal\n\
+                   Objective value 765.71428571\n\
+                         1 c_1        8  31.428571     2      1\n\
+                         2 c_2       24  21.428571     4      8\n\
+                         2 x_2 -1.1428571        0  1e+30  1e+30\n").unwrap();
+
+        let r = SolverClpCmd::read_ranging_files(obj_fname, 
+                                                 rhs_fname, 
+                                                 p.as_mut_milp()).unwrap();
+        assert_eq!(r.c.len(), 5);
+        assert_eq!(r.b.len(), 3);
+        assert_eq!(r.c[0], (160., 320.));
+        assert_eq!(r.c[1], (120., 180.));
+        assert_eq!(r.c[2], (0., 0.));
+        assert_eq!(r.b[0], (12., 12.));
+        assert_eq!(r.b[1], (6., 9.));
+        assert_eq!(r.b[2], (20., 32.));
+    }
+
+    #[test]
+    #[ignore]
+    #[serial]
+    fn clp_solve_lp_ranging() {
+
+        // Checks the layout of the ranging files against the ranges of the
+        // optimal basis, in which rows 1 and 2 are active
+        let mut s = SolverClpCmd::new();
+        s.set_param("logLevel", SolverParam::IntParam(0)).unwrap();
+        s.set_param("presolve", SolverParam::StrParam("off".to_string())).unwrap();
+        s.set_param("ranging", SolverParam::StrParam("yes".to_string())).unwrap();
+        let (status, solution) = s.solve(&mut Problem::Lp(lp_construct())).unwrap();
+
+        assert_eq!(status, SolverStatus::Solved);
+        let r = solution.ranging.unwrap();
+        assert_vec_approx_eq!(vec![r.c[0].0, r.c[0].1], &vec![320./3., 480.], epsilon=1e-6);
+        assert_vec_approx_eq!(vec![r.c[1].0, r.c[1].1], &vec![60., 270.], epsilon=1e-6);
+        assert_vec_approx_eq!(vec![r.b[1].0, r.b[1].1], &vec![7.5, 47./3.], epsilon=1e-6);
+        assert_vec_approx_eq!(vec![r.b[2].0, r.b[2].1], &vec![32./3., 88./3.], epsilon=1e-6);
+    }
+
+    #[test]
+    fn clp_read_sol_file_limits() {
+
+        let mut p = lp_construct();
+        let f = NamedTempFile::new().unwrap();
+        let fname = f.path().to_str().unwrap();
+
+        // Iteration or time limit with best point
+        fs::write(fname,
+                  "stopped on iterations or time\n\
+                   Objective value 810\n\
+                         0 c_0             12            0\n\
+                         1 c_1              9           20\n\
+                         2 c_2             24           30\n\
+                         0 x_0            1.5            0\n\
+                         1 x_1              3            0\n\
+                         2 x_2              0          -10\n").unwrap();
+        let (status, solution) = SolverCbcCmd::read_sol_file(fname, p.as_mut_milp(), false).unwrap();
+        assert_eq!(status, SolverStatus::IterationLimit);
+        assert_vec_approx_eq!(solution.x, &vec![1.5, 3., 0., 0., 0.], epsilon=0.);
+        assert_vec_approx_eq!(solution.lam, &vec![0., 20., 30.], epsilon=0.);
+        assert_vec_approx_eq!(solution.mu, &vec![0., 0., 10., 0., 0.], epsilon=0.);
+
+        // Time limit
+        fs::write(fname,
+                  "stopped on time\n\
+                   Objective value 900\n\
+                         0 x_0              5            0\n").unwrap();
+        let (status, solution) = SolverCbcCmd::read_sol_file(fname, p.as_mut_milp(), false).unwrap();
+        assert_eq!(status, SolverStatus::TimeLimit);
+        assert_vec_approx_eq!(solution.x, &vec![5., 0., 0., 0., 0.], epsilon=0.);
+
+        // Other stops
+        fs::write(fname,
+                  "stopped on difficulties\n\
+                   Objective value 900\n").unwrap();
+        let (status, _solution) = SolverCbcCmd::read_sol_file(fname, p.as_mut_milp(), false).unwrap();
+        assert_eq!(status, SolverStatus::Error);
+    }
+
+    #[test]
+    #[ignore]
+    #[serial]
+    fn clp_solve_lp_params() {
+
+        for algorithm in ["primal", "dual", "barrier"].iter() {
+
+            let mut p = Problem::Lp(lp_construct());
+
+            let mut s = SolverClpCmd::new();
+            s.set_param("logLevel", SolverParam::IntParam(0)).unwrap();
+            s.set_param("algorithm", SolverParam::StrParam(algorithm.to_string())).unwrap();
+            s.set_param("maxIterations", SolverParam::IntParam(1000)).unwrap();
+            s.set_param("seconds", SolverParam::FloatParam(60.)).unwrap();
+            s.set_param("presolve", SolverParam::StrParam("off".to_string())).unwrap();
+            s.set_param("scaling", SolverParam::StrParam("geometric".to_string())).unwrap();
+            s.set_param("dualTolerance", SolverParam::FloatParam(1e-8)).unwrap();
+            s.set_param("ranging", SolverParam::StrParam("yes".to_string())).unwrap();
+            let (status, solution) = s.solve(&mut p).unwrap();
+
+            assert_eq!(status, SolverStatus::Solved);
+            assert_vec_approx_eq!(solution.x, 
+                                  &vec![1.7142857, 2.8571429, -1.1428571, 0., 0.], 
+                                  epsilon=1e-6);
+            let r = solution.ranging.unwrap();
+            assert_eq!(r.c.len(), 5);
+            assert_eq!(r.b.len(), 3);
+            assert!(r.c[0].0 <= 180. && 180. <= r.c[0].1);
+            assert!(r.b[1].0 <= 8. && 8. <= r.b[1].1);
+        }
+    }
+
+    #[test]
+    #[ignore]
+    #[serial]
+    fn clp_solve_lp_basis() {
+
+        let f = NamedTempFile::new().unwrap();
+        let basis_fname = f.path().to_str().unwrap();
+
+        let mut s = SolverClpCmd::new();
+        s.set_param("logLevel", SolverParam::IntParam(0)).unwrap();
+        s.set_param("basisOut", SolverParam::StrParam(basis_fname.to_string())).unwrap();
+        let (status, _solution) = s.solve(&mut Problem::Lp(lp_construct())).unwrap();
+        assert_eq!(status, SolverStatus::Solved);
+        assert!(fs::metadata(basis_fname).unwrap().len() > 0);
+
+        // Warm start
+        s.set_param("basisOut", SolverParam::StrParam("".to_string())).unwrap();
+        s.set_param("basisIn", SolverParam::StrParam(basis_fname.to_string())).unwrap();
+        let (status, solution) = s.solve(&mut Problem::Lp(lp_construct())).unwrap();
+        assert_eq!(status, SolverStatus::Solved);
+        assert_vec_approx_eq!(solution.x, 
+                              &vec![1.7142857, 2.8571429, -1.1428571, 0., 0.], 
+                              epsilon=1e-6);
+    }
+
     #[test]
+    #[ignore]
     #[serial]
     fn clp_solve_lp() {
 
@@ -0,0 +1,555 @@
+//! Execution of command-line solvers.
+
+use std::env;
+use std::thread;
+use std::io::prelude::*;
+use std::io::{self, BufReader};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use std::process::{Child, Command, Stdio};
+use tempfile::{Builder, TempDir};
+use simple_error::SimpleError;
+
+use crate::solver::base::{Solver, SolverParam, SolverStatus};
+use crate::problem::base::ProblemSol;
+
+/// Number of lines of solver output included in error messages.
+const ERROR_LINES: usize = 20;
+
+/// Interval for checking timeout and cancellation of solver runs.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Executable of a command-line solver.
+///
+/// The executable is an explicitly set path if there is one, otherwise
+/// the value of the solver's environment variable (e.g., "NUMOPT_CBC")
+/// if it is set, and otherwise the default executable name, which
+/// needs to be on the system path.
+#[derive(Clone)]
+pub struct SolverCmdExecutable {
+    name: String,
+    env_var: String,
+    path: Option<String>,
+    version_args: Vec<String>,
+}
+
+/// Cancellation token of command-line solvers. Clones of a token share
+/// its state, so a solve can be cancelled from another thread.
+#[derive(Clone, Default)]
+pub struct SolverCmdCancel {
+    cancelled: Arc<AtomicBool>,
+}
+
+/// A trait for command-line solvers.
+pub trait SolverCmd: Solver {
+
+    /// Gets solver executable.
+    fn executable(&self) -> &SolverCmdExecutable;
+
+    /// Gets mutable reference of solver executable.
+    fn executable_mut(&mut self) -> &mut SolverCmdExecutable;
+
+    /// Sets explicit path of solver executable.
+    fn set_executable(&mut self, path: &str) { self.executable_mut().set_path(path) }
+
+    /// Determines whether the solver executable can be run.
+    fn is_available(&self) -> bool { self.version().is_some() }
+
+    /// Gets version of solver executable, parsed from its banner.
+    fn version(&self) -> Option<String> { self.executable().version() }
+
+    /// Gets cancellation token.
+    fn cancel_token(&self) -> &SolverCmdCancel;
+
+    /// Gets mutable reference of cancellation token.
+    fn cancel_token_mut(&mut self) -> &mut SolverCmdCancel;
+
+    /// Sets cancellation token. Cancelling the token (or any of its clones)
+    /// kills the solver process and makes the solve return
+    /// [Interrupted](../base/enum.SolverStatus.html#variant.Interrupted).
+    fn set_cancel_token(&mut self, token: &SolverCmdCancel) { *self.cancel_token_mut() = token.clone() }
+
+    /// Gets wall-clock timeout in seconds from the "timeout" parameter.
+    fn timeout(&self) -> f64 {
+        match self.get_param("timeout") {
+            Some(SolverParam::FloatParam(t)) => *t,
+            _ => f64::INFINITY,
+        }
+    }
+}
+
+/// Private working directory of a command-line solver run.
+/// The directory and its files are removed when this is dropped,
+/// so concurrent solves do not share files.
+pub struct SolverCmdDir {
+    dir: TempDir,
+}
+
+/// Captured output of a command-line solver run.
+pub struct SolverCmdOutput {
+
+    /// Standard output.
+    pub stdout: String,
+
+    /// Standard error.
+    pub stderr: String,
+
+    /// Status of solver run if it was killed due to a timeout
+    /// ([TimeLimit](../base/enum.SolverStatus.html#variant.TimeLimit)) or
+    /// cancellation ([Interrupted](../base/enum.SolverStatus.html#variant.Interrupted)).
+    pub interrupt: Option<SolverStatus>,
+}
+
+impl SolverCmdExecutable {
+
+    /// Creates new solver executable with a given default name, environment
+    /// variable, and arguments that make the executable print its version.
+    pub fn new(name: &str, env_var: &str, version_args: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            env_var: env_var.to_string(),
+            path: None,
+            version_args: version_args.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    /// Gets default executable name.
+    pub fn name(&self) -> &str { &self.name }
+
+    /// Gets name of environment variable with executable path.
+    pub fn env_var(&self) -> &str { &self.env_var }
+
+    /// Sets explicit executable path.
+    pub fn set_path(&mut self, path: &str) { self.path = Some(path.to_string()) }
+
+    /// Gets executable path to use.
+    pub fn path(&self) -> String {
+        match &self.path {
+            Some(p) => p.clone(),
+            None => match env::var(&self.env_var) {
+                Ok(p) if !p.is_empty() => p,
+                _ => self.name.clone(),
+            }
+        }
+    }
+
+    /// Creates command for running the executable.
+    pub fn command(&self) -> Command {
+        Command::new(self.path())
+    }
+
+    /// Gets version of executable, parsed from its banner. Returns None
+    /// if the executable cannot be run or no version is found.
+    pub fn version(&self) -> Option<String> {
+        let output = self.command()
+                         .args(&self.version_args)
+                         .stdin(Stdio::null())
+                         .output()
+                         .ok()?;
+        let banner = format!("{}{}",
+                             String::from_utf8_lossy(&output.stdout),
+                             String::from_utf8_lossy(&output.stderr));
+        parse_version(&banner)
+    }
+}
+
+/// Parses version number, e.g., "2.10.5" or "v4.65", from solver banner.
+pub fn parse_version(banner: &str) -> Option<String> {
+    for token in banner.split(|c: char| c.is_whitespace() || c == ',' || c == ':' || c == '(') {
+        let t = token.trim_start_matches(&['v', 'V'][..]).trim_end_matches('.');
+        let parts: Vec<&str> = t.split('.').collect();
+        if parts.len() >= 2 && 
+           parts.iter().all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit())) {
+            return Some(t.to_string());
+        }
+    }
+    None
+}
+
+impl SolverCmdCancel {
+
+    /// Creates new cancellation token.
+    pub fn new() -> Self { Self::default() }
+
+    /// Cancels solves using this token.
+    pub fn cancel(&self) { self.cancelled.store(true, Ordering::SeqCst) }
+
+    /// Clears cancellation so that the token can be used for new solves.
+    pub fn reset(&self) { self.cancelled.store(false, Ordering::SeqCst) }
+
+    /// Determines whether the token has been cancelled.
+    pub fn is_cancelled(&self) -> bool { self.cancelled.load(Ordering::SeqCst) }
+}
+
+impl SolverCmdDir {
+
+    /// Creates new private working directory in the system temporary directory.
+    pub fn new(prefix: &str) -> Result<Self, SimpleError> {
+        match Builder::new().prefix(prefix).tempdir() {
+            Ok(dir) => Ok(Self { dir: dir }),
+            Err(_e) => Err(SimpleError::new("failed to create temporary directory")),
+        }
+    }
+
+    /// Gets absolute path of file with a given name in the working directory.
+    pub fn file(&self, name: &str) -> String {
+        self.dir.path().join(name).to_string_lossy().into_owned()
+    }
+}
+
+impl SolverCmdOutput {
+
+    /// Gets standard output followed by standard error.
+    pub fn log(&self) -> String {
+        format!("{}{}", self.stdout, self.stderr)
+    }
+
+    /// Gets last lines of standard output followed by standard error.
+    pub fn tail(&self, num_lines: usize) -> String {
+        let log = self.log();
+        let lines: Vec<&str> = log.lines().collect();
+        lines[lines.len().saturating_sub(num_lines)..].join("\n")
+    }
+
+    /// Gets status and solution of the solver run from the result of reading its
+    /// solution file. If the run was killed due to a timeout or cancellation, the
+    /// status of the interruption is used only if a solution with a point was read.
+    /// Otherwise, the status is Error, with the given empty solution if no solution
+    /// was read. If the run was not interrupted, failing to read the solution file
+    /// is an error with the given message.
+    pub fn solution<F>(&self,
+                       read: io::Result<(SolverStatus, ProblemSol)>,
+                       empty: F,
+                       msg: &str) -> Result<(SolverStatus, ProblemSol), SimpleError>
+                       where F: FnOnce() -> ProblemSol {
+        match (read, self.interrupt) {
+            (Ok((status, solution)), None) => Ok((status, solution)),
+            (Ok((status, solution)), Some(_s)) if status == SolverStatus::Error ||
+                                                  status == SolverStatus::Infeasible => Ok((status, solution)),
+            (Ok((_status, solution)), Some(s)) => Ok((s, solution)),
+            (Err(_e), Some(_s)) => Ok((SolverStatus::Error, empty())),
+            (Err(_e), None) => Err(SimpleError::new(msg)),
+        }
+    }
+}
+
+/// Reads stream in a separate thread, optionally echoing it.
+fn capture<R: Read + Send + 'static>(r: R, echo: bool, stderr: bool) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut out = String::new();
+        let mut reader = BufReader::new(r);
+        let mut buf: Vec<u8> = Vec::new();
+        loop {
+            buf.clear();
+            match reader.read_until(b'\n', &mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {
+                    let s = String::from_utf8_lossy(&buf);
+                    if echo && stderr {
+                        eprint!("{}", s);
+                    }
+                    else if echo {
+                        print!("{}", s);
+                        io::stdout().flush().ok();
+                    }
+                    out.push_str(&s);
+                }
+            }
+        }
+        out
+    })
+}
+
+/// Kills solver process, along with any processes it started.
+#[cfg(unix)]
+fn kill(child: &mut Child) {
+    unsafe { libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL); }
+    child.kill().ok();
+}
+
+/// Kills solver process.
+#[cfg(not(unix))]
+fn kill(child: &mut Child) {
+    child.kill().ok();
+}
+
+/// Runs command-line solver, capturing its standard output and error
+/// and optionally echoing them. Fails if the solver cannot be executed
+/// or exits with an unsuccessful status, in which case the error message
+/// includes the last lines of the solver output.
+///
+/// The solver is killed if it runs longer than the given timeout in seconds
+/// or if the given token is cancelled. The output then records the
+/// corresponding status in its "interrupt" field, and
+/// [solution](struct.SolverCmdOutput.html#method.solution) gives the
+/// status of the run.
+pub fn run(cmd: &mut Command,
+           echo: bool,
+           timeout: f64,
+           cancel: &SolverCmdCancel) -> Result<SolverCmdOutput, SimpleError> {
+
+    let program = cmd.get_program().to_string_lossy().into_owned();
+
+    // Own process group so that killing reaches subprocesses
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+
+    // Spawn
+    let mut child = match cmd.stdin(Stdio::null())
+                             .stdout(Stdio::piped())
+                             .stderr(Stdio::piped())
+                             .spawn() {
+        Ok(c) => c,
+        Err(_e) => return Err(SimpleError::new(format!("failed executing {} command", program))),
+    };
+
+    // Capture
+    let stdout = capture(child.stdout.take().unwrap(), echo, false);
+    let stderr = capture(child.stderr.take().unwrap(), echo, true);
+
+    // Wait
+    let start = Instant::now();
+    let mut interrupt: Option<SolverStatus> = None;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(s)) => break Ok(s),
+            Ok(None) => (),
+            Err(e) => break Err(e),
+        }
+        if cancel.is_cancelled() {
+            interrupt = Some(SolverStatus::Interrupted);
+        }
+        else if start.elapsed().as_secs_f64() >= timeout {
+            interrupt = Some(SolverStatus::TimeLimit);
+        }
+        if interrupt.is_some() {
+            kill(&mut child);
+            break child.wait();
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+    let output = SolverCmdOutput {
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+        interrupt: interrupt,
+    };
+
+    // Check status
+    match status {
+        Ok(_s) if output.interrupt.is_some() => Ok(output),
+        Ok(s) if s.success() => Ok(output),
+        Ok(s) => Err(SimpleError::new(format!("{} command failed ({})\n{}",
+                                              program,
+                                              s,
+                                              output.tail(ERROR_LINES)))),
+        Err(_e) => Err(SimpleError::new(format!("failed executing {} command", program))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::fs;
+    use std::env;
+    use std::thread;
+    use std::path::Path;
+    use std::process::Command;
+    use serial_test::serial;
+    use tempfile::TempDir;
+    #[cfg(unix)]
+    use std::os::unix::fs::PermissionsExt;
+
+    use super::*;
+
+    #[test]
+    fn cmd_parse_version() {
+
+        assert_eq!(parse_version("Welcome to the CBC MILP Solver \nVersion: 2.10.5 \nBuild Date: Jun  1 2021"),
+                   Some("2.10.5".to_string()));
+        assert_eq!(parse_version("GLPSOL: GLPK LP/MIP Solver, v4.65\n"), Some("4.65".to_string()));
+        assert_eq!(parse_version("HiGHS version 1.6.0\n"), Some("1.6.0".to_string()));
+        assert_eq!(parse_version("SCIP version 8.0.3 [precision: 8 byte]"), Some("8.0.3".to_string()));
+        assert_eq!(parse_version("Ipopt 3.14.4 (Linux x86_64), ASL(20190605)"), Some("3.14.4".to_string()));
+        assert_eq!(parse_version("command not found"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[serial]
+    fn cmd_executable() {
+
+        let dir = TempDir::new().unwrap();
+        let stub = dir.path().join("numopt-test-solver");
+        fs::write(&stub, "#!/bin/sh\necho \"Welcome to the Test Solver\"\necho \"Version: 1.2.3\"\n").unwrap();
+        fs::set_permissions(&stub, fs::Permissions::from_mode(0o755)).unwrap();
+        let stub_path = stub.to_str().unwrap();
+
+        // Default name
+        let mut e = SolverCmdExecutable::new("numopt-test-solver", "NUMOPT_TEST_SOLVER", &["-quit"]);
+        assert_eq!(e.name(), "numopt-test-solver");
+        assert_eq!(e.env_var(), "NUMOPT_TEST_SOLVER");
+        assert_eq!(e.path(), "numopt-test-solver");
+        assert_eq!(e.version(), None);
+
+        // Environment variable
+        env::set_var("NUMOPT_TEST_SOLVER", stub_path);
+        assert_eq!(e.path(), stub_path);
+        assert_eq!(e.version(), Some("1.2.3".to_string()));
+        env::remove_var("NUMOPT_TEST_SOLVER");
+
+        // Explicit path
+        e.set_path(stub_path);
+        assert_eq!(e.path(), stub_path);
+        assert_eq!(e.version(), Some("1.2.3".to_string()));
+        e.set_path("/nonexistent/numopt-test-solver");
+        assert_eq!(e.version(), None);
+    }
+
+    #[test]
+    fn cmd_dir() {
+
+        let fname: String;
+        {
+            let dir1 = SolverCmdDir::new("numopt").unwrap();
+            let dir2 = SolverCmdDir::new("numopt").unwrap();
+            fname = dir1.file("problem.lp");
+            assert!(Path::new(&fname).is_absolute());
+            assert_ne!(fname, dir2.file("problem.lp"));
+            fs::write(&fname, "test").unwrap();
+            assert!(Path::new(&fname).exists());
+        }
+        assert!(!Path::new(&fname).exists());
+    }
+
+    #[test]
+    fn cmd_run() {
+
+        let cancel = SolverCmdCancel::new();
+
+        // Success
+        let output = run(Command::new("sh").args(&["-c", "echo out; echo err >&2"]),
+                         false,
+                         f64::INFINITY,
+                         &cancel).unwrap();
+        assert_eq!(output.stdout, "out\n");
+        assert_eq!(output.stderr, "err\n");
+        assert_eq!(output.log(), "out\nerr\n");
+
+        // Failure
+        let err = run(Command::new("sh").args(&["-c", "echo bad input; exit 3"]),
+                      false,
+                      f64::INFINITY,
+                      &cancel).err().unwrap();
+        assert!(err.as_str().starts_with("sh command failed"));
+        assert!(err.as_str().ends_with("bad input"));
+
+        // Missing
+        let err = run(&mut Command::new("numopt-missing-solver"), false, f64::INFINITY, &cancel).err().unwrap();
+        assert_eq!(err.as_str(), "failed executing numopt-missing-solver command");
+    }
+
+    #[test]
+    fn cmd_run_timeout() {
+
+        let start = Instant::now();
+        let output = run(Command::new("sh").args(&["-c", "echo started; sleep 10; echo done"]),
+                         false,
+                         0.2,
+                         &SolverCmdCancel::new()).unwrap();
+        assert!(start.elapsed().as_secs_f64() < 5.);
+        assert_eq!(output.interrupt, Some(SolverStatus::TimeLimit));
+        assert_eq!(output.stdout, "started\n");
+
+        let output = run(Command::new("sh").args(&["-c", "echo done"]),
+                         false,
+                         10.,
+                         &SolverCmdCancel::new()).unwrap();
+        assert_eq!(output.interrupt, None);
+        assert_eq!(output.stdout, "done\n");
+    }
+
+    #[test]
+    fn cmd_run_cancel() {
+
+        let token = SolverCmdCancel::new();
+        let other = token.clone();
+        let handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            other.cancel();
+        });
+
+        let start = Instant::now();
+        let output = run(Command::new("sh").args(&["-c", "echo started; sleep 10; echo done"]),
+                         false,
+                         f64::INFINITY,
+                         &token).unwrap();
+        handle.join().unwrap();
+        assert!(start.elapsed().as_secs_f64() < 5.);
+        assert!(token.is_cancelled());
+        assert_eq!(output.interrupt, Some(SolverStatus::Interrupted));
+        assert_eq!(output.stdout, "started\n");
+
+        token.reset();
+        assert!(!token.is_cancelled());
+    }
+
+    #[test]
+    fn cmd_output_solution() {
+
+        let read = |status: SolverStatus| {
+            let mut solution = ProblemSol::new(2, 0, 0);
+            solution.x = vec![1., 2.];
+            Ok((status, solution))
+        };
+        let empty = || ProblemSol::new(2, 0, 0);
+        let bad = || Err(io::Error::other("bad solution file"));
+        let mut output = SolverCmdOutput {
+            stdout: String::new(),
+            stderr: String::new(),
+            interrupt: None,
+        };
+
+        // Completed
+        let (status, solution) = output.solution(read(SolverStatus::Solved), empty, "failed").unwrap();
+        assert_eq!(status, SolverStatus::Solved);
+        assert_eq!(solution.x, vec![1., 2.]);
+        assert_eq!(output.solution(bad(), empty, "failed").err().unwrap().to_string(), "failed");
+
+        // Killed with best point
+        output.interrupt = Some(SolverStatus::TimeLimit);
+        let (status, solution) = output.solution(read(SolverStatus::Solved), empty, "failed").unwrap();
+        assert_eq!(status, SolverStatus::TimeLimit);
+        assert_eq!(solution.x, vec![1., 2.]);
+
+        // Killed without point
+        let (status, _solution) = output.solution(read(SolverStatus::Error), empty, "failed").unwrap();
+        assert_eq!(status, SolverStatus::Error);
+        output.interrupt = Some(SolverStatus::Interrupted);
+        let (status, solution) = output.solution(bad(), empty, "failed").unwrap();
+        assert_eq!(status, SolverStatus::Error);
+        assert_eq!(solution.x, vec![0., 0.]);
+    }
+
+    #[test]
+    fn cmd_run_concurrent() {
+
+        let handles: Vec<thread::JoinHandle<()>> = (0..4).map(|i| {
+            thread::spawn(move || {
+                let dir = SolverCmdDir::new("numopt").unwrap();
+                let fname = dir.file("out.sol");
+                run(Command::new("sh").args(&["-c", &format!("echo {} > {}", i, fname)]),
+                    false,
+                    f64::INFINITY,
+                    &SolverCmdCancel::new()).unwrap();
+                assert_eq!(fs::read_to_string(&fname).unwrap(), format!("{}\n", i));
+            })
+        }).collect();
+        for h in handles {
+            h.join().unwrap();
+        }
+    }
+}
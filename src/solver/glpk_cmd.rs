@@ -0,0 +1,415 @@
+//! GLPK solver interface.
+
+use std::fs::File;
+use std::io::prelude::*;
+use simple_error::SimpleError;
+use std::io::{self, BufReader};
+use std::collections::HashMap;
+
+use crate::model::model_solver::ModelSolver;
+use crate::solver::base::{Solver,
+                          SolverParam,
+                          SolverStatus};
+use crate::solver::cmd::{self, SolverCmd, SolverCmdCancel, SolverCmdDir, SolverCmdExecutable};
+use crate::problem::base::{Problem,
+                           ProblemSol};
+use crate::problem::milp::{ProblemMilp,
+                           ProblemMilpIO};
+
+/// Interface to the GNU Linear Programming Kit (GLPK)
+/// that utilzes the command-line tool "glpsol".
+///
+/// The command-line tool "glpsol" needs to be on the system path, or its location
+/// needs to be given with [set_executable](../cmd/trait.SolverCmd.html#method.set_executable)
+/// or the environment variable "NUMOPT_GLPK".
+///
+/// It can solve problems of type [ProblemLp](../../problem/lp/struct.ProblemLp.html)
+/// and [ProblemMilp](../../problem/milp/struct.ProblemMilp.html).
+///
+/// Parameters:
+/// * "logLevel" (integer): zero to suppress the output of glpsol.
+/// * "tmlim" (integer): time limit in seconds (negative for no limit).
+/// * "mipgap" (float): relative gap at which to stop.
+/// * "presol" (string): "on" or "off".
+/// * "timeout" (float): wall-clock time in seconds after which the solver process is killed.
+pub struct SolverGlpkCmd {
+    executable: SolverCmdExecutable,
+    cancel: SolverCmdCancel,
+    parameters: HashMap<String, SolverParam>,
+}
+
+impl SolverGlpkCmd {
+
+    // Creates solver instance.
+    pub fn new() -> Self {
+
+        let mut parameters: HashMap<String, SolverParam> = HashMap::new();
+        parameters.insert("logLevel".to_string(), SolverParam::IntParam(1));
+        parameters.insert("tmlim".to_string(), SolverParam::IntParam(-1));
+        parameters.insert("mipgap".to_string(), SolverParam::FloatParam(0.));
+        parameters.insert("presol".to_string(), SolverParam::StrParam("on".to_string()));
+        parameters.insert("timeout".to_string(), SolverParam::FloatParam(f64::INFINITY));
+
+        Self {
+            executable: SolverCmdExecutable::new("glpsol", "NUMOPT_GLPK", &["--version"]),
+            cancel: SolverCmdCancel::new(),
+            parameters: parameters,
+        }
+    }
+
+    /// Reads glpsol raw solution file (written with option "--write").
+    ///
+    /// Basic solutions provide primal and dual values. Integer solutions
+    /// provide only primal values. Integer solutions that are feasible but not
+    /// proven optimal have status unknown.
+    pub fn read_sol_file(fname: &str, p: &ProblemMilp) -> io::Result<(SolverStatus, ProblemSol)> {
+
+        let mut status = SolverStatus::Error;
+        let mut solution = ProblemSol::new(p.nx(), p.na(), 0);
+        let f = match File::open(fname) {
+            Ok(ff) => ff,
+            Err(_e) => return Ok((status, solution))
+        };
+        let r = BufReader::new(f);
+        let e = || io::Error::other("bad solution file");
+        let parse_index = |s: Option<&str>| -> io::Result<usize> {
+            match s.map(|ss| ss.parse::<usize>()) {
+                Some(Ok(n)) if n > 0 => Ok(n-1),
+                _ => Err(e())
+            }
+        };
+        let parse_count = |s: Option<&str>| -> io::Result<usize> {
+            match s.map(|ss| ss.parse::<usize>()) {
+                Some(Ok(n)) => Ok(n),
+                _ => Err(e())
+            }
+        };
+        let parse_value = |s: Option<&str>| -> io::Result<f64> {
+            match s.map(|ss| ss.parse::<f64>()) {
+                Some(Ok(x)) => Ok(x),
+                _ => Err(e())
+            }
+        };
+
+        let mut mip = false;
+        let mut row_offset: usize = 0;
+        for l in r.lines() {
+            let line = l?;
+            let mut iter = line.split_ascii_whitespace();
+            match iter.next() {
+
+                // Solution
+                Some("s") => {
+                    mip = match iter.next() {
+                        Some("bas") => false,
+                        Some("mip") => true,
+                        _ => return Err(e())
+                    };
+                    let rows = parse_count(iter.next())?;
+                    if rows < p.na() || rows > p.na()+1 {
+                        return Err(e());
+                    }
+                    row_offset = rows - p.na();
+                    if parse_count(iter.next())? != p.nx() {
+                        return Err(e());
+                    }
+                    status = match (mip, iter.next(), iter.next()) {
+                        (false, Some("f"), Some("f")) => SolverStatus::Solved,
+                        (false, Some("n"), _) => SolverStatus::Infeasible,
+                        (false, Some(_), Some(_)) => SolverStatus::Unknown,
+                        (true, Some("o"), _) => SolverStatus::Solved,
+                        (true, Some("f"), _) => SolverStatus::Unknown,
+                        (true, Some("n"), _) => SolverStatus::Infeasible,
+                        (true, Some(_), _) => SolverStatus::Unknown,
+                        _ => return Err(e())
+                    };
+                },
+
+                // Constraint
+                Some("i") => {
+                    let index = parse_index(iter.next())?;
+                    if mip || index < row_offset {
+                        continue;
+                    }
+                    let index = index - row_offset;
+                    if index >= p.na() {
+                        return Err(e());
+                    }
+                    iter.next();
+                    parse_value(iter.next())?;
+                    solution.lam[index] = parse_value(iter.next())?;
+                },
+
+                // Variable
+                Some("j") => {
+                    let index = parse_index(iter.next())?;
+                    if index >= p.nx() {
+                        return Err(e());
+                    }
+                    if mip {
+                        solution.x[index] = parse_value(iter.next())?;
+                    }
+                    else {
+                        iter.next();
+                        solution.x[index] = parse_value(iter.next())?;
+                        let mul = parse_value(iter.next())?;
+                        if mul > 0. {
+                            solution.pi[index] = mul;
+                        }
+                        else {
+                            solution.mu[index] = -mul;
+                        }
+                    }
+                },
+
+                // Comments and end
+                _ => (),
+            }
+        }
+
+        Ok((status, solution))
+    }
+}
+
+impl SolverCmd for SolverGlpkCmd {
+
+    fn executable(&self) -> &SolverCmdExecutable { &self.executable }
+    fn executable_mut(&mut self) -> &mut SolverCmdExecutable { &mut self.executable }
+    fn cancel_token(&self) -> &SolverCmdCancel { &self.cancel }
+    fn cancel_token_mut(&mut self) -> &mut SolverCmdCancel { &mut self.cancel }
+}
+
+impl Solver for SolverGlpkCmd {
+
+    fn get_params(&self) -> &HashMap<String, SolverParam> { &self.parameters }
+    fn get_params_mut(&mut self) -> &mut HashMap<String, SolverParam> { &mut self.parameters }
+
+    fn solve(&self, problem: &mut Problem) -> Result<(SolverStatus, ProblemSol), SimpleError> {
+
+        // Get problem
+        let p  = match problem {
+            Problem::Milp(x) => x,
+            Problem::Lp(x) => x.as_mut_milp(),
+            _ => return Err(SimpleError::new("problem type not supported"))
+        };
+
+        // Parameters
+        let log_level = match self.get_param("logLevel") {
+            Some(SolverParam::IntParam(i)) => *i,
+            _ => return Err(SimpleError::new("unable to get parameter logLevel"))
+        };
+        let tmlim = match self.get_param("tmlim") {
+            Some(SolverParam::IntParam(i)) => *i,
+            _ => return Err(SimpleError::new("unable to get parameter tmlim"))
+        };
+        let mipgap = match self.get_param("mipgap") {
+            Some(SolverParam::FloatParam(f)) => *f,
+            _ => return Err(SimpleError::new("unable to get parameter mipgap"))
+        };
+        let presol = match self.get_param("presol") {
+            Some(SolverParam::StrParam(s)) => match s.as_ref() {
+                "on" => "--presol",
+                "off" => "--nopresol",
+                _ => return Err(SimpleError::new("invalid value of parameter presol"))
+            },
+            _ => return Err(SimpleError::new("unable to get parameter presol"))
+        };
+
+        // Working directory and filenames
+        let dir = SolverCmdDir::new("glpk")?;
+        let input_filename = dir.file("problem.mps");
+        let output_filename = dir.file("problem.sol");
+
+        // Write input file
+        match p.write_to_mps_file(&input_filename) {
+            Ok(()) => (),
+            Err(_e) => return Err(SimpleError::new("failed to write mps file")),
+        };
+
+        // Arguments
+        let mut args: Vec<String> = vec!["--freemps".to_string(),
+                                         input_filename.clone(),
+                                         presol.to_string(),
+                                         "--mipgap".to_string(),
+                                         format!("{}", mipgap)];
+        if tmlim >= 0 {
+            args.push("--tmlim".to_string());
+            args.push(format!("{}", tmlim));
+        }
+        args.extend(["--write".to_string(),
+                     output_filename.clone()].iter().cloned());
+
+        // Call glpsol command
+        let output = cmd::run(self.executable.command().args(&args),
+                              log_level > 0,
+                              self.timeout(),
+                              &self.cancel)?;
+
+        // Read output file
+        let (mut status, mut solution) = output.solution(Self::read_sol_file(&output_filename, p),
+                                                         || ProblemSol::new(p.nx(), p.na(), 0),
+                                                         "failed to read glpsol solution file")?;
+
+        // Termination reason, unless killed due to timeout or cancellation
+        let log = output.log();
+        if output.interrupt.is_none() {
+            if status != SolverStatus::Solved && log.contains("TIME LIMIT EXCEEDED") {
+                status = SolverStatus::TimeLimit;
+            }
+            else if status == SolverStatus::Unknown && log.contains("MIP GAP TOLERANCE REACHED") {
+                status = SolverStatus::Solved;
+            }
+        }
+
+        solution.log = Some(log);
+
+        // All good
+        Ok((status, solution))
+    }
+}
+
+impl ModelSolver for SolverGlpkCmd {}
+
+#[cfg(test)]
+mod tests {
+
+    use std::fs;
+    use tempfile::{NamedTempFile, TempDir};
+    #[cfg(unix)]
+    use std::os::unix::fs::PermissionsExt;
+
+    use crate::matrix::coo::CooMat;
+    use crate::problem::base::Problem;
+    use crate::problem::lp::ProblemLp;
+    use crate::solver::base::{Solver, SolverParam, SolverStatus};
+    use crate::solver::cmd::SolverCmd;
+    use crate::solver::glpk_cmd::SolverGlpkCmd;
+    use crate::assert_vec_approx_eq;
+
+    const LP_SOL: &str = "c Problem:    \n\
+                          c Rows:       3\n\
+                          c Columns:    5\n\
+                          c Status:     OPTIMAL\n\
+                          c\n\
+                          s bas 3 5 f f 765.714285714286\n\
+                          i 1 s 12 0\n\
+                          i 2 s 8 31.4285714285714\n\
+                          i 3 s 24 21.4285714285714\n\
+                          j 1 b 1.71428571428571 0\n\
+                          j 2 b 2.85714285714286 0\n\
+                          j 3 b -1.14285714285714 0\n\
+                          j 4 u 0 -31.4285714285714\n\
+                          j 5 u 0 -21.4285714285714\n\
+                          e o f\n";
+
+    fn lp_construct() -> ProblemLp {
+
+        // Sample problem
+        // min        180*x0 + 160*x1
+        // subject to 6*x0 +   x1 + x2 == 12
+        //            3*x0 +   x1 + x3 ==  8
+        //            4*x0 + 6*x1 + x4 == 24
+        //            0 <= x0 <= 5
+        //            0 <= x1 <= 5
+        //            x2 <= 0
+        //            x3 <= 0
+        //            x4 <= 0
+
+        ProblemLp::new(
+            vec![180.,160., 0., 0., 0.],
+            CooMat::new(
+                (3, 5),
+                vec![0,0,0,1,1,1,2,2,2],
+                vec![0,1,2,0,1,3,0,1,4],
+                vec![6.,1.,1.,3.,1.,1.,4.,6.,1.]),
+            vec![12.,8.,24.],
+            vec![0.,0.,-1e8,-1e8,-1e8],
+            vec![5.,5.,0.,0.,0.],
+            None,
+        )
+    }
+
+    #[test]
+    fn glpk_read_sol_file() {
+
+        let mut p = lp_construct();
+        let f = NamedTempFile::new().unwrap();
+        let fname = f.path().to_str().unwrap();
+
+        // Basic solution
+        fs::write(fname, LP_SOL).unwrap();
+        let (status, solution) = SolverGlpkCmd::read_sol_file(fname, p.as_mut_milp()).unwrap();
+        assert_eq!(status, SolverStatus::Solved);
+        assert_vec_approx_eq!(solution.x,
+                              &vec![1.7142857, 2.8571429, -1.1428571, 0., 0.],
+                              epsilon=1e-6);
+        assert_vec_approx_eq!(solution.lam,
+                              &vec![0., 31.428571, 21.428571],
+                              epsilon=1e-6);
+        assert_vec_approx_eq!(solution.mu,
+                              &vec![0., 0., 0., 3.1428571e+01, 2.1428571e+01],
+                              epsilon=1e-6);
+        assert_vec_approx_eq!(solution.pi,
+                              &vec![0.;5],
+                              epsilon=1e-6);
+
+        // Infeasible basic solution
+        fs::write(fname, "s bas 3 5 n f 0\ne o f\n").unwrap();
+        let (status, _solution) = SolverGlpkCmd::read_sol_file(fname, p.as_mut_milp()).unwrap();
+        assert_eq!(status, SolverStatus::Infeasible);
+
+        // Feasible integer solution
+        fs::write(fname,
+                  "s mip 3 5 f 800\n\
+                   i 1 12\n\
+                   i 2 8\n\
+                   i 3 24\n\
+                   j 1 2\n\
+                   j 2 2\n\
+                   j 3 -2\n\
+                   j 4 -0\n\
+                   j 5 -4\n\
+                   e o f\n").unwrap();
+        let (status, solution) = SolverGlpkCmd::read_sol_file(fname, p.as_mut_milp()).unwrap();
+        assert_eq!(status, SolverStatus::Unknown);
+        assert_vec_approx_eq!(solution.x, &vec![2., 2., -2., 0., -4.], epsilon=0.);
+        assert_vec_approx_eq!(solution.lam, &vec![0.;3], epsilon=0.);
+
+        // Bad dimensions
+        fs::write(fname, "s bas 3 4 f f 0\ne o f\n").unwrap();
+        assert!(SolverGlpkCmd::read_sol_file(fname, p.as_mut_milp()).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn glpk_solve_lp_stub() {
+
+        // Stub glpsol that writes a fixed solution
+        let dir = TempDir::new().unwrap();
+        let sol = dir.path().join("lp.sol");
+        fs::write(&sol, LP_SOL).unwrap();
+        let stub = dir.path().join("glpsol");
+        fs::write(&stub,
+                  format!("#!/bin/sh\n\
+                           while [ \"$#\" -gt 0 ]; do\n\
+                           \x20 if [ \"$1\" = \"--write\" ]; then cp {} \"$2\"; fi\n\
+                           \x20 shift\n\
+                           done\n", sol.to_str().unwrap())).unwrap();
+        fs::set_permissions(&stub, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut s = SolverGlpkCmd::new();
+        s.set_executable(stub.to_str().unwrap());
+        s.set_param("logLevel", SolverParam::IntParam(0)).unwrap();
+        s.set_param("tmlim", SolverParam::IntParam(10)).unwrap();
+        s.set_param("mipgap", SolverParam::FloatParam(1e-4)).unwrap();
+        s.set_param("presol", SolverParam::StrParam("off".to_string())).unwrap();
+        let (status, solution) = s.solve(&mut Problem::Lp(lp_construct())).unwrap();
+        assert_eq!(status, SolverStatus::Solved);
+        assert_vec_approx_eq!(solution.x,
+                              &vec![1.7142857, 2.8571429, -1.1428571, 0., 0.],
+                              epsilon=1e-6);
+        assert_vec_approx_eq!(solution.lam,
+                              &vec![0., 31.428571, 21.428571],
+                              epsilon=1e-6);
+    }
+}
@@ -0,0 +1,509 @@
+//! HiGHS solver interface.
+
+use std::fs::File;
+use std::io::prelude::*;
+use simple_error::SimpleError;
+use std::io::{self, BufReader, BufWriter};
+use std::collections::HashMap;
+
+use crate::model::model_solver::ModelSolver;
+use crate::solver::base::{Solver,
+                          SolverParam,
+                          SolverStatus};
+use crate::solver::cmd::{self, SolverCmd, SolverCmdCancel, SolverCmdDir, SolverCmdExecutable};
+use crate::problem::base::{Problem,
+                           ProblemSol,
+                           ProblemSolBasis,
+                           BasisStatus};
+use crate::problem::milp::{ProblemMilp,
+                           ProblemMilpIO};
+
+/// Interface to the optimization solver HiGHS
+/// that utilzes the command-line tool "highs".
+///
+/// The command-line tool "highs" needs to be on the system path, or its location
+/// needs to be given with [set_executable](../cmd/trait.SolverCmd.html#method.set_executable)
+/// or the environment variable "NUMOPT_HIGHS".
+///
+/// It can solve problems of type [ProblemLp](../../problem/lp/struct.ProblemLp.html)
+/// and [ProblemMilp](../../problem/milp/struct.ProblemMilp.html).
+///
+/// Parameters (written to a HiGHS options file):
+/// * "output_flag" (string): "true" or "false".
+/// * "time_limit" (float): time limit in seconds.
+/// * "mip_rel_gap" (float): relative gap at which to stop.
+/// * "threads" (integer): number of threads (zero for automatic).
+/// * "presolve" (string): "choose", "on" or "off".
+/// * "solver" (string): "choose", "simplex" or "ipm".
+/// * "timeout" (float): wall-clock time in seconds after which the solver process is killed
+///   (not written to the options file).
+pub struct SolverHighsCmd {
+    executable: SolverCmdExecutable,
+    cancel: SolverCmdCancel,
+    parameters: HashMap<String, SolverParam>,
+}
+
+impl SolverHighsCmd {
+
+    // Creates solver instance.
+    pub fn new() -> Self {
+
+        let mut parameters: HashMap<String, SolverParam> = HashMap::new();
+        parameters.insert("output_flag".to_string(), SolverParam::StrParam("true".to_string()));
+        parameters.insert("time_limit".to_string(), SolverParam::FloatParam(f64::INFINITY));
+        parameters.insert("mip_rel_gap".to_string(), SolverParam::FloatParam(1e-4));
+        parameters.insert("threads".to_string(), SolverParam::IntParam(0));
+        parameters.insert("presolve".to_string(), SolverParam::StrParam("choose".to_string()));
+        parameters.insert("solver".to_string(), SolverParam::StrParam("choose".to_string()));
+        parameters.insert("timeout".to_string(), SolverParam::FloatParam(f64::INFINITY));
+
+        Self {
+            executable: SolverCmdExecutable::new("highs", "NUMOPT_HIGHS", &["--version"]),
+            cancel: SolverCmdCancel::new(),
+            parameters: parameters,
+        }
+    }
+
+    /// Writes HiGHS options file with the solver parameters.
+    pub fn write_options_file(&self, fname: &str) -> io::Result<()> {
+
+        let f = File::create(fname)?;
+        let mut w = BufWriter::new(f);
+
+        let mut names: Vec<&String> = self.parameters.keys().filter(|n| *n != "timeout").collect();
+        names.sort();
+        for name in names {
+            match self.parameters.get(name).unwrap() {
+                SolverParam::IntParam(i) => writeln!(w, "{} = {}", name, i)?,
+                SolverParam::FloatParam(f) => writeln!(w, "{} = {}", name, f)?,
+                SolverParam::StrParam(s) => writeln!(w, "{} = {}", name, s)?,
+            }
+        }
+
+        w.flush()?;
+
+        Ok(())
+    }
+
+    /// Reads HiGHS solution file written in raw style.
+    pub fn read_sol_file(fname: &str, p: &ProblemMilp) -> io::Result<(SolverStatus, ProblemSol)> {
+
+        let mut status = SolverStatus::Error;
+        let mut solution = ProblemSol::new(p.nx(), p.na(), 0);
+        let f = match File::open(fname) {
+            Ok(ff) => ff,
+            Err(_e) => return Ok((status, solution))
+        };
+        let r = BufReader::new(f);
+        let e = || io::Error::other("bad solution file");
+
+        // Sections
+        #[derive(PartialEq)]
+        enum Section { Status, Primal, Dual, Basis, Other }
+        let mut section = Section::Other;
+        let mut basis_cols = true;
+        let mut basis = ProblemSolBasis {
+            x: vec![BasisStatus::Nonbasic; p.nx()],
+            a: vec![BasisStatus::Nonbasic; p.na()],
+        };
+        let mut basis_valid = false;
+
+        for l in r.lines() {
+            let line = l?;
+            let line = line.trim();
+
+            // Section headers
+            if line == "Model status" {
+                section = Section::Status;
+                continue;
+            }
+            else if line == "# Primal solution values" {
+                section = Section::Primal;
+                continue;
+            }
+            else if line == "# Dual solution values" {
+                section = Section::Dual;
+                continue;
+            }
+            else if line == "# Basis" {
+                section = Section::Basis;
+                continue;
+            }
+            else if line.is_empty() {
+                continue;
+            }
+
+            match section {
+
+                // Model status
+                Section::Status => {
+                    status = match line {
+                        "Optimal" => SolverStatus::Solved,
+                        "Infeasible" => SolverStatus::Infeasible,
+                        "Time limit reached" => SolverStatus::TimeLimit,
+                        "Iteration limit reached" => SolverStatus::IterationLimit,
+                        _ => SolverStatus::Unknown,
+                    };
+                    section = Section::Other;
+                },
+
+                // Primal and dual values
+                Section::Primal | Section::Dual => {
+                    let mut iter = line.split_ascii_whitespace();
+                    let name = match iter.next() {
+                        Some(s) => s,
+                        None => return Err(e())
+                    };
+                    if name == "#" || name == "Objective" || iter.clone().next().is_none() {
+                        continue;
+                    }
+                    let value: f64 = match iter.next().map(|s| s.parse()) {
+                        Some(Ok(x)) => x,
+                        _ => return Err(e())
+                    };
+                    let mut name_iter = name.split('_');
+                    let dtype = name_iter.next();
+                    let index: usize = match name_iter.next().map(|s| s.parse()) {
+                        Some(Ok(n)) => n,
+                        _ => return Err(e())
+                    };
+                    match (dtype, &section) {
+                        (Some("x"), Section::Primal) if index < p.nx() => {
+                            solution.x[index] = value;
+                        },
+                        (Some("x"), Section::Dual) if index < p.nx() => {
+                            if value > 0. {
+                                solution.pi[index] = value;
+                            }
+                            else {
+                                solution.mu[index] = -value;
+                            }
+                        },
+                        (Some("c"), Section::Primal) if index < p.na() => (),
+                        (Some("c"), Section::Dual) if index < p.na() => {
+                            solution.lam[index] = value;
+                        },
+                        _ => return Err(e())
+                    }
+                },
+
+                // Basis
+                Section::Basis => {
+                    if line.starts_with("HiGHS") || line == "None" {
+                        continue;
+                    }
+                    else if line == "Valid" {
+                        basis_valid = true;
+                        continue;
+                    }
+                    else if line.starts_with("# Columns") {
+                        basis_cols = true;
+                        continue;
+                    }
+                    else if line.starts_with("# Rows") {
+                        basis_cols = false;
+                        continue;
+                    }
+                    let dest = if basis_cols { &mut basis.x } else { &mut basis.a };
+                    let codes: Vec<&str> = line.split_ascii_whitespace().collect();
+                    if codes.len() != dest.len() {
+                        return Err(e());
+                    }
+                    for (b, code) in dest.iter_mut().zip(codes.iter()) {
+                        *b = match *code {
+                            "0" => BasisStatus::AtLower,
+                            "1" => BasisStatus::Basic,
+                            "2" => BasisStatus::AtUpper,
+                            "3" => BasisStatus::Free,
+                            "4" => BasisStatus::Nonbasic,
+                            _ => return Err(e())
+                        };
+                    }
+                },
+
+                Section::Other => (),
+            }
+        }
+
+        if basis_valid {
+            solution.basis = Some(basis);
+        }
+
+        Ok((status, solution))
+    }
+}
+
+impl SolverCmd for SolverHighsCmd {
+
+    fn executable(&self) -> &SolverCmdExecutable { &self.executable }
+    fn executable_mut(&mut self) -> &mut SolverCmdExecutable { &mut self.executable }
+    fn cancel_token(&self) -> &SolverCmdCancel { &self.cancel }
+    fn cancel_token_mut(&mut self) -> &mut SolverCmdCancel { &mut self.cancel }
+}
+
+impl Solver for SolverHighsCmd {
+
+    fn get_params(&self) -> &HashMap<String, SolverParam> { &self.parameters }
+    fn get_params_mut(&mut self) -> &mut HashMap<String, SolverParam> { &mut self.parameters }
+
+    fn solve(&self, problem: &mut Problem) -> Result<(SolverStatus, ProblemSol), SimpleError> {
+
+        // Get problem
+        let p  = match problem {
+            Problem::Milp(x) => x,
+            Problem::Lp(x) => x.as_mut_milp(),
+            _ => return Err(SimpleError::new("problem type not supported"))
+        };
+
+        // Parameters
+        let output_flag = match self.get_param("output_flag") {
+            Some(SolverParam::StrParam(s)) => s == "true",
+            _ => return Err(SimpleError::new("unable to get parameter output_flag"))
+        };
+
+        // Working directory and filenames
+        let dir = SolverCmdDir::new("highs")?;
+        let input_filename = dir.file("problem.mps");
+        let options_filename = dir.file("problem.opt");
+        let output_filename = dir.file("problem.sol");
+
+        // Write input files
+        match p.write_to_mps_file(&input_filename) {
+            Ok(()) => (),
+            Err(_e) => return Err(SimpleError::new("failed to write mps file")),
+        };
+        match self.write_options_file(&options_filename) {
+            Ok(()) => (),
+            Err(_e) => return Err(SimpleError::new("failed to write options file")),
+        };
+
+        // Call HiGHS command
+        let output = cmd::run(self.executable.command()
+                                      .args(["--model_file",
+                                             &input_filename,
+                                             "--options_file",
+                                             &options_filename,
+                                             "--solution_file",
+                                             &output_filename]),
+                              output_flag,
+                              self.timeout(),
+                              &self.cancel)?;
+
+        // Read output file
+        let (status, mut solution) = output.solution(Self::read_sol_file(&output_filename, p),
+                                                     || ProblemSol::new(p.nx(), p.na(), 0),
+                                                     "failed to read highs solution file")?;
+        solution.log = Some(output.log());
+
+        // All good
+        Ok((status, solution))
+    }
+}
+
+impl ModelSolver for SolverHighsCmd {}
+
+#[cfg(test)]
+mod tests {
+
+    use std::fs;
+    use tempfile::{NamedTempFile, TempDir};
+    #[cfg(unix)]
+    use std::os::unix::fs::PermissionsExt;
+
+    use crate::matrix::coo::CooMat;
+    use crate::problem::base::{Problem, BasisStatus};
+    use crate::problem::lp::ProblemLp;
+    use crate::solver::base::{Solver, SolverParam, SolverStatus};
+    use crate::solver::cmd::SolverCmd;
+    use crate::solver::highs_cmd::SolverHighsCmd;
+    use crate::assert_vec_approx_eq;
+
+    const LP_SOL: &str = "Model status\n\
+                          Optimal\n\
+                          \n\
+                          # Primal solution values\n\
+                          Feasible\n\
+                          Objective 765.7142857142857\n\
+                          # Columns 5\n\
+                          x_0 1.714285714285714\n\
+                          x_1 2.857142857142857\n\
+                          x_2 -1.142857142857143\n\
+                          x_3 0\n\
+                          x_4 0\n\
+                          # Rows 3\n\
+                          c_0 12\n\
+                          c_1 8\n\
+                          c_2 24\n\
+                          \n\
+                          # Dual solution values\n\
+                          Feasible\n\
+                          # Columns 5\n\
+                          x_0 0\n\
+                          x_1 0\n\
+                          x_2 0\n\
+                          x_3 -31.42857142857143\n\
+                          x_4 -21.42857142857143\n\
+                          # Rows 3\n\
+                          c_0 0\n\
+                          c_1 31.42857142857143\n\
+                          c_2 21.42857142857143\n\
+                          \n\
+                          # Basis\n\
+                          HiGHS v1\n\
+                          Valid\n\
+                          # Columns 5\n\
+                          1 1 1 2 2 \n\
+                          # Rows 3\n\
+                          0 0 0 \n";
+
+    fn lp_construct() -> ProblemLp {
+
+        // Sample problem
+        // min        180*x0 + 160*x1
+        // subject to 6*x0 +   x1 + x2 == 12
+        //            3*x0 +   x1 + x3 ==  8
+        //            4*x0 + 6*x1 + x4 == 24
+        //            0 <= x0 <= 5
+        //            0 <= x1 <= 5
+        //            x2 <= 0
+        //            x3 <= 0
+        //            x4 <= 0
+
+        ProblemLp::new(
+            vec![180.,160., 0., 0., 0.],
+            CooMat::new(
+                (3, 5),
+                vec![0,0,0,1,1,1,2,2,2],
+                vec![0,1,2,0,1,3,0,1,4],
+                vec![6.,1.,1.,3.,1.,1.,4.,6.,1.]),
+            vec![12.,8.,24.],
+            vec![0.,0.,-1e8,-1e8,-1e8],
+            vec![5.,5.,0.,0.,0.],
+            None,
+        )
+    }
+
+    #[test]
+    fn highs_write_options_file() {
+
+        let f = NamedTempFile::new().unwrap();
+        let fname = f.path().to_str().unwrap();
+
+        let mut s = SolverHighsCmd::new();
+        s.set_param("time_limit", SolverParam::FloatParam(10.)).unwrap();
+        s.set_param("threads", SolverParam::IntParam(2)).unwrap();
+        s.set_param("solver", SolverParam::StrParam("ipm".to_string())).unwrap();
+        s.write_options_file(fname).unwrap();
+
+        let lines: Vec<String> = fs::read_to_string(fname).unwrap()
+                                                          .lines()
+                                                          .map(|l| l.to_string())
+                                                          .collect();
+        assert_eq!(lines, vec!["mip_rel_gap = 0.0001",
+                               "output_flag = true",
+                               "presolve = choose",
+                               "solver = ipm",
+                               "threads = 2",
+                               "time_limit = 10"]);
+    }
+
+    #[test]
+    fn highs_read_sol_file() {
+
+        let mut p = lp_construct();
+        let f = NamedTempFile::new().unwrap();
+        let fname = f.path().to_str().unwrap();
+
+        // Lp
+        fs::write(fname, LP_SOL).unwrap();
+        let (status, solution) = SolverHighsCmd::read_sol_file(fname, p.as_mut_milp()).unwrap();
+        assert_eq!(status, SolverStatus::Solved);
+        assert_vec_approx_eq!(solution.x,
+                              &vec![1.7142857, 2.8571429, -1.1428571, 0., 0.],
+                              epsilon=1e-6);
+        assert_vec_approx_eq!(solution.lam,
+                              &vec![0., 31.428571, 21.428571],
+                              epsilon=1e-6);
+        assert_vec_approx_eq!(solution.mu,
+                              &vec![0., 0., 0., 3.1428571e+01, 2.1428571e+01],
+                              epsilon=1e-6);
+        assert_vec_approx_eq!(solution.pi,
+                              &vec![0.;5],
+                              epsilon=1e-6);
+        let basis = solution.basis.unwrap();
+        assert_eq!(basis.x, vec![BasisStatus::Basic,
+                                 BasisStatus::Basic,
+                                 BasisStatus::Basic,
+                                 BasisStatus::AtUpper,
+                                 BasisStatus::AtUpper]);
+        assert_eq!(basis.a, vec![BasisStatus::AtLower; 3]);
+
+        // Mip with time limit
+        fs::write(fname,
+                  "Model status\n\
+                   Time limit reached\n\
+                   \n\
+                   # Primal solution values\n\
+                   Feasible\n\
+                   Objective 800\n\
+                   # Columns 5\n\
+                   x_0 2\n\
+                   x_1 2\n\
+                   x_2 -2\n\
+                   x_3 0\n\
+                   x_4 -4\n\
+                   # Rows 3\n\
+                   c_0 12\n\
+                   c_1 8\n\
+                   c_2 24\n\
+                   \n\
+                   # Dual solution values\n\
+                   None\n\
+                   \n\
+                   # Basis\n\
+                   HiGHS v1\n\
+                   None\n").unwrap();
+        let (status, solution) = SolverHighsCmd::read_sol_file(fname, p.as_mut_milp()).unwrap();
+        assert_eq!(status, SolverStatus::TimeLimit);
+        assert_vec_approx_eq!(solution.x, &vec![2., 2., -2., 0., -4.], epsilon=0.);
+        assert!(solution.basis.is_none());
+
+        // Infeasible
+        fs::write(fname,
+                  "Model status\n\
+                   Infeasible\n\
+                   \n\
+                   # Primal solution values\n\
+                   None\n").unwrap();
+        let (status, _solution) = SolverHighsCmd::read_sol_file(fname, p.as_mut_milp()).unwrap();
+        assert_eq!(status, SolverStatus::Infeasible);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn highs_solve_lp_stub() {
+
+        // Stub highs that writes a fixed solution
+        let dir = TempDir::new().unwrap();
+        let sol = dir.path().join("lp.sol");
+        fs::write(&sol, LP_SOL).unwrap();
+        let stub = dir.path().join("highs");
+        fs::write(&stub,
+                  format!("#!/bin/sh\n\
+                           while [ \"$#\" -gt 0 ]; do\n\
+                           \x20 if [ \"$1\" = \"--solution_file\" ]; then cp {} \"$2\"; fi\n\
+                           \x20 shift\n\
+                           done\n", sol.to_str().unwrap())).unwrap();
+        fs::set_permissions(&stub, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut s = SolverHighsCmd::new();
+        s.set_executable(stub.to_str().unwrap());
+        s.set_param("output_flag", SolverParam::StrParam("false".to_string())).unwrap();
+        let (status, solution) = s.solve(&mut Problem::Lp(lp_construct())).unwrap();
+        assert_eq!(status, SolverStatus::Solved);
+        assert_vec_approx_eq!(solution.x,
+                              &vec![1.7142857, 2.8571429, -1.1428571, 0., 0.],
+                              epsilon=1e-6);
+        assert!(solution.basis.is_some());
+    }
+}
@@ -13,6 +13,7 @@ use libc::{c_int, c_void, c_char, c_double};
 #[path = "cipopt.rs"]
 mod cipopt;
 
+use crate::model::model_solver::ModelSolver;
 use crate::solver::base::{Solver, 
                           SolverParam,
                           SolverStatus};
@@ -154,6 +155,8 @@ impl Solver for SolverIpopt {
     }
 }
 
+impl ModelSolver for SolverIpopt {}
+
 extern fn eval_f_cb(n: c_int, 
                     x: *const c_double, 
                     new_x: c_int, 
@@ -1,16 +1,34 @@
 //! Optimization solver interfaces.
 
 pub mod base;
+pub mod cmd;
+pub mod ampl_cmd;
 pub mod clp_cmd;
 pub mod cbc_cmd;
+pub mod glpk_cmd;
+pub mod highs_cmd;
+pub mod scip_cmd;
+pub mod scaled;
 
 #[cfg(feature = "ipopt")] 
 pub mod ipopt;
 
+#[cfg(feature = "sync")]
+pub mod batch;
+
 pub use base::{Solver, SolverParam, SolverStatus};
+pub use cmd::SolverCmd;
 
 #[cfg(feature = "ipopt")] 
 pub use ipopt::SolverIpopt;
 
+#[cfg(feature = "sync")]
+pub use batch::{solve_batch, solve_batch_models};
+
+pub use ampl_cmd::SolverAmplCmd;
 pub use clp_cmd::SolverClpCmd;
-pub use cbc_cmd::SolverCbcCmd;
\ No newline at end of file
+pub use cbc_cmd::SolverCbcCmd;
+pub use glpk_cmd::SolverGlpkCmd;
+pub use highs_cmd::SolverHighsCmd;
+pub use scip_cmd::SolverScipCmd;
+pub use scaled::SolverScaled;
@@ -0,0 +1,282 @@
+//! Solver wrapper that solves scaled optimization problems.
+
+use std::mem;
+use simple_error::SimpleError;
+use std::collections::HashMap;
+
+use crate::matrix::coo::CooMat;
+use crate::problem::lp::ProblemLp;
+use crate::problem::base::{Problem, ProblemSol};
+use crate::problem::scaling::ProblemScaling;
+use crate::model::model_std::ModelStdProb;
+use crate::model::model_solver::ModelSolver;
+use crate::solver::base::{Solver, SolverParam, SolverStatus};
+
+/// Optimization solver that scales problems before passing them to another
+/// solver, and unscales the solutions it returns. See
+/// [ProblemScaling](../../problem/scaling/struct.ProblemScaling.html) for the scaling used.
+/// The parameters are those of the wrapped solver. Standard problems of models are
+/// scaled as well, except for those that the wrapped solver solves from the model
+/// expressions (see `ModelSolver::uses_model_expressions`), which are rejected since
+/// the expressions are not scaled. Hence, models cannot be solved with a scaled
+/// [SolverAmplCmd](../ampl_cmd/struct.SolverAmplCmd.html), nor nonlinear models with a
+/// scaled [SolverScipCmd](../scip_cmd/struct.SolverScipCmd.html).
+pub struct SolverScaled<S: Solver> {
+    solver: S,
+}
+
+impl<S: Solver> SolverScaled<S> {
+
+    /// Creates new scaled solver that wraps the given solver.
+    pub fn new(solver: S) -> Self {
+        Self {
+            solver: solver,
+        }
+    }
+
+    /// Gets reference of wrapped solver.
+    pub fn solver(&self) -> &S { &self.solver }
+
+    /// Gets mutable reference of wrapped solver.
+    pub fn solver_mut(&mut self) -> &mut S { &mut self.solver }
+
+    /// Scales problem, solves scaled problem with given function, restores
+    /// problem, and unscales solution.
+    fn solve_scaled<F>(&self, problem: &mut Problem, solve: F) -> Result<(SolverStatus, ProblemSol), SimpleError>
+        where F: FnOnce(&mut Problem) -> Result<(SolverStatus, ProblemSol), SimpleError> {
+
+        let scaling = ProblemScaling::new(problem);
+
+        // The scaled problem takes the original problem until the solve is done
+        let mut scaled = scaling.scale(mem::replace(problem, empty_problem()));
+        let result = solve(scaled.problem_mut());
+        *problem = scaled.into_original();
+
+        let (status, solution) = result?;
+        Ok((status, scaling.unscale(&solution)))
+    }
+}
+
+impl<S: Solver> Solver for SolverScaled<S> {
+
+    fn get_params(&self) -> &HashMap<String, SolverParam> { self.solver.get_params() }
+
+    fn get_params_mut(&mut self) -> &mut HashMap<String, SolverParam> { self.solver.get_params_mut() }
+
+    fn set_param(&mut self, name: &str, value: SolverParam) -> Result<(), SimpleError> {
+        self.solver.set_param(name, value)
+    }
+
+    fn solve(&self, problem: &mut Problem) -> Result<(SolverStatus, ProblemSol), SimpleError> {
+        self.solve_scaled(problem, |p| self.solver.solve(p))
+    }
+}
+
+impl<S: ModelSolver> ModelSolver for SolverScaled<S> {
+
+    fn solve_std(&self, std_prob: &mut ModelStdProb) -> Result<(SolverStatus, ProblemSol), SimpleError> {
+
+        if self.solver.uses_model_expressions(&std_prob.prob) {
+            return Err(SimpleError::new("scaling not supported for solver that uses model expressions"));
+        }
+
+        // The standard problem holds the scaled problem during the solve
+        let mut problem = mem::replace(&mut std_prob.prob, empty_problem());
+        let result = self.solve_scaled(&mut problem, |p| {
+            mem::swap(p, &mut std_prob.prob);
+            let result = self.solver.solve_std(std_prob);
+            mem::swap(p, &mut std_prob.prob);
+            result
+        });
+        std_prob.prob = problem;
+        result
+    }
+
+    fn uses_model_expressions(&self, problem: &Problem) -> bool {
+        self.solver.uses_model_expressions(problem)
+    }
+}
+
+/// Creates empty problem to hold the place of a problem that is moved.
+fn empty_problem() -> Problem {
+    Problem::Lp(ProblemLp::new(Vec::new(),
+                               CooMat::from_nnz((0, 0), 0),
+                               Vec::new(),
+                               Vec::new(),
+                               Vec::new(),
+                               None))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::collections::HashMap;
+    use simple_error::SimpleError;
+
+    use crate::assert_vec_approx_eq;
+    use crate::matrix::coo::CooMat;
+    use crate::problem::nlp::ProblemNlp;
+    use crate::problem::base::{Problem, ProblemSol};
+    use crate::solver::base::{Solver, SolverParam, SolverStatus};
+    use crate::solver::scaled::SolverScaled;
+    use crate::solver::ampl_cmd::SolverAmplCmd;
+    use crate::solver::scip_cmd::SolverScipCmd;
+    use crate::model::model_std::{ModelStd, ModelStdProb};
+    use crate::model::model_solver::ModelSolver;
+    use crate::model::node_cmp::NodeCmp;
+    use crate::model::node_func::NodeFunc;
+    use crate::model::variable::VariableScalar;
+    use crate::model::model::{Model, Objective};
+
+    // Solver that sets each variable to its upper limit, with upper limit
+    // duals given by the objective function gradient
+    struct SolverUpper {
+        parameters: HashMap<String, SolverParam>,
+    }
+
+    impl Solver for SolverUpper {
+
+        fn get_params(&self) -> &HashMap<String, SolverParam> { &self.parameters }
+        fn get_params_mut(&mut self) -> &mut HashMap<String, SolverParam> { &mut self.parameters }
+
+        fn solve(&self, problem: &mut Problem) -> Result<(SolverStatus, ProblemSol), SimpleError> {
+            let p = match problem {
+                Problem::Nlp(x) => x.as_mut_minlp(),
+                _ => return Err(SimpleError::new("problem type not supported")),
+            };
+            let u = p.u().to_vec();
+            p.evaluate(&u);
+            let mut solution = ProblemSol::new(p.nx(), p.na(), p.nf());
+            solution.x = u;
+            solution.mu = p.gphi().iter().map(|g| -g).collect();
+            Ok((SolverStatus::Solved, solution))
+        }
+    }
+
+    impl ModelSolver for SolverUpper {
+
+        fn solve_std(&self, std_prob: &mut ModelStdProb) -> Result<(SolverStatus, ProblemSol), SimpleError> {
+            let (status, mut solution) = self.solve(&mut std_prob.prob)?;
+            solution.log = Some("solve_std".to_string());
+            Ok((status, solution))
+        }
+    }
+
+    #[test]
+    fn scaled_solve() {
+
+        // minimize   -1e3*x0 - 1e-3*x1 - x0*x1
+        // subject to x <= (2e-3, 5e2)
+        let eval_fn = Box::new(move | phi: &mut f64,
+                                      gphi: &mut Vec<f64>,
+                                      hphi: &mut CooMat<f64>,
+                                      _f: &mut Vec<f64>,
+                                      _j: &mut CooMat<f64>,
+                                      _h: &mut Vec<CooMat<f64>>,
+                                      x: &[f64] | {
+            *phi = -1e3*x[0] - 1e-3*x[1] - x[0]*x[1];
+            gphi[0] = -1e3 - x[1];
+            gphi[1] = -1e-3 - x[0];
+            hphi.data_mut()[0] = -1.;
+        });
+        let mut problem = Problem::Nlp(ProblemNlp::new(CooMat::from_pattern((2, 2), vec![1], vec![0]),
+                                                       CooMat::from_nnz((0, 2), 0),
+                                                       Vec::new(),
+                                                       CooMat::from_nnz((0, 2), 0),
+                                                       Vec::new(),
+                                                       vec![-f64::INFINITY, -f64::INFINITY],
+                                                       vec![2e-3, 5e2],
+                                                       None,
+                                                       eval_fn));
+
+        let solver = SolverScaled::new(SolverUpper { parameters: HashMap::new() });
+        assert!(solver.get_params().is_empty());
+        let (status, solution) = solver.solve(&mut problem).unwrap();
+        assert_eq!(status, SolverStatus::Solved);
+        assert_vec_approx_eq!(solution.x, vec![2e-3, 5e2], epsilon = 1e-12);
+        assert_vec_approx_eq!(solution.mu, vec![1e3 + 5e2, 1e-3 + 2e-3], epsilon = 1e-9);
+
+        // Original problem is restored
+        match &mut problem {
+            Problem::Nlp(x) => {
+                assert_eq!(x.u(), &[2e-3, 5e2]);
+                x.as_mut_minlp().evaluate(&[1., 1.]);
+                assert_eq!(x.as_minlp().phi(), -1e3 - 1e-3 - 1.);
+            },
+            _ => panic!("invalid problem type"),
+        }
+    }
+
+    #[test]
+    fn scaled_solve_std() {
+
+        let x = VariableScalar::new_continuous("x");
+        let y = VariableScalar::new_continuous("y");
+
+        // minimize   -1e3*x - 1e-3*y - x*y
+        // subject to x <= 2e-3, y <= 5e2
+        let mut m = Model::new();
+        m.set_objective(Objective::minimize(&(-1e3*&x - 1e-3*&y - &x*&y)));
+        m.add_constraint(&(&x).leq(2e-3));
+        m.add_constraint(&(&y).leq(5e2));
+
+        let solver = SolverScaled::new(SolverUpper { parameters: HashMap::new() });
+        let mut std_prob = m.std_problem();
+        let (status, solution) = solver.solve_std(&mut std_prob).unwrap();
+        assert_eq!(status, SolverStatus::Solved);
+        assert_eq!(solution.log.as_deref(), Some("solve_std"));
+        let ix = *std_prob.var2index.get(&x).unwrap();
+        let iy = *std_prob.var2index.get(&y).unwrap();
+        assert_vec_approx_eq!(vec![solution.x[ix], solution.x[iy]], vec![2e-3, 5e2], epsilon = 1e-12);
+
+        // Original problem is restored
+        match &std_prob.prob {
+            Problem::Nlp(p) => assert_eq!(p.u()[ix], 2e-3),
+            _ => panic!("invalid problem type"),
+        }
+
+        m.solve(&solver).unwrap();
+        assert_eq!(*m.solver_status().unwrap(), SolverStatus::Solved);
+        assert!((*m.final_primals().get(&y).unwrap() - 5e2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn scaled_set_param() {
+
+        // Wrapped solver that accepts any parameter
+        let mut solver = SolverScaled::new(SolverAmplCmd::new("ipopt"));
+        solver.set_param("max_iter", SolverParam::IntParam(10)).unwrap();
+        match solver.get_param("max_iter") {
+            Some(SolverParam::IntParam(10)) => (),
+            _ => panic!("invalid parameter"),
+        }
+
+        // Wrapped solver that validates parameters
+        let mut solver = SolverScaled::new(SolverScipCmd::new());
+        assert!(solver.set_param("limits/time", SolverParam::FloatParam(5.)).is_ok());
+        assert!(solver.set_param("foo", SolverParam::IntParam(1)).is_err());
+    }
+
+    #[test]
+    fn scaled_solve_std_model_expressions() {
+
+        let x = VariableScalar::new_continuous("x");
+
+        // Solver that always uses model expressions
+        let mut m = Model::new();
+        m.set_objective(Objective::minimize(&(2.*&x)));
+        m.add_constraint(&(&x).geq(1.));
+        let solver = SolverScaled::new(SolverAmplCmd::new("ipopt"));
+        assert!(solver.uses_model_expressions(&m.std_problem().prob));
+        assert_eq!(m.solve(&solver).unwrap_err().to_string(),
+                   "scaling not supported for solver that uses model expressions");
+
+        // Solver that uses model expressions of nonlinear problems
+        let solver = SolverScaled::new(SolverScipCmd::new());
+        assert!(!solver.uses_model_expressions(&m.std_problem().prob));
+        m.set_objective(Objective::minimize(&(&x).cos()));
+        assert!(solver.uses_model_expressions(&m.std_problem().prob));
+        assert_eq!(m.solve(&solver).unwrap_err().to_string(),
+                   "scaling not supported for solver that uses model expressions");
+    }
+}
@@ -0,0 +1,443 @@
+//! SCIP solver interface.
+
+use std::fs::File;
+use std::io::prelude::*;
+use simple_error::SimpleError;
+use std::io::{self, BufReader, BufWriter};
+use std::collections::HashMap;
+
+use crate::model::node::Node;
+use crate::model::node_base::NodeBase;
+use crate::model::model_std::ModelStdProb;
+use crate::model::model_solver::ModelSolver;
+use crate::solver::base::{Solver,
+                          SolverParam,
+                          SolverStatus};
+use crate::solver::cmd::{self, SolverCmd, SolverCmdCancel, SolverCmdDir, SolverCmdExecutable};
+use crate::problem::base::{Problem,
+                           ProblemSol};
+use crate::problem::milp::ProblemMilpIO;
+
+/// Interface to the optimization solver SCIP
+/// that utilzes the command-line tool "scip".
+///
+/// The command-line tool "scip" needs to be on the system path, or its location
+/// needs to be given with [set_executable](../cmd/trait.SolverCmd.html#method.set_executable)
+/// or the environment variable "NUMOPT_SCIP".
+///
+/// It can solve problems of type [ProblemLp](../../problem/lp/struct.ProblemLp.html)
+/// and [ProblemMilp](../../problem/milp/struct.ProblemMilp.html), which are passed to SCIP
+/// as LP files. Problems of type [ProblemNlp](../../problem/nlp/struct.ProblemNlp.html)
+/// and [ProblemMinlp](../../problem/minlp/struct.ProblemMinlp.html) can only be solved
+/// through [Model::solve](../../model/model/struct.Model.html#method.solve), since
+/// their expressions are needed to write a CIP file. Dual values are not
+/// extracted.
+///
+/// Parameters (named as SCIP parameters and passed to SCIP in batch mode
+/// as "set limits time 10"-style commands):
+/// * "limits/time" (float): time limit in seconds.
+/// * "limits/gap" (float): relative gap at which to stop.
+/// * "limits/nodes" (integer): node limit (-1 for no limit).
+/// * "display/verblevel" (integer): verbosity level (0 to 5).
+/// * "timeout" (float): wall-clock time in seconds after which the solver process is killed
+///   (not passed to SCIP).
+pub struct SolverScipCmd {
+    executable: SolverCmdExecutable,
+    cancel: SolverCmdCancel,
+    parameters: HashMap<String, SolverParam>,
+}
+
+impl SolverScipCmd {
+
+    // Creates solver instance.
+    pub fn new() -> Self {
+
+        let mut parameters: HashMap<String, SolverParam> = HashMap::new();
+        parameters.insert("limits/time".to_string(), SolverParam::FloatParam(1e20));
+        parameters.insert("limits/gap".to_string(), SolverParam::FloatParam(0.));
+        parameters.insert("limits/nodes".to_string(), SolverParam::IntParam(-1));
+        parameters.insert("display/verblevel".to_string(), SolverParam::IntParam(4));
+        parameters.insert("timeout".to_string(), SolverParam::FloatParam(f64::INFINITY));
+
+        Self {
+            executable: SolverCmdExecutable::new("scip", "NUMOPT_SCIP", &["-v"]),
+            cancel: SolverCmdCancel::new(),
+            parameters: parameters,
+        }
+    }
+
+    /// Writes expression in SCIP's CIP format.
+    fn write_cip_expr(node: &Node, var2index: &HashMap<Node, usize>) -> String {
+        let args = node.arguments();
+        match node {
+            Node::ConstantScalar(x) => format!("({})", x.value()),
+            Node::VariableScalar(_) => format!("<x_{}>", var2index.get(node).unwrap()),
+            Node::FunctionAdd(_) => {
+                let terms: Vec<String> = args.iter()
+                                             .map(|a| Self::write_cip_expr(a, var2index))
+                                             .collect();
+                format!("({})", terms.join(" + "))
+            },
+            Node::FunctionMul(_) => format!("({} * {})",
+                                            Self::write_cip_expr(args[0], var2index),
+                                            Self::write_cip_expr(args[1], var2index)),
+            Node::FunctionDiv(_) => format!("({} / {})",
+                                            Self::write_cip_expr(args[0], var2index),
+                                            Self::write_cip_expr(args[1], var2index)),
+            Node::FunctionCos(_) => format!("cos({})", Self::write_cip_expr(args[0], var2index)),
+            Node::FunctionSin(_) => format!("sin({})", Self::write_cip_expr(args[0], var2index)),
+        }
+    }
+
+    /// Writes standard problem of an optimization model to a CIP file.
+    /// Variables and constraints are named "x_i", "c_i" (linear), and "f_i" (nonlinear).
+    /// The objective function is represented with an auxiliary variable "obj"
+    /// and the constraint "phi - obj <= 0".
+    pub fn write_cip_file(fname: &str, std_prob: &mut ModelStdProb) -> io::Result<()> {
+
+        let p = match &mut std_prob.prob {
+            Problem::Minlp(x) => x,
+            Problem::Nlp(x) => x.as_mut_minlp(),
+            Problem::Milp(x) => x.as_mut_minlp(),
+            Problem::Lp(x) => x.as_mut_milp().as_mut_minlp(),
+        };
+        let var2index = &std_prob.var2index;
+
+        let f = File::create(fname)?;
+        let mut w = BufWriter::new(f);
+
+        // Avoids writing negative zeros
+        let num = |v: f64| if v == 0. { 0. } else { v };
+
+        // Statistics
+        writeln!(w, "STATISTICS")?;
+        writeln!(w, "  Problem name     : numopt")?;
+        writeln!(w, "OBJECTIVE")?;
+        writeln!(w, "  Sense            : minimize")?;
+
+        // Variables
+        writeln!(w, "VARIABLES")?;
+        for i in 0..p.nx() {
+            writeln!(w, "  [{}] <x_{}>: obj=0, original bounds=[{},{}]",
+                     if p.p()[i] { "integer" } else { "continuous" },
+                     i,
+                     num(p.l()[i]),
+                     num(p.u()[i]))?;
+        }
+        writeln!(w, "  [continuous] <obj>: obj=1, original bounds=[-inf,+inf]")?;
+
+        // Constraints
+        writeln!(w, "CONSTRAINTS")?;
+        let mut a = p.a().to_csr();
+        a.sum_duplicates();
+        for i in 0..a.rows() {
+            write!(w, "  [linear] <c_{}>:", i)?;
+            for k in a.indptr()[i]..a.indptr()[i+1] {
+                write!(w, " {:+}<x_{}>", a.data()[k], a.indices()[k])?;
+            }
+            writeln!(w, " == {};", num(p.b()[i]))?;
+        }
+        for (i, e) in std_prob.f.iter().enumerate() {
+            writeln!(w, "  [nonlinear] <f_{}>: {} == 0;", i, Self::write_cip_expr(e, var2index))?;
+        }
+        writeln!(w, "  [nonlinear] <phi>: {} - <obj> <= 0;",
+                 Self::write_cip_expr(&std_prob.phi, var2index))?;
+
+        // End
+        writeln!(w, "END")?;
+
+        w.flush()?;
+
+        Ok(())
+    }
+
+    /// Reads SCIP solution file.
+    pub fn read_sol_file(fname: &str, nx: usize, na: usize, nf: usize) -> io::Result<(SolverStatus, ProblemSol)> {
+
+        let mut status = SolverStatus::Error;
+        let mut solution = ProblemSol::new(nx, na, nf);
+        let f = match File::open(fname) {
+            Ok(ff) => ff,
+            Err(_e) => return Ok((status, solution))
+        };
+        let r = BufReader::new(f);
+        let e = || io::Error::other("bad solution file");
+
+        for l in r.lines() {
+            let line = l?;
+            let line = line.trim();
+
+            // Status
+            if let Some(rest) = line.strip_prefix("solution status:") {
+                status = match rest.trim() {
+                    "optimal solution found" => SolverStatus::Solved,
+                    "gap limit reached" => SolverStatus::Solved,
+                    "infeasible" => SolverStatus::Infeasible,
+                    "time limit reached" => SolverStatus::TimeLimit,
+                    "node limit reached" => SolverStatus::IterationLimit,
+                    "total node limit reached" => SolverStatus::IterationLimit,
+                    _ => SolverStatus::Unknown,
+                };
+                continue;
+            }
+
+            // Variable values
+            let mut iter = line.split_ascii_whitespace();
+            let name = match iter.next() {
+                Some(s) => s,
+                None => continue,
+            };
+            if !name.starts_with("x_") {
+                continue;
+            }
+            let index: usize = match name[2..].parse() {
+                Ok(n) if n < nx => n,
+                _ => return Err(e())
+            };
+            solution.x[index] = match iter.next().map(|s| s.parse()) {
+                Some(Ok(v)) => v,
+                _ => return Err(e())
+            };
+        }
+
+        Ok((status, solution))
+    }
+
+    /// Writes input file, calls SCIP, and reads its solution.
+    fn run<F>(&self, suffix: &str, nx: usize, na: usize, nf: usize, write_input: F)
+              -> Result<(SolverStatus, ProblemSol), SimpleError>
+              where F: FnOnce(&str) -> io::Result<()> {
+
+        // Parameters
+        let verblevel = match self.get_param("display/verblevel") {
+            Some(SolverParam::IntParam(i)) => *i,
+            _ => return Err(SimpleError::new("unable to get parameter display/verblevel"))
+        };
+
+        // Working directory and filenames
+        let dir = SolverCmdDir::new("scip")?;
+        let input_filename = dir.file(&format!("problem{}", suffix));
+        let output_filename = dir.file("problem.sol");
+
+        // Write input file
+        match write_input(&input_filename) {
+            Ok(()) => (),
+            Err(_e) => return Err(SimpleError::new("failed to write input file")),
+        };
+
+        // Batch commands
+        let mut names: Vec<&String> = self.parameters.keys().filter(|n| *n != "timeout").collect();
+        names.sort();
+        let mut commands: Vec<String> = Vec::new();
+        for name in names {
+            let value = match self.parameters.get(name).unwrap() {
+                SolverParam::IntParam(i) => format!("{}", i),
+                SolverParam::FloatParam(f) => format!("{}", f),
+                SolverParam::StrParam(s) => s.clone(),
+            };
+            commands.push(format!("set {} {}", name.replace("/", " "), value));
+        }
+        commands.push(format!("read {}", input_filename));
+        commands.push("optimize".to_string());
+        commands.push(format!("write solution {}", output_filename));
+        commands.push("quit".to_string());
+
+        // Call SCIP command
+        let output = cmd::run(self.executable.command().args(["-c", &commands.join(" ")]),
+                              verblevel > 0,
+                              self.timeout(),
+                              &self.cancel)?;
+
+        // Read output file
+        let (status, mut solution) = output.solution(Self::read_sol_file(&output_filename, nx, na, nf),
+                                                     || ProblemSol::new(nx, na, nf),
+                                                     "failed to read scip solution file")?;
+        solution.log = Some(output.log());
+
+        // All good
+        Ok((status, solution))
+    }
+}
+
+impl SolverCmd for SolverScipCmd {
+
+    fn executable(&self) -> &SolverCmdExecutable { &self.executable }
+    fn executable_mut(&mut self) -> &mut SolverCmdExecutable { &mut self.executable }
+    fn cancel_token(&self) -> &SolverCmdCancel { &self.cancel }
+    fn cancel_token_mut(&mut self) -> &mut SolverCmdCancel { &mut self.cancel }
+}
+
+impl Solver for SolverScipCmd {
+
+    fn get_params(&self) -> &HashMap<String, SolverParam> { &self.parameters }
+    fn get_params_mut(&mut self) -> &mut HashMap<String, SolverParam> { &mut self.parameters }
+
+    fn solve(&self, problem: &mut Problem) -> Result<(SolverStatus, ProblemSol), SimpleError> {
+
+        // Get problem
+        let p  = match problem {
+            Problem::Milp(x) => x,
+            Problem::Lp(x) => x.as_mut_milp(),
+            _ => return Err(SimpleError::new("problem type requires model expressions"))
+        };
+
+        self.run(".lp", p.nx(), p.na(), 0, |fname| p.write_to_lp_file(fname))
+    }
+}
+
+impl ModelSolver for SolverScipCmd {
+
+    fn solve_std(&self, std_prob: &mut ModelStdProb) -> Result<(SolverStatus, ProblemSol), SimpleError> {
+
+        // Sizes
+        let (nx, na, nf) = match &std_prob.prob {
+            Problem::Nlp(x) => (x.nx(), x.na(), x.nf()),
+            Problem::Minlp(x) => (x.nx(), x.na(), x.nf()),
+            _ => return self.solve(&mut std_prob.prob),
+        };
+
+        self.run(".cip", nx, na, nf, |fname| Self::write_cip_file(fname, std_prob))
+    }
+
+    fn uses_model_expressions(&self, problem: &Problem) -> bool {
+        matches!(problem, Problem::Nlp(_) | Problem::Minlp(_))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::fs;
+    use tempfile::{NamedTempFile, TempDir};
+    #[cfg(unix)]
+    use std::os::unix::fs::PermissionsExt;
+
+    use crate::model::node_func::NodeFunc;
+    use crate::model::node_cmp::NodeCmp;
+    use crate::model::variable::VariableScalar;
+    use crate::model::model::{Model, Objective};
+    use crate::model::model_std::ModelStd;
+    use crate::solver::base::{Solver, SolverParam, SolverStatus};
+    use crate::solver::cmd::SolverCmd;
+    use crate::solver::scip_cmd::SolverScipCmd;
+    use crate::assert_vec_approx_eq;
+
+    fn minlp_construct() -> (Model, Vec<crate::model::node::Node>) {
+
+        // min   x*y + cos(z)
+        // s.t.  x + y == 3
+        //       x*z == 1
+        //       0 <= x <= 4, integer
+        //       0 <= y <= 4
+
+        let x = VariableScalar::new_integer("x");
+        let y = VariableScalar::new_continuous("y");
+        let z = VariableScalar::new_continuous("z");
+
+        let mut m = Model::new();
+        m.set_objective(Objective::minimize(&(&x*&y + z.cos())));
+        m.add_constraint(&(&x + &y).equal(3.));
+        m.add_constraint(&(&x*&z).equal(1.));
+        m.add_constraint(&x.geq(0.));
+        m.add_constraint(&x.leq(4.));
+        m.add_constraint(&y.geq(0.));
+        m.add_constraint(&y.leq(4.));
+        (m, vec![x, y, z])
+    }
+
+    #[test]
+    fn scip_read_sol_file() {
+
+        let f = NamedTempFile::new().unwrap();
+        let fname = f.path().to_str().unwrap();
+
+        fs::write(fname,
+                  "solution status: optimal solution found\n\
+                   objective value:                                 -1.5\n\
+                   x_0                                                 1.5 \t(obj:0)\n\
+                   x_2                                                  -2 \t(obj:0)\n\
+                   obj                                                -1.5 \t(obj:1)\n").unwrap();
+        let (status, solution) = SolverScipCmd::read_sol_file(fname, 3, 1, 1).unwrap();
+        assert_eq!(status, SolverStatus::Solved);
+        assert_vec_approx_eq!(solution.x, &vec![1.5, 0., -2.], epsilon=0.);
+        assert_eq!(solution.nu.len(), 1);
+
+        fs::write(fname,
+                  "solution status: time limit reached\n\
+                   objective value:                                    3\n\
+                   x_1                                                   3 \t(obj:1)\n").unwrap();
+        let (status, solution) = SolverScipCmd::read_sol_file(fname, 2, 0, 0).unwrap();
+        assert_eq!(status, SolverStatus::TimeLimit);
+        assert_vec_approx_eq!(solution.x, &vec![0., 3.], epsilon=0.);
+
+        fs::write(fname,
+                  "solution status: infeasible\n\
+                   no solution available\n").unwrap();
+        let (status, _solution) = SolverScipCmd::read_sol_file(fname, 2, 0, 0).unwrap();
+        assert_eq!(status, SolverStatus::Infeasible);
+    }
+
+    #[test]
+    fn scip_write_cip_file() {
+
+        let (m, vars) = minlp_construct();
+        let mut std_prob = m.std_problem();
+        let f = NamedTempFile::new().unwrap();
+        let fname = f.path().to_str().unwrap();
+        SolverScipCmd::write_cip_file(fname, &mut std_prob).unwrap();
+
+        let ix = *std_prob.var2index.get(&vars[0]).unwrap();
+        let iy = *std_prob.var2index.get(&vars[1]).unwrap();
+        let iz = *std_prob.var2index.get(&vars[2]).unwrap();
+        let content = fs::read_to_string(fname).unwrap();
+        assert!(content.starts_with("STATISTICS\n"));
+        assert!(content.ends_with("END\n"));
+        assert!(content.contains(&format!("  [integer] <x_{}>: obj=0, original bounds=[0,4]\n", ix)));
+        assert!(content.contains(&format!("  [continuous] <x_{}>: obj=0, original bounds=[0,4]\n", iy)));
+        assert!(content.contains(&format!("  [continuous] <x_{}>: obj=0, original bounds=[-100000000,100000000]\n", iz)));
+        assert!(content.contains("  [continuous] <obj>: obj=1, original bounds=[-inf,+inf]\n"));
+        assert!(content.contains(" == 3;\n"));
+        assert!(content.contains(&format!("  [nonlinear] <f_0>: ((<x_{}> * <x_{}>) + (-1)) == 0;\n", ix, iz)));
+        assert!(content.contains(&format!("  [nonlinear] <phi>: ((<x_{}> * <x_{}>) + cos(<x_{}>)) - <obj> <= 0;\n", ix, iy, iz)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn scip_solve_minlp_stub() {
+
+        let (mut m, vars) = minlp_construct();
+        let std_prob = m.std_problem();
+        let ix = *std_prob.var2index.get(&vars[0]).unwrap();
+        let iy = *std_prob.var2index.get(&vars[1]).unwrap();
+        let iz = *std_prob.var2index.get(&vars[2]).unwrap();
+
+        // Stub scip that writes a fixed solution
+        let dir = TempDir::new().unwrap();
+        let sol = dir.path().join("minlp.sol");
+        fs::write(&sol, format!("solution status: optimal solution found\n\
+                                 objective value: 2.5403023\n\
+                                 x_{} 1 \t(obj:0)\n\
+                                 x_{} 2 \t(obj:0)\n\
+                                 x_{} 1 \t(obj:0)\n\
+                                 obj 2.5403023 \t(obj:1)\n", ix, iy, iz)).unwrap();
+        let stub = dir.path().join("scip");
+        fs::write(&stub,
+                  format!("#!/bin/sh\n\
+                           out=$(echo \"$2\" | sed 's/.*write solution \\([^ ]*\\).*/\\1/')\n\
+                           cp {} \"$out\"\n", sol.to_str().unwrap())).unwrap();
+        fs::set_permissions(&stub, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut s = SolverScipCmd::new();
+        s.set_executable(stub.to_str().unwrap());
+        s.set_param("display/verblevel", SolverParam::IntParam(0)).unwrap();
+        m.solve(&s).unwrap();
+        assert_eq!(*m.solver_status().unwrap(), SolverStatus::Solved);
+        let final_primals = m.final_primals();
+        assert_eq!(*final_primals.get(&vars[0]).unwrap(), 1.);
+        assert_eq!(*final_primals.get(&vars[1]).unwrap(), 2.);
+        assert_eq!(*final_primals.get(&vars[2]).unwrap(), 1.);
+
+        // Expressions are needed for Minlp
+        assert!(s.solve(&mut m.std_problem().prob).is_err());
+    }
+}
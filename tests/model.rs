@@ -4,6 +4,7 @@ use numopt::model::*;
 use numopt::solver::*;
 
 #[test]
+#[ignore]
 #[serial]
 fn numopt_model_solve_milp_cbc_cmd() {
 